@@ -0,0 +1,685 @@
+//! Opt-in localhost API for editors and scripts. A WebSocket streams the app's
+//! `transcription-update`, `transcript-ready`, `dictation-status`,
+//! `transcription-error` and `output-error` events as JSON and accepts start/stop commands; the same
+//! commands are available as plain HTTP endpoints, alongside an
+//! OpenAI-compatible transcription endpoint (`openai`). The server binds to
+//! 127.0.0.1 only and every request needs the configured token.
+
+pub mod openai;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{DefaultBodyLimit, Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use openai::FileTranscriber;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+pub const DEFAULT_PORT: u16 = 7837;
+
+/// App events forwarded to API clients.
+pub const FORWARDED_EVENTS: [&str; 5] = [
+    "transcription-update",
+    "transcript-ready",
+    "dictation-status",
+    "transcription-error",
+    "output-error",
+];
+
+/// One message to a WebSocket client: an app event name and its payload.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiEvent {
+    pub event: String,
+    pub payload: Value,
+}
+
+/// Fans app events out to connected clients and remembers the last
+/// dictation status, so new clients and `/v1/status` can report it.
+pub struct EventHub {
+    sender: broadcast::Sender<ApiEvent>,
+    status: Mutex<String>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(256).0,
+            status: Mutex::new("idle".to_string()),
+        }
+    }
+
+    pub fn publish(&self, event: &str, payload: Value) {
+        if event == "dictation-status" {
+            if let Some(status) = payload.as_str() {
+                *self.status.lock().unwrap() = status.to_string();
+            }
+        }
+        // No subscribers is fine: nobody is connected.
+        let _ = self.sender.send(ApiEvent {
+            event: event.to_string(),
+            payload,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ApiEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn status(&self) -> String {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Dictation controls exposed through the API. The app implements this over
+/// `AppState`; calls may block, so the server runs them off the async runtime.
+pub trait DictationControl: Send + Sync {
+    fn start(&self) -> Result<(), String>;
+    fn stop(&self) -> Result<(), String>;
+    /// Returns whether dictation is running afterwards.
+    fn toggle(&self) -> Result<bool, String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiCommand {
+    Start,
+    Stop,
+    Toggle,
+    Status,
+}
+
+#[derive(Clone)]
+pub struct ApiContext {
+    pub token: String,
+    pub hub: Arc<EventHub>,
+    pub control: Arc<dyn DictationControl>,
+    pub transcriber: Arc<dyn FileTranscriber>,
+}
+
+impl ApiContext {
+    /// Run a command and return the resulting status.
+    async fn run(&self, command: ApiCommand) -> Result<Value, String> {
+        let control = self.control.clone();
+        tokio::task::spawn_blocking(move || match command {
+            ApiCommand::Start => control.start(),
+            ApiCommand::Stop => control.stop(),
+            ApiCommand::Toggle => control.toggle().map(|_| ()),
+            ApiCommand::Status => Ok(()),
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(json!({ "status": self.hub.status() }))
+    }
+}
+
+/// The app's event hub and running server, kept in `AppState`.
+#[derive(Default)]
+pub struct ApiHost {
+    pub hub: Arc<EventHub>,
+    pub server: Mutex<Option<ApiServer>>,
+}
+
+/// Handle to a running server; dropping it shuts the server down and closes
+/// open WebSocket streams.
+pub struct ApiServer {
+    addr: SocketAddr,
+    shutdown: CancellationToken,
+}
+
+impl ApiServer {
+    /// Bind 127.0.0.1:`port` (0 picks a free port) and return the handle plus
+    /// the future that serves requests, to be spawned on the async runtime.
+    pub fn bind(
+        port: u16,
+        context: ApiContext,
+    ) -> Result<(Self, impl Future<Output = ()> + Send), String> {
+        if context.token.is_empty() {
+            return Err("API token is not set".to_string());
+        }
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| format!("Failed to bind 127.0.0.1:{}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+
+        let shutdown = CancellationToken::new();
+        let app = router(context, shutdown.clone());
+        let stopped = shutdown.clone();
+        let serve = async move {
+            let result = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => axum::serve(listener, app)
+                    .with_graceful_shutdown(async move { stopped.cancelled().await })
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = result {
+                eprintln!("api: server stopped: {}", e);
+            }
+        };
+        Ok((Self { addr, shutdown }, serve))
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+#[derive(Clone)]
+struct Shared {
+    context: ApiContext,
+    shutdown: CancellationToken,
+}
+
+fn router(context: ApiContext, shutdown: CancellationToken) -> Router {
+    let shared = Shared { context, shutdown };
+    Router::new()
+        .route("/v1/status", get(status))
+        .route("/v1/dictation/{command}", post(command))
+        .route("/v1/events", get(events))
+        .route(
+            "/v1/audio/transcriptions",
+            post(openai::transcriptions).layer(DefaultBodyLimit::max(openai::MAX_UPLOAD_BYTES)),
+        )
+        .layer(middleware::from_fn_with_state(shared.clone(), authorize))
+        .with_state(shared)
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// Accept `Authorization: Bearer <token>` or, for WebSocket clients that
+/// cannot set headers, a `token` query parameter.
+async fn authorize(State(shared): State<Shared>, request: Request, next: Next) -> Response {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let query = request
+        .uri()
+        .query()
+        .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("token=")));
+    match bearer.or(query) {
+        Some(token) if tokens_match(token, &shared.context.token) => next.run(request).await,
+        _ => error(StatusCode::UNAUTHORIZED, "Missing or invalid API token"),
+    }
+}
+
+/// Compare without returning early on the first mismatched byte.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn status(State(shared): State<Shared>) -> Json<Value> {
+    Json(json!({ "status": shared.context.hub.status() }))
+}
+
+async fn command(State(shared): State<Shared>, Path(command): Path<String>) -> Response {
+    let Ok(command) = serde_json::from_value::<ApiCommand>(Value::String(command.clone())) else {
+        return error(
+            StatusCode::NOT_FOUND,
+            format!("Unknown command: {}", command),
+        );
+    };
+    match shared.context.run(command).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => error(StatusCode::CONFLICT, e),
+    }
+}
+
+async fn events(State(shared): State<Shared>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| stream(socket, shared))
+}
+
+#[derive(Deserialize)]
+struct CommandMessage {
+    command: ApiCommand,
+}
+
+async fn stream(mut socket: WebSocket, shared: Shared) {
+    let hub = shared.context.hub.clone();
+    let mut events = hub.subscribe();
+    let hello = ApiEvent {
+        event: "dictation-status".to_string(),
+        payload: json!(hub.status()),
+    };
+    if send(&mut socket, &hello).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            _ = shared.shutdown.cancelled() => break,
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if send(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = command_reply(&shared.context, &text).await;
+                    if send(&mut socket, &reply).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Reply to a `{"command": "..."}` message with a `command-result` event.
+async fn command_reply(context: &ApiContext, text: &str) -> ApiEvent {
+    let payload = match serde_json::from_str::<CommandMessage>(text) {
+        Ok(message) => match context.run(message.command).await {
+            Ok(mut result) => {
+                result["command"] = json!(message.command);
+                result["ok"] = json!(true);
+                result
+            }
+            Err(e) => json!({ "command": message.command, "ok": false, "error": e }),
+        },
+        Err(e) => json!({ "ok": false, "error": format!("Invalid command: {}", e) }),
+    };
+    ApiEvent {
+        event: "command-result".to_string(),
+        payload,
+    }
+}
+
+async fn send(socket: &mut WebSocket, event: &ApiEvent) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).unwrap_or_default();
+    socket.send(Message::Text(text.into())).await
+}
+
+/// A random 128-bit token, hex encoded.
+pub fn generate_token() -> Result<String, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to generate API token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio_tungstenite::tungstenite;
+
+    /// Publishes status events like the app does and records calls.
+    struct FakeControl {
+        hub: Arc<EventHub>,
+        running: AtomicBool,
+    }
+
+    impl DictationControl for FakeControl {
+        fn start(&self) -> Result<(), String> {
+            if self.running.swap(true, Ordering::SeqCst) {
+                return Ok(());
+            }
+            self.hub.publish("dictation-status", json!("listening"));
+            Ok(())
+        }
+
+        fn stop(&self) -> Result<(), String> {
+            self.running.store(false, Ordering::SeqCst);
+            self.hub.publish("dictation-status", json!("idle"));
+            Ok(())
+        }
+
+        fn toggle(&self) -> Result<bool, String> {
+            if self.running.load(Ordering::SeqCst) {
+                self.stop().map(|_| false)
+            } else {
+                Err("No model loaded".to_string())
+            }
+        }
+    }
+
+    /// Echoes the request back as one segment per second of audio.
+    struct FakeTranscriber;
+
+    impl FileTranscriber for FakeTranscriber {
+        fn transcribe(
+            &self,
+            audio: &[f32],
+            language: &str,
+            prompt: Option<&str>,
+        ) -> Result<Vec<openai::Segment>, String> {
+            let seconds = audio.len() / 16000;
+            Ok((0..seconds)
+                .map(|i| openai::Segment {
+                    start: i as f64,
+                    end: (i + 1) as f64,
+                    text: format!("{} {}", language, prompt.unwrap_or("-")),
+                })
+                .collect())
+        }
+
+        fn default_language(&self) -> String {
+            "en".to_string()
+        }
+    }
+
+    fn context(token: &str) -> (ApiContext, Arc<EventHub>) {
+        let hub = Arc::new(EventHub::new());
+        let context = ApiContext {
+            token: token.to_string(),
+            hub: hub.clone(),
+            control: Arc::new(FakeControl {
+                hub: hub.clone(),
+                running: AtomicBool::new(false),
+            }),
+            transcriber: Arc::new(FakeTranscriber),
+        };
+        (context, hub)
+    }
+
+    fn serve() -> (ApiServer, Arc<EventHub>) {
+        let (context, hub) = context("secret");
+        let (server, future) = ApiServer::bind(0, context).unwrap();
+        tokio::spawn(future);
+        (server, hub)
+    }
+
+    async fn next_event(
+        ws: &mut tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+    ) -> Value {
+        loop {
+            match ws.next().await.unwrap().unwrap() {
+                tungstenite::Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    // --- Hub Tests ---
+
+    #[test]
+    fn test_hub_tracks_status() {
+        let hub = EventHub::new();
+        assert_eq!(hub.status(), "idle");
+        let mut rx = hub.subscribe();
+        hub.publish("dictation-status", json!("listening"));
+        hub.publish(
+            "transcription-update",
+            json!({ "text": "hi", "is_partial": true }),
+        );
+        assert_eq!(hub.status(), "listening");
+        assert_eq!(rx.try_recv().unwrap().payload, json!("listening"));
+        assert_eq!(rx.try_recv().unwrap().event, "transcription-update");
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abd", "abc"));
+        assert!(!tokens_match("ab", "abc"));
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let a = generate_token().unwrap();
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, generate_token().unwrap());
+    }
+
+    #[test]
+    fn test_bind_requires_token() {
+        let (context, _) = context("");
+        assert!(ApiServer::bind(0, context).is_err());
+    }
+
+    // --- HTTP Tests ---
+
+    #[tokio::test]
+    async fn test_http_requires_token() {
+        let (server, _) = serve();
+        let url = format!("http://{}/v1/status", server.addr());
+        let client = reqwest::Client::new();
+
+        let resp = client.get(&url).send().await.unwrap();
+        assert_eq!(resp.status(), 401);
+        let resp = client.get(&url).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(resp.status(), 401);
+
+        let resp = client.get(&url).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body, json!({ "status": "idle" }));
+    }
+
+    #[tokio::test]
+    async fn test_http_commands() {
+        let (server, _) = serve();
+        let client = reqwest::Client::new();
+        let post = |command: &str| {
+            client
+                .post(format!("http://{}/v1/dictation/{}", server.addr(), command))
+                .bearer_auth("secret")
+                .send()
+        };
+
+        let body: Value = post("start").await.unwrap().json().await.unwrap();
+        assert_eq!(body, json!({ "status": "listening" }));
+        let body: Value = post("stop").await.unwrap().json().await.unwrap();
+        assert_eq!(body, json!({ "status": "idle" }));
+
+        let resp = post("toggle").await.unwrap();
+        assert_eq!(resp.status(), 409);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["error"], "No model loaded");
+
+        assert_eq!(post("explode").await.unwrap().status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_server_stops_when_dropped() {
+        let (server, _) = serve();
+        let addr = server.addr();
+        drop(server);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(reqwest::get(format!("http://{}/v1/status", addr))
+            .await
+            .is_err());
+    }
+
+    // --- Transcription Endpoint Tests ---
+
+    /// Build a multipart body by hand: a two-second WAV plus text fields.
+    fn upload(fields: &[(&str, &str)]) -> (String, Vec<u8>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.wav");
+        crate::audio::wav::write_wav(&path, &vec![0.0; 32000], 16000).unwrap();
+        let boundary = "whispertype-test-boundary";
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(
+                format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+                )
+                .as_bytes(),
+            );
+        }
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"clip.wav\"\r\n\
+                 Content-Type: audio/wav\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&std::fs::read(&path).unwrap());
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+        (format!("multipart/form-data; boundary={boundary}"), body)
+    }
+
+    #[tokio::test]
+    async fn test_openai_transcription_upload() {
+        let (server, _) = serve();
+        let url = format!("http://{}/v1/audio/transcriptions", server.addr());
+        let client = reqwest::Client::new();
+        let send = |fields: &[(&str, &str)]| {
+            let (content_type, body) = upload(fields);
+            client
+                .post(&url)
+                .bearer_auth("secret")
+                .header(header::CONTENT_TYPE, content_type)
+                .body(body)
+                .send()
+        };
+
+        let resp = send(&[("model", "whisper-1")]).await.unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body, json!({ "text": "en - en -" }));
+
+        let resp = send(&[
+            ("model", "whisper-1"),
+            ("language", "de"),
+            ("prompt", "Kenobi"),
+            ("response_format", "srt"),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(
+            resp.text().await.unwrap(),
+            "1\n00:00:00,000 --> 00:00:01,000\nde Kenobi\n\n\
+             2\n00:00:01,000 --> 00:00:02,000\nde Kenobi\n\n"
+        );
+
+        let resp = send(&[("response_format", "verbose_json")]).await.unwrap();
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["duration"], 2.0);
+        assert_eq!(body["language"], "en");
+        assert_eq!(body["segments"].as_array().unwrap().len(), 2);
+
+        let resp = send(&[("response_format", "xml")]).await.unwrap();
+        assert_eq!(resp.status(), 400);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+    }
+
+    #[tokio::test]
+    async fn test_openai_transcription_requires_file() {
+        let (server, _) = serve();
+        let resp = reqwest::Client::new()
+            .post(format!("http://{}/v1/audio/transcriptions", server.addr()))
+            .bearer_auth("secret")
+            .header(
+                header::CONTENT_TYPE,
+                "multipart/form-data; boundary=b",
+            )
+            .body("--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n--b--\r\n")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 400);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["error"]["message"], "Missing 'file' field");
+    }
+
+    // --- WebSocket Tests ---
+
+    #[tokio::test]
+    async fn test_websocket_rejects_bad_token() {
+        let (server, _) = serve();
+        let url = format!("ws://{}/v1/events?token=nope", server.addr());
+        assert!(tokio_tungstenite::connect_async(url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_streams_events_and_accepts_commands() {
+        let (server, hub) = serve();
+        let url = format!("ws://{}/v1/events?token=secret", server.addr());
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        assert_eq!(
+            next_event(&mut ws).await,
+            json!({ "event": "dictation-status", "payload": "idle" })
+        );
+
+        hub.publish(
+            "transcription-update",
+            json!({ "text": "hello", "is_partial": false }),
+        );
+        assert_eq!(
+            next_event(&mut ws).await,
+            json!({
+                "event": "transcription-update",
+                "payload": { "text": "hello", "is_partial": false }
+            })
+        );
+
+        ws.send(tungstenite::Message::Text(r#"{"command":"start"}"#.into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_event(&mut ws).await,
+            json!({
+                "event": "command-result",
+                "payload": { "command": "start", "ok": true, "status": "listening" }
+            })
+        );
+        // The status change is broadcast to every client, including this one.
+        assert_eq!(
+            next_event(&mut ws).await,
+            json!({ "event": "dictation-status", "payload": "listening" })
+        );
+
+        ws.send(tungstenite::Message::Text(r#"{"command":"fly"}"#.into()))
+            .await
+            .unwrap();
+        let reply = next_event(&mut ws).await;
+        assert_eq!(reply["payload"]["ok"], false);
+
+        drop(server);
+        let closed = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+            while let Some(Ok(message)) = ws.next().await {
+                if message.is_close() {
+                    return true;
+                }
+            }
+            true
+        })
+        .await;
+        assert_eq!(closed, Ok(true), "open streams end with the server");
+    }
+}
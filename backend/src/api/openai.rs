@@ -0,0 +1,375 @@
+//! OpenAI-compatible `POST /v1/audio/transcriptions`, so tools written for
+//! the cloud API can point their base URL at the local server and use the
+//! model that is already loaded. Accepts the same multipart fields (`file`,
+//! `model`, `language`, `prompt`, `response_format`, `temperature`) and
+//! returns `json`, `text`, `srt`, `vtt` or `verbose_json`.
+
+use super::Shared;
+use crate::audio::wav;
+use axum::extract::{Multipart, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+
+/// Same upload limit as the OpenAI API.
+pub const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+/// A timed piece of a transcript; times are in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Runs whole recordings through the loaded model. The app implements this
+/// over `TranscriptionEngine`; calls block, so the server runs them off the
+/// async runtime.
+pub trait FileTranscriber: Send + Sync {
+    /// Transcribe 16kHz mono audio. `language` is a Whisper language code or
+    /// `auto`.
+    fn transcribe(
+        &self,
+        audio: &[f32],
+        language: &str,
+        prompt: Option<&str>,
+    ) -> Result<Vec<Segment>, String>;
+
+    /// Language used when the request doesn't name one.
+    fn default_language(&self) -> String;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Text,
+    Srt,
+    Vtt,
+    VerboseJson,
+}
+
+impl ResponseFormat {
+    fn parse(value: &str) -> Result<Self, String> {
+        serde_json::from_value(json!(value)).map_err(|_| {
+            format!(
+                "Unsupported response_format '{}': expected json, text, srt, vtt or verbose_json",
+                value
+            )
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Upload {
+    audio: Option<Vec<u8>>,
+    language: Option<String>,
+    prompt: Option<String>,
+    format: ResponseFormat,
+}
+
+async fn read_upload(mut multipart: Multipart) -> Result<Upload, String> {
+    let mut upload = Upload::default();
+    while let Some(field) = multipart.next_field().await.map_err(|e| e.to_string())? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let bytes = field.bytes().await.map_err(|e| e.to_string())?;
+            upload.audio = Some(bytes.to_vec());
+            continue;
+        }
+        let value = field.text().await.map_err(|e| e.to_string())?;
+        let value = value.trim();
+        match name.as_str() {
+            "language" if !value.is_empty() => upload.language = Some(value.to_string()),
+            "prompt" if !value.is_empty() => upload.prompt = Some(value.to_string()),
+            "response_format" => upload.format = ResponseFormat::parse(value)?,
+            // `model`, `temperature` and `timestamp_granularities[]` are accepted
+            // for compatibility; the loaded model decodes greedily.
+            _ => {}
+        }
+    }
+    Ok(upload)
+}
+
+/// Decode an upload to 16kHz mono. WAV is read directly; other formats go
+/// through `ffmpeg` when it is installed.
+pub fn decode_audio(bytes: &[u8]) -> Result<Vec<f32>, String> {
+    if bytes.starts_with(b"RIFF") {
+        return wav::decode_wav_mono(bytes);
+    }
+    let mut child = Command::new("ffmpeg")
+        .args(["-v", "error", "-i", "pipe:0", "-f", "s16le", "-ac", "1"])
+        .args(["-ar", &wav::TARGET_RATE.to_string(), "pipe:1"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|_| "Unsupported audio format: upload WAV, or install ffmpeg".to_string())?;
+
+    // Feed stdin from a thread so a full stdout pipe can't deadlock us.
+    let mut stdin = child.stdin.take().ok_or("ffmpeg stdin unavailable")?;
+    let input = bytes.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let mut pcm = Vec::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        stdout.read_to_end(&mut pcm).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(format!(
+            "Could not decode audio: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(pcm
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect())
+}
+
+/// `HH:MM:SS<sep>mmm`; SRT uses `,` and WebVTT `.`.
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// The segments' text joined with single spaces.
+pub fn join_text(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn to_srt(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                timestamp(s.start, ','),
+                timestamp(s.end, ','),
+                s.text.trim()
+            )
+        })
+        .collect()
+}
+
+pub fn to_vtt(segments: &[Segment]) -> String {
+    let cues: String = segments
+        .iter()
+        .map(|s| {
+            format!(
+                "{} --> {}\n{}\n\n",
+                timestamp(s.start, '.'),
+                timestamp(s.end, '.'),
+                s.text.trim()
+            )
+        })
+        .collect();
+    format!("WEBVTT\n\n{}", cues)
+}
+
+/// Render segments in the requested format.
+pub fn render(
+    format: ResponseFormat,
+    segments: &[Segment],
+    language: &str,
+    duration: f64,
+) -> Response {
+    let text = join_text(segments);
+    match format {
+        ResponseFormat::Json => Json(json!({ "text": text })).into_response(),
+        ResponseFormat::Text => plain("text/plain; charset=utf-8", text),
+        ResponseFormat::Srt => plain("application/x-subrip", to_srt(segments)),
+        ResponseFormat::Vtt => plain("text/vtt; charset=utf-8", to_vtt(segments)),
+        ResponseFormat::VerboseJson => {
+            let segments: Vec<_> = segments
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    json!({
+                        "id": i,
+                        "seek": 0,
+                        "start": s.start,
+                        "end": s.end,
+                        "text": s.text.trim(),
+                    })
+                })
+                .collect();
+            Json(json!({
+                "task": "transcribe",
+                "language": language,
+                "duration": duration,
+                "text": text,
+                "segments": segments,
+            }))
+            .into_response()
+        }
+    }
+}
+
+fn plain(content_type: &'static str, body: String) -> Response {
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+/// Errors in the OpenAI shape, which client libraries know how to surface.
+fn openai_error(status: StatusCode, kind: &str, message: impl Into<String>) -> Response {
+    let body = json!({ "error": { "message": message.into(), "type": kind } });
+    (status, Json(body)).into_response()
+}
+
+pub(super) async fn transcriptions(State(shared): State<Shared>, multipart: Multipart) -> Response {
+    let invalid = |e: String| openai_error(StatusCode::BAD_REQUEST, "invalid_request_error", e);
+    let upload = match read_upload(multipart).await {
+        Ok(upload) => upload,
+        Err(e) => return invalid(e),
+    };
+    let Some(bytes) = upload.audio else {
+        return invalid("Missing 'file' field".to_string());
+    };
+
+    let transcriber = shared.context.transcriber.clone();
+    let format = upload.format;
+    let result = tokio::task::spawn_blocking(move || {
+        let audio = decode_audio(&bytes).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let language = upload
+            .language
+            .unwrap_or_else(|| transcriber.default_language());
+        let segments = transcriber
+            .transcribe(&audio, &language, upload.prompt.as_deref())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let duration = audio.len() as f64 / wav::TARGET_RATE as f64;
+        Ok::<_, (StatusCode, String)>((segments, language, duration))
+    })
+    .await;
+
+    match result {
+        Ok(Ok((segments, language, duration))) => render(format, &segments, &language, duration),
+        Ok(Err((status, e))) if status == StatusCode::BAD_REQUEST => invalid(e),
+        Ok(Err((status, e))) => openai_error(status, "server_error", e),
+        Err(e) => openai_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "server_error",
+            e.to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments() -> Vec<Segment> {
+        vec![
+            Segment {
+                start: 0.0,
+                end: 1.5,
+                text: " Hello there.".to_string(),
+            },
+            Segment {
+                start: 61.25,
+                end: 3725.0,
+                text: "General Kenobi.".to_string(),
+            },
+        ]
+    }
+
+    async fn body(response: Response) -> (String, String) {
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (content_type, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    // --- Formatting Tests ---
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(timestamp(61.25, ','), "00:01:01,250");
+        assert_eq!(timestamp(3725.0, '.'), "01:02:05.000");
+        assert_eq!(timestamp(-1.0, '.'), "00:00:00.000");
+    }
+
+    #[test]
+    fn test_srt() {
+        assert_eq!(
+            to_srt(&segments()),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there.\n\n\
+             2\n00:01:01,250 --> 01:02:05,000\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_vtt() {
+        assert_eq!(
+            to_vtt(&segments()),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nHello there.\n\n\
+             00:01:01.250 --> 01:02:05.000\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_response_format_parse() {
+        assert_eq!(
+            ResponseFormat::parse("verbose_json").unwrap(),
+            ResponseFormat::VerboseJson
+        );
+        assert_eq!(ResponseFormat::parse("srt").unwrap(), ResponseFormat::Srt);
+        assert!(ResponseFormat::parse("xml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_render_formats() {
+        let (kind, text) = body(render(ResponseFormat::Json, &segments(), "en", 2.0)).await;
+        assert_eq!(kind, "application/json");
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value, json!({ "text": "Hello there. General Kenobi." }));
+
+        let (kind, text) = body(render(ResponseFormat::Text, &segments(), "en", 2.0)).await;
+        assert!(kind.starts_with("text/plain"));
+        assert_eq!(text, "Hello there. General Kenobi.");
+
+        let (kind, text) = body(render(ResponseFormat::VerboseJson, &segments(), "en", 2.0)).await;
+        assert_eq!(kind, "application/json");
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["language"], "en");
+        assert_eq!(value["duration"], 2.0);
+        assert_eq!(value["segments"][1]["id"], 1);
+        assert_eq!(value["segments"][1]["start"], 61.25);
+        assert_eq!(value["segments"][0]["text"], "Hello there.");
+    }
+
+    // --- Decoding Tests ---
+
+    #[test]
+    fn test_decode_wav_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.wav");
+        wav::write_wav(&path, &vec![0.25; 8000], 8000).unwrap();
+        let audio = decode_audio(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(audio.len(), 16000);
+    }
+}
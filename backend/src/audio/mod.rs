@@ -4,9 +4,12 @@
 
 pub mod buffer;
 pub mod capture;
+pub mod segmenter;
 pub mod vad;
+pub mod wav;
 
 use ringbuf::traits::Consumer;
+use segmenter::SpeechGate;
 use std::sync::mpsc;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -25,7 +28,7 @@ pub enum AudioMessage {
 
 /// Converts an interleaved multi-channel audio slice into a mono signal
 /// by averaging the samples across all available channels in each frame.
-fn to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
+pub(crate) fn to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
     if channels == 1 {
         return samples.to_vec();
     }
@@ -39,7 +42,7 @@ fn to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
 /// Resample audio from src_rate to dst_rate using linear interpolation.
 /// Resamples the input audio slice from `input_rate` to `output_rate`
 /// using basic linear interpolation. Preserves valid amplitudes within [-1.0, 1.0].
-fn resample(input: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
+pub(crate) fn resample(input: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
     if src_rate == dst_rate {
         return input.to_vec();
     }
@@ -380,9 +383,7 @@ impl AudioPipeline {
                 };
 
                 let mut read_buf = vec![0.0f32; 4800]; // 100ms at 48kHz
-                let mut was_speech = false;
-                let mut silence_after_speech: u32 = 0;
-                let grace_segments: u32 = 5; // ~500ms grace after speech ends
+                let mut gate = SpeechGate::default(); // ~500ms grace after speech ends
 
                 while running.load(Ordering::SeqCst) {
                     let n = consumer.pop_slice(&mut read_buf);
@@ -402,33 +403,16 @@ impl AudioPipeline {
                         energy_vad.contains_speech(&resampled)
                     };
 
-                    if is_speech {
-                        was_speech = true;
-                        silence_after_speech = 0;
-                        if chunk_tx.send(AudioMessage::Segment(resampled)).is_err() {
-                            break;
-                        }
-                    } else if was_speech {
-                        silence_after_speech += 1;
-                        // Send grace segments (capture tail of utterance)
-                        if silence_after_speech <= grace_segments
-                            && chunk_tx.send(AudioMessage::Segment(resampled)).is_err()
-                        {
-                            break;
-                        }
-                        if silence_after_speech == grace_segments {
-                            if chunk_tx.send(AudioMessage::EndOfSpeech).is_err() {
-                                break;
-                            }
-                            was_speech = false;
-                            silence_after_speech = 0;
-                            // Reset Silero state for next utterance
-                            if let Some(ref mut svad) = silero_vad {
-                                svad.reset();
-                            }
+                    let messages = gate.push(resampled, is_speech);
+                    if matches!(messages.last(), Some(AudioMessage::EndOfSpeech)) {
+                        // Reset Silero state for next utterance
+                        if let Some(ref mut svad) = silero_vad {
+                            svad.reset();
                         }
                     }
-                    // If !is_speech && !was_speech: silence with no prior speech, do nothing
+                    if messages.into_iter().any(|m| chunk_tx.send(m).is_err()) {
+                        break;
+                    }
                 }
 
                 consumer
@@ -0,0 +1,160 @@
+//! Turns per-segment VAD decisions into the `AudioMessage` stream consumed by
+//! the transcription thread: speech segments are forwarded, a short grace
+//! period of silence is kept to capture the tail of the utterance, and then
+//! `EndOfSpeech` is sent.
+
+use super::vad::{EnergyVad, SileroVad, VadBackend};
+use super::AudioMessage;
+use std::cell::RefCell;
+
+/// Silent segments (~100ms each) forwarded after speech before `EndOfSpeech`.
+pub const GRACE_SEGMENTS: u32 = 5;
+
+/// Segment size fed to the VAD, matching the DSP thread (100ms at 16kHz).
+pub const FRAME_SAMPLES: usize = 1600;
+
+pub struct SpeechGate {
+    grace_segments: u32,
+    was_speech: bool,
+    silence_after_speech: u32,
+}
+
+impl Default for SpeechGate {
+    fn default() -> Self {
+        Self::new(GRACE_SEGMENTS)
+    }
+}
+
+impl SpeechGate {
+    pub fn new(grace_segments: u32) -> Self {
+        Self {
+            grace_segments: grace_segments.max(1),
+            was_speech: false,
+            silence_after_speech: 0,
+        }
+    }
+
+    /// Feed one resampled segment and the VAD decision for it. Returns the
+    /// messages to send, in order. When the result ends with `EndOfSpeech` the
+    /// caller should reset a stateful VAD for the next utterance.
+    pub fn push(&mut self, segment: Vec<f32>, is_speech: bool) -> Vec<AudioMessage> {
+        let mut out = Vec::new();
+        if is_speech {
+            self.was_speech = true;
+            self.silence_after_speech = 0;
+            out.push(AudioMessage::Segment(segment));
+        } else if self.was_speech {
+            self.silence_after_speech += 1;
+            if self.silence_after_speech <= self.grace_segments {
+                out.push(AudioMessage::Segment(segment));
+            }
+            if self.silence_after_speech == self.grace_segments {
+                out.push(AudioMessage::EndOfSpeech);
+                self.was_speech = false;
+                self.silence_after_speech = 0;
+            }
+        }
+        // Silence with no prior speech: nothing to send.
+        out
+    }
+}
+
+/// Split 16kHz audio into `frame_len` segments and run them through a gate,
+/// as the DSP thread does for live input. Used by offline tools.
+pub fn segment_clip(
+    audio: &[f32],
+    frame_len: usize,
+    mut is_speech: impl FnMut(&[f32]) -> bool,
+    mut on_end_of_speech: impl FnMut(),
+) -> Vec<AudioMessage> {
+    let mut gate = SpeechGate::default();
+    let mut messages = Vec::new();
+    for frame in audio.chunks(frame_len.max(1)) {
+        let speech = is_speech(frame);
+        let out = gate.push(frame.to_vec(), speech);
+        if matches!(out.last(), Some(AudioMessage::EndOfSpeech)) {
+            on_end_of_speech();
+        }
+        messages.extend(out);
+    }
+    messages
+}
+
+/// Run a 16kHz clip through a fresh VAD of the given backend and the speech
+/// gate, in `FRAME_SAMPLES` segments.
+pub fn gate_clip(
+    audio: &[f32],
+    backend: VadBackend,
+    threshold: f32,
+) -> Result<Vec<AudioMessage>, String> {
+    Ok(match backend {
+        VadBackend::Energy => {
+            let mut vad = EnergyVad::new(threshold);
+            segment_clip(audio, FRAME_SAMPLES, |f| vad.contains_speech(f), || {})
+        }
+        VadBackend::Silero => {
+            // Both callbacks need the model; the gate calls them in turn.
+            let vad = RefCell::new(SileroVad::new(0.5)?);
+            segment_clip(
+                audio,
+                FRAME_SAMPLES,
+                |f| vad.borrow_mut().process_audio(f),
+                || vad.borrow_mut().reset(),
+            )
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(messages: &[AudioMessage]) -> String {
+        messages
+            .iter()
+            .map(|m| match m {
+                AudioMessage::Segment(_) => 'S',
+                AudioMessage::EndOfSpeech => 'E',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_leading_silence_is_dropped() {
+        let mut gate = SpeechGate::new(2);
+        assert!(gate.push(vec![0.0], false).is_empty());
+        assert_eq!(kinds(&gate.push(vec![0.5], true)), "S");
+    }
+
+    #[test]
+    fn test_grace_segments_then_end_of_speech() {
+        let mut gate = SpeechGate::new(2);
+        gate.push(vec![0.5], true);
+        assert_eq!(kinds(&gate.push(vec![0.0], false)), "S");
+        assert_eq!(kinds(&gate.push(vec![0.0], false)), "SE");
+        assert!(gate.push(vec![0.0], false).is_empty(), "gate closed");
+    }
+
+    #[test]
+    fn test_speech_during_grace_continues_utterance() {
+        let mut gate = SpeechGate::new(2);
+        gate.push(vec![0.5], true);
+        gate.push(vec![0.0], false);
+        assert_eq!(kinds(&gate.push(vec![0.5], true)), "S");
+        assert_eq!(kinds(&gate.push(vec![0.0], false)), "S");
+        assert_eq!(kinds(&gate.push(vec![0.0], false)), "SE");
+    }
+
+    #[test]
+    fn test_segment_clip_resets_vad_per_utterance() {
+        // Two bursts of "speech" separated by long silence.
+        let mut audio = vec![1.0; 300];
+        audio.extend(vec![0.0; 700]);
+        audio.extend(vec![1.0; 200]);
+        audio.extend(vec![0.0; 600]);
+        let mut resets = 0;
+        let messages = segment_clip(&audio, 100, |f| f[0] > 0.5, || resets += 1);
+        assert_eq!(kinds(&messages), "SSSSSSSSESSSSSSSE");
+        assert_eq!(resets, 2);
+    }
+}
@@ -0,0 +1,207 @@
+//! Minimal WAV reader/writer for offline tools (benchmarks, evaluation and
+//! replay tests). Reads PCM 8/16/24/32-bit and 32-bit float files and converts
+//! them to the 16kHz mono f32 format the engines expect.
+
+use super::{resample, to_mono};
+use std::path::Path;
+
+pub const TARGET_RATE: u32 = 16000;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    pub float: bool,
+}
+
+/// Read a WAV file as 16kHz mono f32 samples.
+pub fn read_wav(path: &Path) -> Result<Vec<f32>, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    decode_wav_mono(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Decode a WAV byte buffer as 16kHz mono f32 samples.
+pub fn decode_wav_mono(bytes: &[u8]) -> Result<Vec<f32>, String> {
+    let (format, samples) = decode_wav(bytes)?;
+    let mono = to_mono(&samples, format.channels);
+    Ok(resample(&mono, format.sample_rate, TARGET_RATE))
+}
+
+/// Decode a WAV byte buffer into interleaved f32 samples in [-1, 1].
+pub fn decode_wav(bytes: &[u8]) -> Result<(WavFormat, Vec<f32>), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a RIFF/WAVE file".to_string());
+    }
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let body = &bytes[pos + 8..(pos + 8 + size).min(bytes.len())];
+        match id {
+            b"fmt " => format = Some(parse_fmt(body)?),
+            b"data" => {
+                let format = format.ok_or("data chunk before fmt chunk")?;
+                let samples = decode_samples(body, &format)?;
+                return Ok((format, samples));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size.
+        pos += 8 + size + (size & 1);
+    }
+    Err("no data chunk".to_string())
+}
+
+fn parse_fmt(body: &[u8]) -> Result<WavFormat, String> {
+    if body.len() < 16 {
+        return Err("fmt chunk too short".to_string());
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    let mut tag = u16_at(0);
+    if tag == FORMAT_EXTENSIBLE && body.len() >= 26 {
+        // The sub-format GUID starts with the real format tag.
+        tag = u16_at(24);
+    }
+    let format = WavFormat {
+        channels: u16_at(2),
+        sample_rate: u32::from_le_bytes(body[4..8].try_into().unwrap()),
+        bits_per_sample: u16_at(14),
+        float: tag == FORMAT_FLOAT,
+    };
+    match (tag, format.bits_per_sample) {
+        (FORMAT_PCM, 8 | 16 | 24 | 32) | (FORMAT_FLOAT, 32) => {}
+        _ => {
+            return Err(format!(
+                "unsupported WAV encoding (format {}, {} bits)",
+                tag, format.bits_per_sample
+            ))
+        }
+    }
+    if format.channels == 0 || format.sample_rate == 0 {
+        return Err("invalid channel count or sample rate".to_string());
+    }
+    Ok(format)
+}
+
+fn decode_samples(data: &[u8], format: &WavFormat) -> Result<Vec<f32>, String> {
+    let width = format.bits_per_sample as usize / 8;
+    let samples = data
+        .chunks_exact(width)
+        .map(|b| match (width, format.float) {
+            (1, _) => (b[0] as f32 - 128.0) / 128.0,
+            (2, _) => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            (3, _) => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+            (4, true) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            (_, _) => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        })
+        .collect();
+    Ok(samples)
+}
+
+/// Write 16-bit PCM mono samples.
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) -> Result<(), String> {
+    let data_len = samples.len() * 2;
+    let mut out = Vec::with_capacity(44 + data_len);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len as u32).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(data_len as u32).to_le_bytes());
+    for s in samples {
+        let v = (s.clamp(-1.0, 1.0) * 32767.0).round() as i16;
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    std::fs::write(path, out).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav_bytes(tag: u16, channels: u16, rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data.len() as u32 + 10).to_le_bytes());
+        out.extend_from_slice(b"WAVE");
+        // An unrelated odd-sized chunk first, to exercise padding.
+        out.extend_from_slice(b"LIST");
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&channels.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        let block = channels * bits / 8;
+        out.extend_from_slice(&(rate * block as u32).to_le_bytes());
+        out.extend_from_slice(&block.to_le_bytes());
+        out.extend_from_slice(&bits.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn test_roundtrip_16bit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.wav");
+        let samples = vec![0.0, 0.5, -0.5, 1.0];
+        write_wav(&path, &samples, TARGET_RATE).unwrap();
+        let read = read_wav(&path).unwrap();
+        assert_eq!(read.len(), 4);
+        for (a, b) in samples.iter().zip(&read) {
+            assert!((a - b).abs() < 1e-3, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_decode_stereo_float() {
+        let data: Vec<u8> = [0.25f32, 0.75, -1.0, 1.0]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let (format, samples) = decode_wav(&wav_bytes(FORMAT_FLOAT, 2, 48000, 32, &data)).unwrap();
+        assert!(format.float);
+        assert_eq!(format.channels, 2);
+        assert_eq!(samples, vec![0.25, 0.75, -1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_decode_24bit() {
+        // -1.0 and +0.5 in 24-bit little endian.
+        let data = [0x00, 0x00, 0x80, 0x00, 0x00, 0x40];
+        let (_, samples) = decode_wav(&wav_bytes(FORMAT_PCM, 1, 16000, 24, &data)).unwrap();
+        assert_eq!(samples, vec![-1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_read_resamples_to_16k_mono() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("48k.wav");
+        write_wav(&path, &vec![0.1; 4800], 48000).unwrap();
+        assert_eq!(read_wav(&path).unwrap().len(), 1600);
+    }
+
+    #[test]
+    fn test_rejects_invalid_files() {
+        assert!(decode_wav(b"not a wav file").is_err());
+        assert!(decode_wav(&wav_bytes(FORMAT_PCM, 1, 16000, 12, &[0, 0])).is_err());
+        assert!(decode_wav(&wav_bytes(7, 1, 8000, 8, &[0])).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Inference benchmark across installed models: load time, per-pass latency
+//! percentiles, real-time factor and peak memory. Results are cached in
+//! `~/.whispertype/benchmark.json` and shown next to each model in the picker.
+//!
+//! This module holds the engine-independent parts (statistics, measurement
+//! loop, report persistence); `runner` drives the actual engines.
+
+pub mod runner;
+
+use crate::config::Config;
+use crate::hardware::{Device, HardwareInfo};
+use crate::transcription::ModelType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+pub const DEFAULT_PASSES: usize = 5;
+/// Length of the generated clip used when no WAV is supplied.
+pub const SYNTHETIC_CLIP_SECS: f32 = 5.0;
+/// `BenchReport::clip` of a run on the generated clip.
+pub const SYNTHETIC_CLIP: &str = "synthetic";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    /// Nearest-rank percentiles over `samples`; `None` if empty.
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        Some(Self {
+            min_ms: sorted[0],
+            mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p95_ms: percentile(95.0),
+            p99_ms: percentile(99.0),
+            max_ms: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Timings for one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelBench {
+    pub model_id: String,
+    pub display_name: String,
+    pub model_type: ModelType,
+    /// Device requested for the model; see `EngineMode::requested_device`.
+    pub device: Device,
+    pub load_ms: f64,
+    pub passes: usize,
+    pub latency: LatencyStats,
+    /// Mean pass latency divided by clip duration (lower is faster).
+    pub rtf: f64,
+    /// Peak resident memory while this model ran, if the OS reports it. Only
+    /// measured by the `bench` binary; the app also holds its loaded model.
+    pub peak_rss_mb: Option<u64>,
+    /// Output of the first pass, to sanity-check the model.
+    pub transcript: String,
+}
+
+/// A model that could not be benchmarked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchFailure {
+    pub model_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub app_version: String,
+    /// Unix timestamp (seconds) of the run.
+    pub created_at: u64,
+    /// WAV path, or `"synthetic"` for the generated clip.
+    pub clip: String,
+    pub audio_secs: f64,
+    pub hardware: HardwareInfo,
+    pub results: Vec<ModelBench>,
+    #[serde(default)]
+    pub failures: Vec<BenchFailure>,
+}
+
+impl BenchReport {
+    /// Measured RTF per model ID, for the model picker. Empty for a run on
+    /// the synthetic clip, whose timings say little about speech.
+    pub fn rtf_by_model(&self) -> HashMap<String, f64> {
+        if self.clip == SYNTHETIC_CLIP {
+            return HashMap::new();
+        }
+        self.results
+            .iter()
+            .map(|r| (r.model_id.clone(), r.rtf))
+            .collect()
+    }
+}
+
+/// Raw output of `measure`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub load_ms: f64,
+    pub pass_ms: Vec<f64>,
+    pub transcript: String,
+}
+
+/// Time `load`, then `passes` runs of `transcribe` over `audio`.
+pub fn measure<T>(
+    load: impl FnOnce() -> Result<T, String>,
+    mut transcribe: impl FnMut(&mut T, &[f32]) -> Result<String, String>,
+    audio: &[f32],
+    passes: usize,
+) -> Result<Measurement, String> {
+    let started = Instant::now();
+    let mut engine = load()?;
+    let load_ms = elapsed_ms(started);
+
+    let mut pass_ms = Vec::with_capacity(passes);
+    let mut transcript = String::new();
+    for pass in 0..passes.max(1) {
+        let started = Instant::now();
+        let text = transcribe(&mut engine, audio)?;
+        pass_ms.push(elapsed_ms(started));
+        if pass == 0 {
+            transcript = text.trim().to_string();
+        }
+    }
+    Ok(Measurement {
+        load_ms,
+        pass_ms,
+        transcript,
+    })
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Real-time factor of a pass taking `latency_ms` on `audio_secs` of audio.
+pub fn rtf(latency_ms: f64, audio_secs: f64) -> f64 {
+    if audio_secs <= 0.0 {
+        return 0.0;
+    }
+    latency_ms / (audio_secs * 1000.0)
+}
+
+/// Tone/silence/tone clip used when no WAV is supplied. Whisper may decode it
+/// as silence or noise, so a real speech clip gives more representative
+/// decoder timings.
+pub fn synthetic_clip(secs: f32) -> Vec<f32> {
+    let rate = 16000.0;
+    let len = (rate * secs) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / rate;
+            let phase = t / secs;
+            if phase < 0.4 {
+                (2.0 * std::f32::consts::PI * 440.0 * t).sin() * 0.3
+            } else if phase < 0.6 {
+                0.0
+            } else {
+                (2.0 * std::f32::consts::PI * 880.0 * t).sin() * 0.3
+            }
+        })
+        .collect()
+}
+
+/// Reset the peak RSS counter so the next reading covers only what follows.
+/// Linux only; elsewhere the peak accumulates over the whole run.
+pub fn reset_peak_rss() {
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Peak resident set size of this process in MB (Linux `VmHWM`).
+pub fn peak_rss_mb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_hwm(&status)
+}
+
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find(|l| l.starts_with("VmHWM:"))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb / 1024)
+}
+
+pub fn report_path() -> PathBuf {
+    Config::app_dir().join("benchmark.json")
+}
+
+pub fn save_report(report: &BenchReport) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(Config::app_dir()).map_err(|e| e.to_string())?;
+    std::fs::write(report_path(), json).map_err(|e| format!("Failed to save benchmark: {}", e))
+}
+
+/// The last saved report, if any. Unreadable reports are ignored.
+pub fn load_report() -> Option<BenchReport> {
+    let json = std::fs::read_to_string(report_path()).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- LatencyStats Tests ---
+
+    #[test]
+    fn test_latency_stats_percentiles() {
+        let samples: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        let stats = LatencyStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.min_ms, 1.0);
+        assert_eq!(stats.max_ms, 100.0);
+        assert_eq!(stats.mean_ms, 50.5);
+        assert_eq!(stats.p50_ms, 50.0);
+        assert_eq!(stats.p90_ms, 90.0);
+        assert_eq!(stats.p95_ms, 95.0);
+        assert_eq!(stats.p99_ms, 99.0);
+    }
+
+    #[test]
+    fn test_latency_stats_small_sample() {
+        let stats = LatencyStats::from_samples(&[30.0, 10.0, 20.0]).unwrap();
+        assert_eq!(stats.p50_ms, 20.0);
+        assert_eq!(stats.p99_ms, 30.0);
+        assert!(LatencyStats::from_samples(&[]).is_none());
+    }
+
+    // --- Measurement Tests ---
+
+    #[test]
+    fn test_measure_runs_all_passes() {
+        let mut calls = 0;
+        let m = measure(
+            || Ok(0usize),
+            |count, audio| {
+                *count += 1;
+                calls += 1;
+                Ok(format!(" pass {} of {} samples ", count, audio.len()))
+            },
+            &[0.0; 160],
+            4,
+        )
+        .unwrap();
+        assert_eq!(calls, 4);
+        assert_eq!(m.pass_ms.len(), 4);
+        assert_eq!(m.transcript, "pass 1 of 160 samples");
+    }
+
+    #[test]
+    fn test_measure_propagates_errors() {
+        let load_err = measure(
+            || Err::<(), _>("no model".to_string()),
+            |_, _| Ok(String::new()),
+            &[],
+            1,
+        );
+        assert_eq!(load_err.unwrap_err(), "no model");
+        let pass_err = measure(|| Ok(()), |_, _| Err("boom".to_string()), &[], 3);
+        assert_eq!(pass_err.unwrap_err(), "boom");
+    }
+
+    #[test]
+    fn test_rtf() {
+        assert_eq!(rtf(500.0, 5.0), 0.1);
+        assert_eq!(rtf(500.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_synthetic_clip_length() {
+        let clip = synthetic_clip(2.0);
+        assert_eq!(clip.len(), 32000);
+        assert!(clip.iter().all(|s| s.abs() <= 0.3));
+    }
+
+    #[test]
+    fn test_parse_vm_hwm() {
+        let status = "Name:\tbench\nVmPeak:\t 900000 kB\nVmHWM:\t  204800 kB\n";
+        assert_eq!(parse_vm_hwm(status), Some(200));
+        assert_eq!(parse_vm_hwm("Name:\tbench\n"), None);
+    }
+
+    // --- Report Tests ---
+
+    #[test]
+    fn test_report_roundtrip_and_rtf_map() {
+        let mut report = BenchReport {
+            app_version: "0.3.0".to_string(),
+            created_at: 1,
+            clip: "speech.wav".to_string(),
+            audio_secs: 5.0,
+            hardware: crate::hardware::probe(),
+            results: vec![ModelBench {
+                model_id: "tiny".to_string(),
+                display_name: "Tiny".to_string(),
+                model_type: ModelType::WhisperGgml,
+                device: Device::Cpu,
+                load_ms: 120.0,
+                passes: 2,
+                latency: LatencyStats::from_samples(&[100.0, 200.0]).unwrap(),
+                rtf: 0.03,
+                peak_rss_mb: Some(300),
+                transcript: String::new(),
+            }],
+            failures: vec![BenchFailure {
+                model_id: "large-v3".to_string(),
+                error: "out of memory".to_string(),
+            }],
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: BenchReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.results, report.results);
+        assert_eq!(parsed.failures, report.failures);
+        assert_eq!(parsed.rtf_by_model().get("tiny"), Some(&0.03));
+
+        report.clip = SYNTHETIC_CLIP.to_string();
+        assert!(report.rtf_by_model().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Runs the benchmark against the real Whisper and Moonshine engines. Each
+//! model gets a fresh engine instance, so the app's loaded model is untouched.
+
+use super::{
+    measure, peak_rss_mb, reset_peak_rss, rtf, synthetic_clip, BenchFailure, BenchReport,
+    LatencyStats, ModelBench, SYNTHETIC_CLIP, SYNTHETIC_CLIP_SECS,
+};
+use crate::audio::wav::{read_wav, TARGET_RATE};
+use crate::hardware::{Device, HardwareInfo};
+use crate::model_manager::is_model_downloaded;
+use crate::transcription::engine::TranscriptionEngine;
+use crate::transcription::moonshine::MoonshineEngine;
+use crate::transcription::options::EngineOptions;
+use crate::transcription::{ModelType, WhisperModel};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// WAV clip to transcribe; `None` uses the synthetic clip.
+    pub clip: Option<PathBuf>,
+    pub passes: usize,
+    /// Models to run; `None` runs every downloaded model.
+    pub model_ids: Option<Vec<String>>,
+    pub language: String,
+    pub engine: EngineOptions,
+    /// Record `peak_rss_mb`. Only meaningful in a process that holds nothing
+    /// but the benchmarked model, i.e. the `bench` binary, not the app.
+    pub measure_rss: bool,
+}
+
+/// Benchmark the selected downloaded models. `progress` is called with each
+/// model's ID before it runs, along with its index and the total.
+pub fn run(
+    options: &BenchOptions,
+    models: &[WhisperModel],
+    hardware: &HardwareInfo,
+    mut progress: impl FnMut(&str, usize, usize),
+) -> Result<BenchReport, String> {
+    let (audio, clip) = match &options.clip {
+        Some(path) => (read_wav(path)?, path.display().to_string()),
+        None => (
+            synthetic_clip(SYNTHETIC_CLIP_SECS),
+            SYNTHETIC_CLIP.to_string(),
+        ),
+    };
+    if audio.is_empty() {
+        return Err("Benchmark clip is empty".to_string());
+    }
+    let audio_secs = audio.len() as f64 / TARGET_RATE as f64;
+
+    let selected: Vec<&WhisperModel> = models
+        .iter()
+        .filter(|m| match &options.model_ids {
+            Some(ids) => ids.contains(&m.id),
+            None => true,
+        })
+        .filter(|m| is_model_downloaded(&m.id))
+        .collect();
+    if selected.is_empty() {
+        return Err("No downloaded models to benchmark".to_string());
+    }
+
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for (index, model) in selected.iter().enumerate() {
+        progress(&model.id, index, selected.len());
+        if options.measure_rss {
+            reset_peak_rss();
+        }
+        match bench_model(model, &audio, options) {
+            Ok((device, m)) => {
+                let Some(latency) = LatencyStats::from_samples(&m.pass_ms) else {
+                    continue;
+                };
+                results.push(ModelBench {
+                    model_id: model.id.clone(),
+                    display_name: model.display_name.clone(),
+                    model_type: model.model_type.clone(),
+                    device,
+                    load_ms: m.load_ms,
+                    passes: m.pass_ms.len(),
+                    rtf: rtf(latency.mean_ms, audio_secs),
+                    latency,
+                    peak_rss_mb: if options.measure_rss {
+                        peak_rss_mb()
+                    } else {
+                        None
+                    },
+                    transcript: m.transcript,
+                });
+            }
+            Err(error) => failures.push(BenchFailure {
+                model_id: model.id.clone(),
+                error,
+            }),
+        }
+    }
+
+    Ok(BenchReport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        clip,
+        audio_secs,
+        hardware: hardware.clone(),
+        results,
+        failures,
+    })
+}
+
+fn bench_model(
+    model: &WhisperModel,
+    audio: &[f32],
+    options: &BenchOptions,
+) -> Result<(Device, super::Measurement), String> {
+    let path = model.model_path();
+    match model.model_type {
+        ModelType::MoonshineOnnx => {
+            let m = measure(
+                || MoonshineEngine::load(&path),
+                |engine, audio| engine.transcribe(audio),
+                audio,
+                options.passes,
+            )?;
+            Ok((Device::Cpu, m))
+        }
+        ModelType::WhisperGgml => {
+            let mut device = Device::Cpu;
+            let m = measure(
+                || {
+                    let engine = TranscriptionEngine::new();
+                    let mode = engine.load_model(&path, &model.id, &options.engine)?;
+                    device = mode.requested_device;
+                    let state = engine.create_inference_state()?;
+                    // The state must drop before the engine (tuple fields drop
+                    // in order); see `create_inference_state`.
+                    Ok((state, engine))
+                },
+                |(state, engine), audio| {
+                    let segments = engine.transcribe(state, audio, &options.language)?;
+                    Ok(segments
+                        .iter()
+                        .map(|s| s.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "))
+                },
+                audio,
+                options.passes,
+            )?;
+            Ok((device, m))
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Inference benchmark across all downloaded Whisper and Moonshine models.
+//!
+//! Runs each model on a WAV clip (or a generated clip), prints a JSON report
+//! with load time, latency percentiles, real-time factor and peak memory, and
+//! saves it to `~/.whispertype/benchmark.json` for the model picker.
+//!
+//! Usage:
+//!   cargo run --release --bin bench [-- --clip <file.wav> --passes <N> --models <id,id> --out <path> --no-save]
+
+use std::path::PathBuf;
+use tauri_app_lib::bench::runner::{self, BenchOptions};
+use tauri_app_lib::config::Config;
+use tauri_app_lib::transcription::models::all_models;
+use tauri_app_lib::{bench, hardware, model_manager};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut clip = None;
+    let mut passes = bench::DEFAULT_PASSES;
+    let mut model_ids = None;
+    let mut out = None;
+    let mut save = true;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = |i: usize| {
+            args.get(i + 1).cloned().unwrap_or_else(|| {
+                eprintln!("{} needs a value", args[i]);
+                std::process::exit(2);
+            })
+        };
+        match args[i].as_str() {
+            "--clip" => {
+                clip = Some(PathBuf::from(value(i)));
+                i += 1;
+            }
+            "--passes" => {
+                passes = value(i).parse().unwrap_or_else(|_| {
+                    eprintln!("--passes must be a number");
+                    std::process::exit(2);
+                });
+                i += 1;
+            }
+            "--models" => {
+                model_ids = Some(value(i).split(',').map(str::to_string).collect());
+                i += 1;
+            }
+            "--out" => {
+                out = Some(PathBuf::from(value(i)));
+                i += 1;
+            }
+            "--no-save" => save = false,
+            _ => {
+                eprintln!("Unknown arg: {}", args[i]);
+                std::process::exit(2);
+            }
+        }
+        i += 1;
+    }
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Failed to load config: {}", e);
+        std::process::exit(1);
+    });
+    model_manager::init_registry(&config);
+    let hw = hardware::info();
+
+    let options = BenchOptions {
+        clip,
+        passes,
+        model_ids,
+        language: config.language.clone(),
+        engine: hardware::engine_options(&config, hw),
+        measure_rss: true,
+    };
+    let report = runner::run(&options, &all_models(), hw, |id, index, total| {
+        eprintln!("[{}/{}] {}", index + 1, total, id);
+    })
+    .unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    for failure in &report.failures {
+        eprintln!("{} failed: {}", failure.model_id, failure.error);
+    }
+    let json = serde_json::to_string_pretty(&report).expect("report serializes");
+    println!("{}", json);
+
+    if let Some(path) = out {
+        if let Err(e) = std::fs::write(&path, &json) {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+        }
+    }
+    if save {
+        if let Err(e) = bench::save_report(&report) {
+            eprintln!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Word/character error rate evaluation over a directory of WAV clips, each
+//! with a `.txt` reference transcript of the same name.
+//!
+//! Every clip goes through the streaming dictation path (VAD gating,
+//! LocalAgreement, Whisper quality pass) and through plain offline
+//! transcription; the JSON report on stdout has per-file and corpus-level
+//! WER/CER for both. Settings not given on the command line come from the
+//! app config.
+//!
+//! Usage:
+//!   cargo run --release --bin eval -- <dir> [--model <id>] [--stream-engine whisper|moonshine]
+//!     [--vad energy|silero] [--vad-threshold <f>] [--real-time]
+//!     [--streaming-only | --offline-only] [--out <path>]
+
+use std::path::PathBuf;
+use tauri_app_lib::audio::vad::VadBackend;
+use tauri_app_lib::config::{Config, StreamEngineConfig};
+use tauri_app_lib::eval::runner::{self, EvalOptions};
+use tauri_app_lib::eval::{self, Pacing};
+use tauri_app_lib::{hardware, model_manager};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Failed to load config: {}", e);
+        std::process::exit(1);
+    });
+    model_manager::init_registry(&config);
+
+    let mut dir = None;
+    let mut options = EvalOptions {
+        model_id: config.default_model.clone(),
+        language: config.language.clone(),
+        stream_engine: config.stream_engine.clone(),
+        vad_backend: config.vad_backend.clone(),
+        vad_threshold: config.vad_threshold,
+        pacing: Pacing::EveryMessage,
+        streaming: true,
+        offline: true,
+        engine: hardware::engine_options(&config, hardware::info()),
+    };
+    let mut out = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = |i: usize| {
+            args.get(i + 1).cloned().unwrap_or_else(|| {
+                eprintln!("{} needs a value", args[i]);
+                std::process::exit(2);
+            })
+        };
+        match args[i].as_str() {
+            "--model" => {
+                options.model_id = value(i);
+                i += 1;
+            }
+            "--stream-engine" => {
+                options.stream_engine = match value(i).as_str() {
+                    "whisper" => StreamEngineConfig::WhisperOnly,
+                    "moonshine" => StreamEngineConfig::Moonshine,
+                    other => {
+                        eprintln!("Unknown stream engine: {}", other);
+                        std::process::exit(2);
+                    }
+                };
+                i += 1;
+            }
+            "--vad" => {
+                options.vad_backend = match value(i).as_str() {
+                    "energy" => VadBackend::Energy,
+                    "silero" => VadBackend::Silero,
+                    other => {
+                        eprintln!("Unknown VAD backend: {}", other);
+                        std::process::exit(2);
+                    }
+                };
+                i += 1;
+            }
+            "--vad-threshold" => {
+                options.vad_threshold = value(i).parse().expect("--vad-threshold must be a number");
+                i += 1;
+            }
+            "--real-time" => options.pacing = Pacing::RealTime,
+            "--streaming-only" => options.offline = false,
+            "--offline-only" => options.streaming = false,
+            "--out" => {
+                out = Some(PathBuf::from(value(i)));
+                i += 1;
+            }
+            arg if !arg.starts_with("--") && dir.is_none() => dir = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("Unknown arg: {}", args[i]);
+                std::process::exit(2);
+            }
+        }
+        i += 1;
+    }
+
+    let Some(dir) = dir else {
+        eprintln!("Usage: eval <dir> [--model <id>] [--stream-engine whisper|moonshine] [--vad energy|silero] [--vad-threshold <f>] [--real-time] [--streaming-only | --offline-only] [--out <path>]");
+        std::process::exit(2);
+    };
+    let (samples, missing) = eval::discover(&dir).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    for wav in &missing {
+        eprintln!("Skipping {} (no .txt reference)", wav.display());
+    }
+
+    let report = runner::run(&samples, &options, |name, index, total| {
+        eprintln!("[{}/{}] {}", index + 1, total, name);
+    })
+    .unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    for (label, summary) in [
+        ("streaming", &report.streaming),
+        ("offline", &report.offline),
+    ] {
+        if let Some(s) = summary {
+            eprintln!(
+                "{}: WER {:.2}% ({}/{} words), CER {:.2}%",
+                label,
+                s.wer.rate * 100.0,
+                s.wer.errors,
+                s.wer.length,
+                s.cer.rate * 100.0
+            );
+        }
+    }
+    let json = serde_json::to_string_pretty(&report).expect("report serializes");
+    println!("{}", json);
+
+    if let Some(path) = out {
+        if let Err(e) = std::fs::write(&path, &json) {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+        }
+    }
+}
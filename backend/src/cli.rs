@@ -0,0 +1,312 @@
+//! Headless subcommands, handled in `main()` before the GUI starts:
+//!
+//! ```text
+//! whispertype import-model <path> [--id <model-id>] [--link]
+//! whispertype export-bundle <dest-dir> <model-id>...
+//! whispertype import-bundle <bundle-dir> [--link]
+//! whispertype toggle|start|stop|status
+//! whispertype set-model <model-id>
+//! whispertype transcribe <audio-file>
+//! ```
+//!
+//! The last three control the running app over its control socket (`ipc`).
+
+use crate::config::{Config, OutputMode};
+use crate::ipc;
+use crate::model_manager;
+use crate::model_manager::import::ImportMode;
+use crate::output;
+use crate::output::sink::OutputTargets;
+use crate::transcription::models::all_models;
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage:
+  whispertype                                    Start the app
+  whispertype import-model <path> [--id <model-id>] [--link]
+  whispertype export-bundle <dest-dir> <model-id>...
+  whispertype import-bundle <bundle-dir> [--link]
+  whispertype toggle|start|stop|status           Control the running app
+  whispertype set-model <model-id>               Load a model in the running app
+  whispertype transcribe <audio-file>            Print a file's transcript from the running app";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    ImportModel {
+        path: PathBuf,
+        model_id: Option<String>,
+        mode: ImportMode,
+    },
+    ExportBundle {
+        dest: PathBuf,
+        model_ids: Vec<String>,
+    },
+    ImportBundle {
+        path: PathBuf,
+        mode: ImportMode,
+    },
+    /// Sent to the running instance.
+    Remote(ipc::Request),
+    /// Transcribed by the running instance and written to stdout.
+    Transcribe {
+        path: PathBuf,
+    },
+    Help,
+}
+
+/// Parse arguments (without the program name). `Ok(None)` means no
+/// subcommand: start the GUI.
+pub fn parse(args: &[String]) -> Result<Option<Command>, String> {
+    let Some((name, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    let mut positional = Vec::new();
+    let mut model_id = None;
+    let mut mode = ImportMode::Copy;
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--link" => mode = ImportMode::Link,
+            "--id" => {
+                model_id = Some(iter.next().ok_or("--id needs a model ID")?.clone());
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let command = match name.as_str() {
+        "import-model" => match positional.as_slice() {
+            [path] => Command::ImportModel {
+                path: PathBuf::from(path),
+                model_id,
+                mode,
+            },
+            _ => return Err("import-model takes exactly one path".to_string()),
+        },
+        "export-bundle" => match positional.split_first() {
+            Some((dest, ids)) if !ids.is_empty() => Command::ExportBundle {
+                dest: PathBuf::from(dest),
+                model_ids: ids.to_vec(),
+            },
+            _ => return Err("export-bundle needs a directory and model IDs".to_string()),
+        },
+        "import-bundle" => match positional.as_slice() {
+            [path] => Command::ImportBundle {
+                path: PathBuf::from(path),
+                mode,
+            },
+            _ => return Err("import-bundle takes exactly one directory".to_string()),
+        },
+        "toggle" | "start" | "stop" | "status" if !positional.is_empty() => {
+            return Err(format!("{} takes no arguments", name));
+        }
+        "toggle" => Command::Remote(ipc::Request::Toggle),
+        "start" => Command::Remote(ipc::Request::Start),
+        "stop" => Command::Remote(ipc::Request::Stop),
+        "status" => Command::Remote(ipc::Request::Status),
+        "set-model" => match positional.as_slice() {
+            [model] => Command::Remote(ipc::Request::SetModel {
+                model: model.clone(),
+            }),
+            _ => return Err("set-model takes exactly one model ID".to_string()),
+        },
+        "transcribe" => match positional.as_slice() {
+            [path] => Command::Transcribe {
+                path: PathBuf::from(path),
+            },
+            _ => return Err("transcribe takes exactly one audio file".to_string()),
+        },
+        "help" | "--help" | "-h" => Command::Help,
+        // Anything else (e.g. flags passed by a desktop launcher) starts the GUI.
+        _ => return Ok(None),
+    };
+    Ok(Some(command))
+}
+
+/// Run a subcommand if one was given. Returns the process exit code, or
+/// `None` if the GUI should start.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = match parse(args) {
+        Ok(Some(command)) => command,
+        Ok(None) => return None,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return Some(2);
+        }
+    };
+
+    match execute(command) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            Some(1)
+        }
+    }
+}
+
+/// Load the config and set up the model registry for a local command.
+/// Returns the config and the models directory.
+fn open_registry() -> Result<(Config, PathBuf), String> {
+    let config = Config::load()?;
+    model_manager::init_registry(&config);
+    Ok((config, Config::models_dir()))
+}
+
+fn execute(command: Command) -> Result<(), String> {
+    match command {
+        Command::ImportModel {
+            path,
+            model_id,
+            mode,
+        } => {
+            let (mut config, models_dir) = open_registry()?;
+            let id = model_manager::import::import_model(
+                &path,
+                model_id.as_deref(),
+                mode,
+                &all_models(),
+                &models_dir,
+            )?;
+            if config.mark_downloaded(std::slice::from_ref(&id)) {
+                config.save()?;
+            }
+            println!("Imported {}", id);
+        }
+        Command::ExportBundle { dest, model_ids } => {
+            let (_, models_dir) = open_registry()?;
+            let manifest = model_manager::bundle::export_bundle(
+                &model_ids,
+                &all_models(),
+                &models_dir,
+                &dest,
+            )?;
+            for model in &manifest.models {
+                println!("Exported {}", model.id);
+            }
+            println!("Bundle written to {}", dest.display());
+        }
+        Command::ImportBundle { path, mode } => {
+            let (mut config, models_dir) = open_registry()?;
+            let result =
+                model_manager::bundle::import_bundle(&path, &all_models(), &models_dir, mode)?;
+            if config.mark_downloaded(&result.imported) {
+                config.save()?;
+            }
+            for id in &result.imported {
+                println!("Imported {}", id);
+            }
+            for id in &result.skipped {
+                println!("Skipped {} (already installed)", id);
+            }
+        }
+        Command::Remote(request) => println!("{}", ipc::send(&ipc::socket_path(), &request)?),
+        Command::Transcribe { path } => {
+            // The app resolves relative paths against its own directory.
+            let path = std::env::current_dir()
+                .map_err(|e| e.to_string())?
+                .join(path);
+            let text = ipc::send(&ipc::socket_path(), &ipc::Request::Transcribe { path })?;
+            let targets = OutputTargets::from_config(&Config::load()?);
+            output::output_text(&format!("{}\n", text), &OutputMode::Stdout, &targets)?;
+        }
+        Command::Help => println!("{}", USAGE),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_starts_gui() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(parse(&args(&["--some-launcher-flag"])), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_import_model() {
+        assert_eq!(
+            parse(&args(&["import-model", "/tmp/ggml-base.bin"])),
+            Ok(Some(Command::ImportModel {
+                path: PathBuf::from("/tmp/ggml-base.bin"),
+                model_id: None,
+                mode: ImportMode::Copy,
+            }))
+        );
+        assert_eq!(
+            parse(&args(&[
+                "import-model",
+                "--link",
+                "/tmp/q5.bin",
+                "--id",
+                "small"
+            ])),
+            Ok(Some(Command::ImportModel {
+                path: PathBuf::from("/tmp/q5.bin"),
+                model_id: Some("small".to_string()),
+                mode: ImportMode::Link,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_bundles() {
+        assert_eq!(
+            parse(&args(&["export-bundle", "/mnt/usb", "tiny", "base"])),
+            Ok(Some(Command::ExportBundle {
+                dest: PathBuf::from("/mnt/usb"),
+                model_ids: args(&["tiny", "base"]),
+            }))
+        );
+        assert_eq!(
+            parse(&args(&["import-bundle", "/mnt/usb"])),
+            Ok(Some(Command::ImportBundle {
+                path: PathBuf::from("/mnt/usb"),
+                mode: ImportMode::Copy,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_commands() {
+        assert_eq!(
+            parse(&args(&["toggle"])),
+            Ok(Some(Command::Remote(ipc::Request::Toggle)))
+        );
+        assert_eq!(
+            parse(&args(&["status"])),
+            Ok(Some(Command::Remote(ipc::Request::Status)))
+        );
+        assert_eq!(
+            parse(&args(&["set-model", "large-v3-turbo"])),
+            Ok(Some(Command::Remote(ipc::Request::SetModel {
+                model: "large-v3-turbo".to_string()
+            })))
+        );
+        assert!(parse(&args(&["set-model"])).is_err());
+        assert_eq!(
+            parse(&args(&["transcribe", "meeting.wav"])),
+            Ok(Some(Command::Transcribe {
+                path: PathBuf::from("meeting.wav")
+            }))
+        );
+        assert!(parse(&args(&["transcribe"])).is_err());
+        assert!(parse(&args(&["toggle", "now"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&args(&["import-model"])).is_err());
+        assert!(parse(&args(&["import-model", "a", "b"])).is_err());
+        assert!(parse(&args(&["import-model", "a", "--id"])).is_err());
+        assert!(parse(&args(&["export-bundle", "/mnt/usb"])).is_err());
+        assert!(parse(&args(&["import-bundle", "x", "--force"])).is_err());
+    }
+}
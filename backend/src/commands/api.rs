@@ -0,0 +1,139 @@
+//! Local API commands, plus the glue that runs the API server (`crate::api`)
+//! against the live app: file transcription on the loaded engine and
+//! forwarding of emitted events to connected clients.
+
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Listener, Manager, State};
+
+use super::dictation::{AppControl, AppState};
+use crate::api::openai::{FileTranscriber, Segment};
+use crate::api::{generate_token, ApiContext, ApiServer, FORWARDED_EVENTS};
+
+/// File transcription on the loaded engine, for the API and `whispertype
+/// transcribe`.
+pub(super) struct AppTranscriber(pub(super) AppHandle);
+
+impl FileTranscriber for AppTranscriber {
+    fn transcribe(
+        &self,
+        audio: &[f32],
+        language: &str,
+        prompt: Option<&str>,
+    ) -> Result<Vec<Segment>, String> {
+        let state = self.0.state::<AppState>();
+        let segments = state.engine.transcribe_file(audio, language, prompt)?;
+        // Whisper timestamps are in centiseconds.
+        Ok(segments
+            .into_iter()
+            .map(|s| Segment {
+                start: s.start as f64 / 100.0,
+                end: s.end as f64 / 100.0,
+                text: s.text,
+            })
+            .collect())
+    }
+
+    fn default_language(&self) -> String {
+        let state = self.0.state::<AppState>();
+        let language = state.config.lock().map(|c| c.language.clone());
+        language.unwrap_or_else(|_| "auto".to_string())
+    }
+}
+
+/// Forward app events to API clients. Called once at startup; the hub
+/// outlives server restarts.
+pub fn forward_events(app: &AppHandle) {
+    let hub = app.state::<AppState>().api.hub.clone();
+    for name in FORWARDED_EVENTS {
+        let hub = hub.clone();
+        app.listen_any(name, move |event| {
+            let payload = serde_json::from_str(event.payload()).unwrap_or_default();
+            hub.publish(name, payload);
+        });
+    }
+}
+
+/// Start, restart or stop the API server to match the config. A token is
+/// generated and saved the first time the API is enabled.
+pub fn apply_api_config(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut server = state.api.server.lock().map_err(|e| e.to_string())?;
+    server.take();
+
+    let (port, token) = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        if !config.api_enabled {
+            return Ok(());
+        }
+        if config.api_token.is_empty() {
+            config.api_token = generate_token()?;
+            config.save()?;
+        }
+        (config.api_port, config.api_token.clone())
+    };
+
+    let context = ApiContext {
+        token,
+        hub: state.api.hub.clone(),
+        control: Arc::new(AppControl(app.clone())),
+        transcriber: Arc::new(AppTranscriber(app.clone())),
+    };
+    // A server just stopped above may still hold the port for a moment.
+    let mut attempt = 0;
+    let (handle, serve) = loop {
+        match ApiServer::bind(port, context.clone()) {
+            Ok(bound) => break bound,
+            Err(_) if attempt < 10 => {
+                attempt += 1;
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    eprintln!("api: listening on http://{}", handle.addr());
+    tauri::async_runtime::spawn(serve);
+    *server = Some(handle);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    /// Base URL when running, e.g. `http://127.0.0.1:7837`.
+    pub url: Option<String>,
+    pub token: String,
+}
+
+#[tauri::command]
+pub fn get_api_status(state: State<'_, AppState>) -> Result<ApiStatus, String> {
+    let url = state
+        .api
+        .server
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|s| format!("http://{}", s.addr()));
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(ApiStatus {
+        enabled: config.api_enabled,
+        running: url.is_some(),
+        url,
+        token: config.api_token.clone(),
+    })
+}
+
+/// Replace the API token, disconnecting clients that use the old one.
+#[tauri::command]
+pub fn regenerate_api_token(state: State<'_, AppState>, app: AppHandle) -> Result<String, String> {
+    let token = generate_token()?;
+    {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.api_token = token.clone();
+        config.save()?;
+    }
+    apply_api_config(&app)?;
+    Ok(token)
+}
@@ -1,4 +1,4 @@
-use tauri::State;
+use tauri::{AppHandle, State};
 
 use crate::commands::dictation::AppState;
 use crate::config::Config;
@@ -10,9 +10,37 @@ pub fn get_config(state: State<'_, AppState>) -> Result<Config, String> {
 }
 
 #[tauri::command]
-pub fn update_config(config: Config, state: State<'_, AppState>) -> Result<(), String> {
+pub fn update_config(
+    config: Config,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
     let mut current = state.config.lock().map_err(|e| e.to_string())?;
-    *current = config;
+    state
+        .downloads
+        .set_max_concurrent(config.max_concurrent_downloads);
+    crate::transcription::models::set_mirror(config.model_mirror.clone());
+    if config.model_catalog_url != current.model_catalog_url {
+        let url = config.model_catalog_url.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::model_manager::catalog::refresh_catalog(url.as_deref()).await {
+                eprintln!("Model catalog refresh failed: {}", e);
+            }
+        });
+    }
+    let api_changed =
+        config.api_enabled != current.api_enabled || config.api_port != current.api_port;
+    let wyoming_changed = config.wyoming_enabled != current.wyoming_enabled
+        || config.wyoming_host != current.wyoming_host
+        || config.wyoming_port != current.wyoming_port;
+    current.apply_update(config);
     current.save()?;
+    drop(current);
+    if api_changed {
+        crate::commands::api::apply_api_config(&app)?;
+    }
+    if wyoming_changed {
+        crate::commands::wyoming::apply_wyoming_config(&app)?;
+    }
     Ok(())
 }
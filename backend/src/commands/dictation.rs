@@ -4,14 +4,23 @@
 use ringbuf::traits::Split;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::audio::{AudioMessage, AudioPipeline};
+use crate::api::{ApiHost, DictationControl};
+use crate::audio::AudioPipeline;
+use crate::commands::models::load_model_then_start;
 use crate::config::{Config, StreamEngineConfig};
+use crate::focus;
+use crate::model_manager::sync::ModelsWatcher;
+use crate::model_manager::DownloadManager;
 use crate::output;
-use crate::transcription::agreement::LocalAgreement;
-use crate::transcription::engine::TranscriptionEngine;
+use crate::output::sink::OutputSink;
+use crate::transcription::engine::{TranscriptionEngine, WhisperRecognizer};
+use crate::transcription::loader::ModelLoadTracker;
 use crate::transcription::moonshine::MoonshineEngine;
+use crate::transcription::session::DictationSession;
+use crate::transcription::streaming::{Recognizer, StreamingTranscriber};
+use crate::wyoming::WyomingServer;
 
 pub struct AppState {
     pub engine: Arc<TranscriptionEngine>,
@@ -20,6 +29,11 @@ pub struct AppState {
     pub transcription_thread: Mutex<Option<std::thread::JoinHandle<()>>>,
     pub audio_handle: Mutex<Option<crate::audio::capture::AudioHandle>>,
     pub last_shortcut: Mutex<Option<Instant>>,
+    pub downloads: DownloadManager,
+    pub models_watcher: Mutex<Option<ModelsWatcher>>,
+    pub model_load: ModelLoadTracker,
+    pub api: ApiHost,
+    pub wyoming: Mutex<Option<WyomingServer>>,
 }
 
 fn join_transcription_thread(state: &AppState) {
@@ -35,6 +49,31 @@ fn join_transcription_thread(state: &AppState) {
     audio_lock.take();
 }
 
+/// The settings for a dictation session: `config` with the overrides of the
+/// profile matching the focused window, plus that profile's model.
+fn session_config(config: &Config) -> (Config, Option<String>) {
+    if config.profiles.is_empty() {
+        return (config.clone(), None);
+    }
+    let window = match focus::focused_window() {
+        Ok(window) => window,
+        Err(e) => {
+            eprintln!("profiles: focused window unknown ({}); using defaults", e);
+            return (config.clone(), None);
+        }
+    };
+    match config.profile_for(&window.class, &window.title) {
+        Some(profile) => {
+            eprintln!(
+                "profiles: using '{}' for {} ({})",
+                profile.name, window.class, window.title
+            );
+            (config.with_profile(profile), profile.model.clone())
+        }
+        None => (config.clone(), None),
+    }
+}
+
 /// Core toggle logic, callable from both Tauri commands and the global hotkey handler.
 pub fn toggle_dictation_inner(state: &AppState, app: &AppHandle) -> Result<bool, String> {
     if state.pipeline.is_running() {
@@ -50,14 +89,38 @@ pub fn toggle_dictation_inner(state: &AppState, app: &AppHandle) -> Result<bool,
         app.emit("dictation-status", "idle").ok();
         Ok(false)
     } else {
+        // A model is still loading (e.g. the startup auto-load): queue the start
+        // behind it, or un-queue it if the user toggles again.
+        if let Some(queued) = state.model_load.toggle_queued_start() {
+            app.emit("dictation-status", "loading").ok();
+            return Ok(queued);
+        }
+
+        let (config, profile_model) = {
+            let config = state.config.lock().map_err(|e| e.to_string())?;
+            session_config(&config)
+        };
+
+        // The focused application's profile wants another model, or a
+        // profile's model is loaded and this session has none: load the right
+        // one and start once it is ready.
+        let loaded = state.engine.get_active_model();
+        if let Some(model) = config.model_to_load(profile_model.as_deref(), loaded.as_deref()) {
+            match load_model_then_start(app, &model) {
+                Ok(()) => return Ok(true),
+                Err(e) if profile_model.is_some() => return Err(e),
+                // The default is not a loadable Whisper model (e.g. deleted);
+                // dictate with the one that is loaded.
+                Err(e) => eprintln!("profiles: not switching back to '{}': {}", model, e),
+            }
+        }
+
         if !state.engine.is_loaded() {
             return Err(
                 "No model loaded. Please load a model before starting dictation.".to_string(),
             );
         }
 
-        let config = state.config.lock().map_err(|e| e.to_string())?;
-
         let mut handle_lock = state.audio_handle.lock().unwrap();
 
         if handle_lock.is_some() {
@@ -84,9 +147,9 @@ pub fn toggle_dictation_inner(state: &AppState, app: &AppHandle) -> Result<bool,
         )?;
 
         let language = config.language.clone();
-        let output_mode = config.output_mode.clone();
+        let format = config.format_rules();
+        let (mut sink, live) = output::live::from_config(&config);
         let stream_engine_config = config.stream_engine.clone();
-        drop(config);
         drop(handle_lock);
 
         // Load Moonshine BEFORE starting the audio pipeline so that all ORT
@@ -126,10 +189,10 @@ pub fn toggle_dictation_inner(state: &AppState, app: &AppHandle) -> Result<bool,
         let app_clone = app.clone();
 
         let handle = std::thread::spawn(move || {
-            let mut whisper_state = match engine.create_inference_state() {
-                Ok(s) => {
+            let whisper = match WhisperRecognizer::new(engine, &language) {
+                Ok(w) => {
                     eprintln!("whisper: inference state created (once per session)");
-                    s
+                    w
                 }
                 Err(e) => {
                     app_clone
@@ -142,251 +205,22 @@ pub fn toggle_dictation_inner(state: &AppState, app: &AppHandle) -> Result<bool,
                     return;
                 }
             };
-
-            let mut moonshine = moonshine;
-
-            let mut audio_buf: Vec<f32> = Vec::new();
-            let mut agreement = LocalAgreement::new();
-            let min_samples: usize = 16000; // 1.0s minimum to avoid hallucination
-            let max_samples: usize = 16000 * 30; // 30s maximum buffer
-            let mut has_output = false; // tracks whether we've typed anything this utterance
-
-            while let Ok(msg) = receiver.recv() {
-                let mut got_end = false;
-
-                match msg {
-                    AudioMessage::Segment(seg) => audio_buf.extend_from_slice(&seg),
-                    AudioMessage::EndOfSpeech => got_end = true,
-                }
-
-                // Drain all queued messages to get latest audio state
-                while let Ok(m) = receiver.try_recv() {
-                    match m {
-                        AudioMessage::Segment(seg) => audio_buf.extend_from_slice(&seg),
-                        AudioMessage::EndOfSpeech => got_end = true,
-                    }
-                }
-
-                // Cap buffer at maximum window
-                if audio_buf.len() > max_samples {
-                    let excess = audio_buf.len() - max_samples;
-                    audio_buf.drain(..excess);
-                }
-
-                // Skip inference if not enough audio and not end of speech
-                if audio_buf.len() < min_samples {
-                    if got_end {
-                        audio_buf.clear();
-                        agreement.reset();
-                        has_output = false;
-                    }
-                    continue;
-                }
-
-                // === Dual-path inference ===
-                if got_end {
-                    // --- EndOfSpeech: quality pass ---
-                    // One last Moonshine stream pass through agreement (dual-path only)
-                    if let Some(ref mut ms) = moonshine {
-                        if let Ok(ms_text) = ms.transcribe(&audio_buf) {
-                            if !ms_text.is_empty() {
-                                let result = agreement.process(&ms_text);
-                                if !result.newly_confirmed.is_empty() {
-                                    let output = if has_output {
-                                        format!(" {}", result.newly_confirmed)
-                                    } else {
-                                        result.newly_confirmed.clone()
-                                    };
-                                    if let Err(e) = output::output_text(&output, &output_mode) {
-                                        app_clone
-                                            .emit("output-error", format!("Output error: {}", e))
-                                            .ok();
-                                    }
-                                    has_output = true;
-                                    app_clone
-                                        .emit(
-                                            "transcription-update",
-                                            serde_json::json!({
-                                                "text": result.newly_confirmed,
-                                                "is_partial": false,
-                                            }),
-                                        )
-                                        .ok();
-                                }
-                            }
-                        }
-                    }
-
-                    // Whisper quality pass on full utterance
-                    match engine.transcribe(&mut whisper_state, &audio_buf, &language) {
-                        Ok(segments) => {
-                            let text: String = segments
-                                .iter()
-                                .map(|s| s.text.as_str())
-                                .collect::<Vec<_>>()
-                                .join(" ");
-                            let text = text.trim().to_string();
-
-                            // In whisper-only mode, process through agreement (v0.2.0 behavior)
-                            if moonshine.is_none() && !text.is_empty() {
-                                let result = agreement.process(&text);
-                                if !result.newly_confirmed.is_empty() {
-                                    let output = if has_output {
-                                        format!(" {}", result.newly_confirmed)
-                                    } else {
-                                        result.newly_confirmed.clone()
-                                    };
-                                    if let Err(e) = output::output_text(&output, &output_mode) {
-                                        app_clone
-                                            .emit("output-error", format!("Output error: {}", e))
-                                            .ok();
-                                    }
-                                    has_output = true;
-                                    app_clone
-                                        .emit(
-                                            "transcription-update",
-                                            serde_json::json!({
-                                                "text": result.newly_confirmed,
-                                                "is_partial": false,
-                                            }),
-                                        )
-                                        .ok();
-                                }
-                                // Update tentative display (v0.2.0 behavior)
-                                app_clone
-                                    .emit(
-                                        "transcription-update",
-                                        serde_json::json!({
-                                            "text": result.tentative,
-                                            "is_partial": true,
-                                        }),
-                                    )
-                                    .ok();
-                            }
-
-                            // Finalize: confirm remaining tentative words
-                            let remaining = agreement.finalize();
-                            if !remaining.is_empty() {
-                                let output = if has_output {
-                                    format!(" {}", remaining)
-                                } else {
-                                    remaining.clone()
-                                };
-                                if let Err(e) = output::output_text(&output, &output_mode) {
-                                    app_clone
-                                        .emit("output-error", format!("Output error: {}", e))
-                                        .ok();
-                                }
-                                app_clone
-                                    .emit(
-                                        "transcription-update",
-                                        serde_json::json!({
-                                            "text": remaining,
-                                            "is_partial": false,
-                                        }),
-                                    )
-                                    .ok();
-                            }
-                        }
-                        Err(e) => {
-                            // Still finalize agreement even if Whisper fails
-                            let remaining = agreement.finalize();
-                            if !remaining.is_empty() {
-                                let output = if has_output {
-                                    format!(" {}", remaining)
-                                } else {
-                                    remaining.clone()
-                                };
-                                let _ = output::output_text(&output, &output_mode);
-                                app_clone
-                                    .emit(
-                                        "transcription-update",
-                                        serde_json::json!({
-                                            "text": remaining,
-                                            "is_partial": false,
-                                        }),
-                                    )
-                                    .ok();
-                            }
-                            app_clone
-                                .emit(
-                                    "transcription-error",
-                                    format!("Whisper quality pass failed: {}", e),
-                                )
-                                .ok();
-                        }
-                    }
-
-                    // Clear tentative display and reset for next utterance
-                    app_clone
-                        .emit(
-                            "transcription-update",
-                            serde_json::json!({ "text": "", "is_partial": true }),
-                        )
-                        .ok();
-                    audio_buf.clear();
-                    has_output = false;
-                } else {
-                    // --- During speech: stream pass ---
-                    let text = if let Some(ref mut ms) = moonshine {
-                        ms.transcribe(&audio_buf).unwrap_or_default()
-                    } else {
-                        match engine.transcribe(&mut whisper_state, &audio_buf, &language) {
-                            Ok(segments) => segments
-                                .iter()
-                                .map(|s| s.text.as_str())
-                                .collect::<Vec<_>>()
-                                .join(" ")
-                                .trim()
-                                .to_string(),
-                            Err(e) => {
-                                app_clone
-                                    .emit(
-                                        "transcription-error",
-                                        format!("Transcription failed: {}", e),
-                                    )
-                                    .ok();
-                                String::new()
-                            }
-                        }
-                    };
-
-                    if !text.is_empty() {
-                        let result = agreement.process(&text);
-                        if !result.newly_confirmed.is_empty() {
-                            let output = if has_output {
-                                format!(" {}", result.newly_confirmed)
-                            } else {
-                                result.newly_confirmed.clone()
-                            };
-                            if let Err(e) = output::output_text(&output, &output_mode) {
-                                app_clone
-                                    .emit("output-error", format!("Output error: {}", e))
-                                    .ok();
-                            }
-                            has_output = true;
-                            app_clone
-                                .emit(
-                                    "transcription-update",
-                                    serde_json::json!({
-                                        "text": result.newly_confirmed,
-                                        "is_partial": false,
-                                    }),
-                                )
-                                .ok();
-                        }
-                        app_clone
-                            .emit(
-                                "transcription-update",
-                                serde_json::json!({
-                                    "text": result.tentative,
-                                    "is_partial": true,
-                                }),
-                            )
-                            .ok();
-                    }
-                }
+            let stream = moonshine.map(|m| Box::new(m) as Box<dyn Recognizer>);
+            let mut session = DictationSession::with_format(
+                StreamingTranscriber::new(Box::new(whisper), stream),
+                format,
+            );
+            if let Some(live) = live {
+                session = session.with_live(live);
             }
+            let mut receiver = receiver;
+            session.run(
+                &mut receiver,
+                |text| sink.write(text),
+                |event| {
+                    app_clone.emit(event.name(), event.payload()).ok();
+                },
+            );
 
             app_clone.emit("dictation-status", "idle").ok();
         });
@@ -401,16 +235,23 @@ pub fn toggle_dictation(state: State<'_, AppState>, app: AppHandle) -> Result<bo
     toggle_dictation_inner(&state, &app)
 }
 
-#[tauri::command]
-pub fn start_dictation(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
-    if !state.pipeline.is_running() {
-        toggle_dictation_inner(&state, &app)?;
+/// Start dictation if it is not already running. While a model is loading the
+/// start is queued behind it; repeating it keeps it queued.
+pub fn start_dictation_inner(state: &AppState, app: &AppHandle) -> Result<(), String> {
+    if state.pipeline.is_running() {
+        return Ok(());
     }
+    if state.model_load.queue_start() {
+        app.emit("dictation-status", "loading").ok();
+        return Ok(());
+    }
+    toggle_dictation_inner(state, app)?;
     Ok(())
 }
 
-#[tauri::command]
-pub fn stop_dictation(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
+/// Stop dictation (or cancel a start queued behind a model load).
+pub fn stop_dictation_inner(state: &AppState, app: &AppHandle) {
+    state.model_load.cancel_queued_start();
     if state.pipeline.is_running() {
         if let Some(handle) = state.audio_handle.lock().unwrap().as_ref() {
             let _ = handle
@@ -418,9 +259,37 @@ pub fn stop_dictation(state: State<'_, AppState>, app: AppHandle) -> Result<(),
                 .send(crate::audio::capture::AudioCommand::Stop);
         }
         state.pipeline.stop();
-        join_transcription_thread(&state);
+        join_transcription_thread(state);
         app.emit("dictation-status", "idle").ok();
     }
+}
+
+/// Dictation control for the local API and D-Bus service.
+pub struct AppControl(pub AppHandle);
+
+impl DictationControl for AppControl {
+    fn start(&self) -> Result<(), String> {
+        start_dictation_inner(&self.0.state::<AppState>(), &self.0)
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        stop_dictation_inner(&self.0.state::<AppState>(), &self.0);
+        Ok(())
+    }
+
+    fn toggle(&self) -> Result<bool, String> {
+        toggle_dictation_inner(&self.0.state::<AppState>(), &self.0)
+    }
+}
+
+#[tauri::command]
+pub fn start_dictation(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
+    start_dictation_inner(&state, &app)
+}
+
+#[tauri::command]
+pub fn stop_dictation(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
+    stop_dictation_inner(&state, &app);
     Ok(())
 }
 
@@ -443,6 +312,11 @@ mod tests {
             transcription_thread: Mutex::new(None),
             audio_handle: Mutex::new(None),
             last_shortcut: Mutex::new(None),
+            downloads: DownloadManager::default(),
+            models_watcher: Mutex::new(None),
+            model_load: ModelLoadTracker::new(),
+            api: ApiHost::default(),
+            wyoming: Mutex::new(None),
         };
 
         assert!(
@@ -465,6 +339,11 @@ mod tests {
             transcription_thread: Mutex::new(None),
             audio_handle: Mutex::new(None),
             last_shortcut: Mutex::new(None),
+            downloads: DownloadManager::default(),
+            models_watcher: Mutex::new(None),
+            model_load: ModelLoadTracker::new(),
+            api: ApiHost::default(),
+            wyoming: Mutex::new(None),
         };
 
         // Should not panic or block
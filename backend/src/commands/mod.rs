@@ -1,4 +1,7 @@
+pub mod api;
 pub mod config;
 pub mod dictation;
 pub mod models;
+pub mod remote;
 pub mod system;
+pub mod wyoming;
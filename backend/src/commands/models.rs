@@ -1,9 +1,19 @@
+use std::time::Instant;
 use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::commands::dictation::AppState;
-use crate::config::Config;
+use crate::bench::runner::BenchOptions;
+use crate::bench::{self, BenchReport};
+use crate::commands::dictation::{toggle_dictation_inner, AppState};
+use crate::config::{Config, CustomModel};
+use crate::hardware;
 use crate::model_manager;
-use crate::transcription::{get_model_registry, ModelType, WhisperModel};
+use crate::model_manager::bundle::{BundleImport, BundleManifest};
+use crate::model_manager::import::ImportMode;
+use crate::model_manager::storage::{CleanupResult, StorageReport};
+use crate::transcription::loader::{ModelLoadEvent, ModelLoadStatus};
+use crate::transcription::models::{all_models, find_model, set_custom_models};
+use crate::transcription::options::EngineMode;
+use crate::transcription::{ModelType, WhisperModel};
 
 #[derive(serde::Serialize)]
 pub struct ModelInfo {
@@ -13,6 +23,9 @@ pub struct ModelInfo {
     pub size_bytes: u64,
     pub vram_mb: u16,
     pub downloaded: bool,
+    pub custom: bool,
+    /// Real-time factor from the last benchmark run, if this model was in it.
+    pub measured_rtf: Option<f64>,
 }
 
 impl From<&WhisperModel> for ModelInfo {
@@ -24,27 +37,43 @@ impl From<&WhisperModel> for ModelInfo {
             size_bytes: m.size_bytes,
             vram_mb: m.vram_mb,
             downloaded: model_manager::is_model_downloaded(&m.id),
+            custom: m.custom,
+            measured_rtf: None,
         }
     }
 }
 
 #[tauri::command]
 pub fn list_models() -> Vec<ModelInfo> {
-    get_model_registry().iter().map(ModelInfo::from).collect()
+    let measured = bench::load_report()
+        .map(|r| r.rtf_by_model())
+        .unwrap_or_default();
+    all_models()
+        .iter()
+        .map(|m| ModelInfo {
+            measured_rtf: measured.get(&m.id).copied(),
+            ..ModelInfo::from(m)
+        })
+        .collect()
 }
 
 #[tauri::command]
 pub async fn download_model(model_id: String, app: AppHandle) -> Result<(), String> {
-    model_manager::download_model(&model_id, &app).await?;
-
     let state = app.state::<AppState>();
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    if !config.downloaded_models.contains(&model_id) {
-        config.downloaded_models.push(model_id);
-        config.save()?;
-    }
+    let (handle, id) = (app.clone(), model_id.clone());
+    state
+        .downloads
+        .run(&model_id, |token| async move {
+            model_manager::download_model(&id, &handle, &token).await
+        })
+        .await?;
 
-    Ok(())
+    mark_downloaded(&state, &[model_id])
+}
+
+#[tauri::command]
+pub fn cancel_download(model_id: String, state: State<'_, AppState>) -> bool {
+    state.downloads.cancel(&model_id)
 }
 
 #[tauri::command]
@@ -62,48 +91,481 @@ pub fn delete_model(model_id: String, state: State<'_, AppState>) -> Result<(),
 }
 
 #[tauri::command]
-pub async fn load_model(
-    model_id: String,
-    state: State<'_, AppState>,
-    app: AppHandle,
-) -> Result<(), String> {
-    app.emit("dictation-status", "loading").ok();
+pub async fn load_model(model_id: String, app: AppHandle) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || switch_model_blocking(&app, &model_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-    let registry = get_model_registry();
-    let model = registry
-        .iter()
-        .find(|m| m.id == model_id)
-        .ok_or_else(|| format!("Unknown model: {}", model_id))?;
+/// Load a model and make it the default for the next start. Blocking.
+pub fn switch_model_blocking(app: &AppHandle, model_id: &str) -> Result<(), String> {
+    load_model_blocking(app, model_id)?;
+    let state = app.state::<AppState>();
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    config.default_model = model_id.to_string();
+    config.save()
+}
 
+/// Load a Whisper model into the engine, emitting `model-load` progress and
+/// `dictation-status` events. A dictation start requested while the load was
+/// running is performed afterwards. Blocking.
+pub fn load_model_blocking(app: &AppHandle, model_id: &str) -> Result<(), String> {
     // Moonshine ONNX models are loaded on-demand by the dictation thread via
     // transcribe-rs, not through the Whisper engine.  Only Whisper GGML models
     // should be loaded here.
-    if model.model_type == ModelType::MoonshineOnnx {
-        app.emit("dictation-status", "idle").ok();
+    let Some(model_path) = whisper_model_path(model_id)? else {
         return Ok(());
-    }
+    };
+    app.state::<AppState>().model_load.begin(model_id);
+    run_load(app, model_id, &model_path)
+}
+
+/// Load a Whisper model in the background and start dictation once it is
+/// ready, e.g. for an output profile whose model is not the loaded one.
+pub fn load_model_then_start(app: &AppHandle, model_id: &str) -> Result<(), String> {
+    let model_path = whisper_model_path(model_id)?
+        .ok_or_else(|| format!("Not a Whisper model: {}", model_id))?;
+    let state = app.state::<AppState>();
+    // Queue the start before the load thread can finish.
+    state.model_load.begin(model_id);
+    state.model_load.queue_start();
+
+    let app = app.clone();
+    let model_id = model_id.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = run_load(&app, &model_id, &model_path) {
+            eprintln!("Failed to load profile model '{}': {}", model_id, e);
+        }
+    });
+    Ok(())
+}
 
-    let model_path = Config::models_dir().join(&model.filename);
+/// The file of a downloaded Whisper model; `None` for Moonshine models.
+fn whisper_model_path(model_id: &str) -> Result<Option<std::path::PathBuf>, String> {
+    let model = find_model(model_id).ok_or_else(|| format!("Unknown model: {}", model_id))?;
+    if model.model_type == ModelType::MoonshineOnnx {
+        return Ok(None);
+    }
+    let model_path = model.model_path();
     if !model_path.exists() {
         return Err(format!("Model not downloaded: {}", model_id));
     }
+    Ok(Some(model_path))
+}
 
-    let engine = state.engine.clone();
-    let mid = model_id.clone();
+/// The load itself, after `ModelLoadTracker::begin`.
+fn run_load(app: &AppHandle, model_id: &str, model_path: &std::path::Path) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let options = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        hardware::engine_options(&config, hardware::info())
+    };
 
-    tokio::task::spawn_blocking(move || engine.load_model(&model_path, &mid))
-        .await
-        .map_err(|e| e.to_string())??;
+    app.emit("dictation-status", "loading").ok();
+    emit_model_load(app, model_id, ModelLoadStatus::Loading, 0, None, None);
+
+    let started = Instant::now();
+    let result = {
+        let _serial = state.model_load.serialize();
+        state.engine.load_model(model_path, model_id, &options)
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let start_queued = state.model_load.finish();
+
+    match &result {
+        Ok(mode) => emit_model_load(
+            app,
+            model_id,
+            ModelLoadStatus::Loaded,
+            elapsed_ms,
+            None,
+            Some(mode.clone()),
+        ),
+        Err(e) => emit_model_load(
+            app,
+            model_id,
+            ModelLoadStatus::Failed,
+            elapsed_ms,
+            Some(e.clone()),
+            None,
+        ),
+    }
+    app.emit("dictation-status", "idle").ok();
+
+    if start_queued && result.is_ok() {
+        if let Err(e) = toggle_dictation_inner(&state, app) {
+            app.emit("transcription-error", e).ok();
+        }
+    }
+    result.map(|_| ())
+}
+
+fn emit_model_load(
+    app: &AppHandle,
+    model_id: &str,
+    status: ModelLoadStatus,
+    elapsed_ms: u64,
+    error: Option<String>,
+    engine: Option<EngineMode>,
+) {
+    let event = ModelLoadEvent {
+        model_id: model_id.to_string(),
+        status,
+        elapsed_ms,
+        error,
+        engine,
+    };
+    app.emit("model-load", event).ok();
+}
+
+/// Load `Config::default_model` at startup. Skipped if it is not downloaded
+/// (e.g. first run) or is a Moonshine model. The load is registered with the
+/// tracker before this returns, so a dictation start right after launch queues
+/// behind it; the load itself runs on a blocking thread.
+pub fn load_default_model(app: &AppHandle) {
+    let model_id = {
+        let state = app.state::<AppState>();
+        let config = state.config.lock().unwrap();
+        config.default_model.clone()
+    };
+    if !model_manager::is_model_downloaded(&model_id) {
+        eprintln!(
+            "Default model '{}' not downloaded; not auto-loading",
+            model_id
+        );
+        return;
+    }
+    let model_path = match whisper_model_path(&model_id) {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("Failed to auto-load default model '{}': {}", model_id, e);
+            return;
+        }
+    };
+    app.state::<AppState>().model_load.begin(&model_id);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = run_load(&app, &model_id, &model_path) {
+            eprintln!("Failed to auto-load default model '{}': {}", model_id, e);
+        }
+    });
+}
+
+/// The model currently being loaded, if any.
+#[tauri::command]
+pub fn get_loading_model(state: State<'_, AppState>) -> Option<String> {
+    state.model_load.loading_model()
+}
+
+/// Register a custom GGML model. Local files are validated (and hashed, if a
+/// checksum is given) immediately; URL models are fetched by `download_model`.
+#[tauri::command]
+pub async fn add_custom_model(
+    model: CustomModel,
+    state: State<'_, AppState>,
+) -> Result<ModelInfo, String> {
+    let existing = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .custom_models
+        .clone();
+    let model = model_manager::custom::validate_custom_model(&model, &existing)?;
+
+    if let (false, Some(expected)) = (model.is_url(), model.sha256.clone()) {
+        let path = std::path::PathBuf::from(&model.source);
+        tokio::task::spawn_blocking(move || model_manager::custom::verify_sha256(&path, &expected))
+            .await
+            .map_err(|e| e.to_string())??;
+    }
 
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    config.default_model = model_id;
+    if config.custom_models.iter().any(|m| m.id == model.id) {
+        return Err(format!("A model with ID '{}' already exists", model.id));
+    }
+    config.custom_models.push(model.clone());
     config.save()?;
+    set_custom_models(&config.custom_models);
+
+    Ok(ModelInfo::from(&WhisperModel::from_custom(&model)))
+}
+
+/// Unregister a custom model. A copy downloaded from a URL is deleted; a model
+/// registered from a local path is left on disk.
+#[tauri::command]
+pub fn remove_custom_model(model_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let model = find_model(&model_id)
+        .filter(|m| m.custom)
+        .ok_or_else(|| format!("Unknown custom model: {}", model_id))?;
+
+    if state.engine.get_active_model().as_deref() == Some(model_id.as_str()) {
+        state.engine.unload_model()?;
+    }
+    model_manager::delete_model(&model.id)?;
+
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    config.custom_models.retain(|m| m.id != model_id);
+    config.downloaded_models.retain(|m| m != &model_id);
+    config.save()?;
+    set_custom_models(&config.custom_models);
 
-    app.emit("dictation-status", "idle").ok();
     Ok(())
 }
 
+/// Copy or link an existing `ggml-*.bin` file or Moonshine directory into the
+/// models directory. Returns the ID of the imported model.
+#[tauri::command]
+pub async fn import_model(
+    path: String,
+    model_id: Option<String>,
+    mode: Option<ImportMode>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let id = tokio::task::spawn_blocking(move || {
+        model_manager::import::import_model(
+            std::path::Path::new(&path),
+            model_id.as_deref(),
+            mode.unwrap_or_default(),
+            &all_models(),
+            &Config::models_dir(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    mark_downloaded(&state, std::slice::from_ref(&id))?;
+    Ok(id)
+}
+
+/// Write the given installed models and a manifest into `dest` for transfer to
+/// an offline machine.
+#[tauri::command]
+pub async fn export_model_bundle(
+    model_ids: Vec<String>,
+    dest: String,
+) -> Result<BundleManifest, String> {
+    tokio::task::spawn_blocking(move || {
+        model_manager::bundle::export_bundle(
+            &model_ids,
+            &all_models(),
+            &Config::models_dir(),
+            std::path::Path::new(&dest),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Verify and install every model in a bundle created by `export_model_bundle`.
+#[tauri::command]
+pub async fn import_model_bundle(
+    path: String,
+    mode: Option<ImportMode>,
+    state: State<'_, AppState>,
+) -> Result<BundleImport, String> {
+    let result = tokio::task::spawn_blocking(move || {
+        model_manager::bundle::import_bundle(
+            std::path::Path::new(&path),
+            &all_models(),
+            &Config::models_dir(),
+            mode.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    mark_downloaded(&state, &result.imported)?;
+    Ok(result)
+}
+
+fn mark_downloaded(state: &State<'_, AppState>, ids: &[String]) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    if config.mark_downloaded(ids) {
+        config.save()?;
+    }
+    Ok(())
+}
+
+/// Per-model disk usage plus leftover and unrecognized files in the models directory.
+#[tauri::command]
+pub async fn get_storage_report() -> Result<StorageReport, String> {
+    tokio::task::spawn_blocking(|| {
+        model_manager::storage::scan(&Config::models_dir(), &all_models())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Delete `.tmp` leftovers of interrupted downloads and imports. Temp files of
+/// downloads that are still running are kept.
+#[tauri::command]
+pub fn cleanup_models_dir(state: State<'_, AppState>) -> Result<CleanupResult, String> {
+    let report = model_manager::storage::scan(&Config::models_dir(), &all_models())?;
+    Ok(model_manager::storage::remove_orphans(&report, |id| {
+        state.downloads.is_downloading(id)
+    }))
+}
+
+/// Move all models to `path` (or back to the default location for `None`) and
+/// remember the choice in `Config::models_location`.
+#[tauri::command]
+pub async fn set_models_location(
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let target = path
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(Config::default_models_dir);
+    if !target.is_absolute() {
+        return Err(format!(
+            "Models directory must be absolute: {}",
+            target.display()
+        ));
+    }
+    let current = Config::models_dir();
+    if target == current {
+        return Ok(());
+    }
+    if all_models()
+        .iter()
+        .any(|m| state.downloads.is_downloading(&m.id))
+    {
+        return Err("Cannot move models while a download is running".to_string());
+    }
+
+    let (from, to) = (current, target.clone());
+    tokio::task::spawn_blocking(move || model_manager::storage::migrate_models_dir(&from, &to))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let location = (target != Config::default_models_dir()).then_some(target);
+    Config::set_models_dir(location.clone());
+    {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.models_location = location;
+        config.save()?;
+    }
+    start_models_watcher(&app);
+    sync_downloaded_models(&app);
+    Ok(())
+}
+
+/// Reconcile `Config::downloaded_models` with the models directory and emit
+/// `models-changed` if anything was added or removed outside the app.
+pub fn sync_downloaded_models(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(mut config) = state.config.lock() else {
+        return;
+    };
+    let changes = model_manager::sync::reconcile(&mut config);
+    if changes.is_empty() {
+        return;
+    }
+    if let Err(e) = config.save() {
+        eprintln!("Failed to save config after model sync: {}", e);
+    }
+    drop(config);
+    app.emit("models-changed", &changes).ok();
+}
+
+/// (Re)start watching the current models directory.
+pub fn start_models_watcher(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let handle = app.clone();
+    let dir = Config::models_dir();
+    let watcher = std::fs::create_dir_all(&dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| model_manager::sync::watch(&dir, move || sync_downloaded_models(&handle)));
+    match watcher {
+        Ok(w) => *state.models_watcher.lock().unwrap() = Some(w),
+        Err(e) => eprintln!("Model directory watcher disabled: {}", e),
+    }
+}
+
+/// Re-fetch `Config::model_catalog_url` and apply it to the registry. Returns the
+/// number of models in the resulting registry (excluding custom models).
+#[tauri::command]
+pub async fn refresh_model_catalog(state: State<'_, AppState>) -> Result<usize, String> {
+    let url = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .model_catalog_url
+        .clone();
+    model_manager::catalog::refresh_catalog(url.as_deref()).await
+}
+
 #[tauri::command]
 pub fn get_active_model(state: State<'_, AppState>) -> Option<String> {
     state.engine.get_active_model()
 }
+
+/// Device, flash-attention and thread settings the active model runs with.
+#[tauri::command]
+pub fn get_engine_mode(state: State<'_, AppState>) -> Option<EngineMode> {
+    state.engine.get_engine_mode()
+}
+
+/// Payload of the `benchmark-progress` event.
+#[derive(Clone, serde::Serialize)]
+struct BenchmarkProgress {
+    model_id: String,
+    index: usize,
+    total: usize,
+}
+
+/// Benchmark downloaded models (all of them unless `model_ids` is given) on a
+/// WAV clip or the built-in synthetic clip, save the report and return it.
+#[tauri::command]
+pub async fn run_benchmark(
+    model_ids: Option<Vec<String>>,
+    clip: Option<String>,
+    passes: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BenchReport, String> {
+    if state.pipeline.is_running() || state.model_load.loading_model().is_some() {
+        return Err("Stop dictation and wait for model loads before benchmarking".to_string());
+    }
+    let options = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        BenchOptions {
+            clip: clip.map(std::path::PathBuf::from),
+            passes: passes.unwrap_or(bench::DEFAULT_PASSES),
+            model_ids,
+            language: config.language.clone(),
+            engine: hardware::engine_options(&config, hardware::info()),
+            measure_rss: false,
+        }
+    };
+
+    let report = tokio::task::spawn_blocking(move || {
+        bench::runner::run(
+            &options,
+            &all_models(),
+            hardware::info(),
+            |model_id, index, total| {
+                let progress = BenchmarkProgress {
+                    model_id: model_id.to_string(),
+                    index,
+                    total,
+                };
+                app.emit("benchmark-progress", progress).ok();
+            },
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    bench::save_report(&report)?;
+    Ok(report)
+}
+
+/// The last saved benchmark report, if any.
+#[tauri::command]
+pub fn get_benchmark_results() -> Option<BenchReport> {
+    bench::load_report()
+}
@@ -0,0 +1,64 @@
+//! Requests from the control socket (`crate::ipc`), i.e. the `whispertype
+//! toggle|start|stop|status|set-model|transcribe` subcommands and second
+//! launches.
+
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use super::api::AppTranscriber;
+use super::dictation::{AppControl, AppState};
+use super::models::switch_model_blocking;
+use crate::api::openai::{decode_audio, join_text, FileTranscriber};
+use crate::api::DictationControl;
+use crate::ipc::{RemoteControl, Request};
+
+pub struct AppRemote(pub AppHandle);
+
+impl AppRemote {
+    /// Current status plus the loaded model, e.g. `listening (base)`.
+    fn status(&self) -> String {
+        let state = self.0.state::<AppState>();
+        let model = state
+            .engine
+            .get_active_model()
+            .unwrap_or_else(|| "no model loaded".to_string());
+        format!("{} ({})", state.api.hub.status(), model)
+    }
+
+    /// Transcribe an audio file with the loaded model, in the configured
+    /// language.
+    fn transcribe(&self, path: &Path) -> Result<String, String> {
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let audio = decode_audio(&bytes)?;
+        let transcriber = AppTranscriber(self.0.clone());
+        let segments = transcriber.transcribe(&audio, &transcriber.default_language(), None)?;
+        Ok(join_text(&segments))
+    }
+}
+
+impl RemoteControl for AppRemote {
+    fn handle(&self, request: Request) -> Result<String, String> {
+        let control = AppControl(self.0.clone());
+        match request {
+            Request::Toggle => {
+                control.toggle()?;
+            }
+            Request::Start => control.start()?,
+            Request::Stop => control.stop()?,
+            Request::Status => {}
+            Request::SetModel { model } => switch_model_blocking(&self.0, &model)?,
+            Request::Show => {
+                let window = self
+                    .0
+                    .get_webview_window("main")
+                    .ok_or("Main window not found")?;
+                window.unminimize().map_err(|e| e.to_string())?;
+                window.show().map_err(|e| e.to_string())?;
+                window.set_focus().map_err(|e| e.to_string())?;
+            }
+            Request::Transcribe { path } => return self.transcribe(&path),
+        }
+        Ok(self.status())
+    }
+}
@@ -1,4 +1,8 @@
 use crate::audio::capture::AudioCapture;
+use crate::commands::dictation::AppState;
+use crate::hardware::{self, HardwareInfo, Recommendations};
+use crate::transcription::models::all_models;
+use tauri::State;
 
 #[tauri::command]
 pub fn list_audio_devices() -> Result<Vec<String>, String> {
@@ -7,21 +11,33 @@ pub fn list_audio_devices() -> Result<Vec<String>, String> {
 
 #[tauri::command]
 pub fn get_gpu_info() -> Result<serde_json::Value, String> {
-    // Basic GPU detection — parse nvidia-smi output
-    let output = std::process::Command::new("nvidia-smi")
-        .args([
-            "--query-gpu=name,memory.total",
-            "--format=csv,noheader,nounits",
-        ])
-        .output()
-        .map_err(|_| "nvidia-smi not found — CUDA may not be available".to_string())?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let parts: Vec<&str> = stdout.trim().split(", ").collect();
+    let hw = hardware::info();
+    let gpu = hw
+        .gpu
+        .as_ref()
+        .ok_or("nvidia-smi not found — CUDA may not be available")?;
 
     Ok(serde_json::json!({
-        "name": parts.first().unwrap_or(&"Unknown"),
-        "vram_total_mb": parts.get(1).and_then(|s| s.parse::<u32>().ok()).unwrap_or(0),
-        "cuda_available": output.status.success(),
+        "name": gpu.name,
+        "vram_total_mb": gpu.vram_total_mb,
+        "cuda_available": hw.cuda_build,
     }))
 }
+
+/// CPU, SIMD, RAM and GPU details (probed once per run).
+#[tauri::command]
+pub fn get_hardware_info() -> HardwareInfo {
+    hardware::info().clone()
+}
+
+/// Rank all known models by expected real-time factor on this machine, using
+/// the configured compute device and thread count.
+#[tauri::command]
+pub fn recommend_models(state: State<'_, AppState>) -> Result<Recommendations, String> {
+    let hw = hardware::info();
+    let options = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        hardware::engine_options(&config, hw)
+    };
+    Ok(hardware::recommend(&all_models(), hw, &options))
+}
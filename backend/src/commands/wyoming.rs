@@ -0,0 +1,97 @@
+//! Runs the Wyoming ASR server (`crate::wyoming`) against the live app:
+//! uploads are gated by the configured VAD, like microphone input, and
+//! transcribed with the loaded Whisper model, the only one advertised.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::dictation::AppState;
+use crate::audio::segmenter::gate_clip;
+use crate::audio::AudioMessage;
+use crate::transcription::models::all_models;
+use crate::wyoming::{AsrModel, WyomingBackend, WyomingServer};
+
+struct AppBackend(AppHandle);
+
+impl WyomingBackend for AppBackend {
+    fn models(&self) -> Vec<AsrModel> {
+        // Requests are decoded with the loaded model, so only it is offered.
+        let Some(loaded) = self.0.state::<AppState>().engine.get_active_model() else {
+            return Vec::new();
+        };
+        all_models()
+            .into_iter()
+            .filter(|m| m.id == loaded)
+            .map(|m| AsrModel {
+                languages: m.languages().iter().map(|l| l.to_string()).collect(),
+                name: m.id,
+                description: m.display_name,
+            })
+            .collect()
+    }
+
+    fn transcribe(&self, audio: &[f32], language: Option<&str>) -> Result<String, String> {
+        let state = self.0.state::<AppState>();
+        let (default_language, vad_backend, vad_threshold) = {
+            let config = state.config.lock().map_err(|e| e.to_string())?;
+            (
+                config.language.clone(),
+                config.vad_backend.clone(),
+                config.vad_threshold,
+            )
+        };
+        // Keep only speech, as the DSP thread would for microphone input.
+        let speech: Vec<f32> = gate_clip(audio, vad_backend, vad_threshold)?
+            .into_iter()
+            .filter_map(|message| match message {
+                AudioMessage::Segment(samples) => Some(samples),
+                AudioMessage::EndOfSpeech => None,
+            })
+            .flatten()
+            .collect();
+        if speech.is_empty() {
+            return Ok(String::new());
+        }
+        let language = language.unwrap_or(&default_language);
+        let segments = state.engine.transcribe_file(&speech, language, None)?;
+        Ok(segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+}
+
+/// Start, restart or stop the Wyoming server to match the config.
+pub fn apply_wyoming_config(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut server = state.wyoming.lock().map_err(|e| e.to_string())?;
+    server.take();
+
+    let (host, port) = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        if !config.wyoming_enabled {
+            return Ok(());
+        }
+        (config.wyoming_host.clone(), config.wyoming_port)
+    };
+
+    let backend: Arc<dyn WyomingBackend> = Arc::new(AppBackend(app.clone()));
+    // A server just stopped above may still hold the port for a moment.
+    let mut attempt = 0;
+    let (handle, serve) = loop {
+        match WyomingServer::bind(&host, port, backend.clone()) {
+            Ok(bound) => break bound,
+            Err(_) if attempt < 10 => {
+                attempt += 1;
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    eprintln!("wyoming: listening on tcp://{}", handle.addr());
+    tauri::async_runtime::spawn(serve);
+    *server = Some(handle);
+    Ok(())
+}
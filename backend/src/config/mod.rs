@@ -1,2 +1,6 @@
+pub mod profiles;
 pub mod settings;
-pub use settings::{Config, OutputMode, StreamEngineConfig};
+pub use profiles::{FormatOverrides, FormatRules, OutputProfile};
+pub use settings::{
+    ComputeDevice, Config, CustomModel, LiveRewrite, OutputMode, StreamEngineConfig, TypingBackend,
+};
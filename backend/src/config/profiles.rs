@@ -0,0 +1,332 @@
+//! Per-application output profiles. When dictation starts, the first profile
+//! whose rules match the focused window overrides the output mode, paste
+//! shortcut, formatting, language and model for that session.
+
+use super::settings::{Config, OutputMode};
+use serde::{Deserialize, Serialize};
+
+/// Formatting applied to confirmed text before it is output.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FormatRules {
+    /// Keep Whisper's capital on the first word of an utterance; `false`
+    /// lowercases it (e.g. for code editors). `I` and acronyms are kept.
+    #[serde(default = "default_capitalize")]
+    pub capitalize: bool,
+    /// Text output after each utterance, e.g. `"\n"` or `" "`.
+    #[serde(default)]
+    pub suffix: String,
+    /// Write spoken numbers, ordinals, currencies, times, dates, percentages
+    /// and units in written form ("twenty three dollars" -> "$23").
+    #[serde(default)]
+    pub normalize: bool,
+    /// Locale for `normalize`, e.g. `en-US`, `en-GB` or `de`; unset follows
+    /// the transcription language.
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+fn default_capitalize() -> bool {
+    true
+}
+
+impl Default for FormatRules {
+    fn default() -> Self {
+        Self {
+            capitalize: default_capitalize(),
+            suffix: String::new(),
+            normalize: false,
+            locale: None,
+        }
+    }
+}
+
+impl FormatRules {
+    /// These rules with each field set in `overrides` replaced.
+    pub fn merged(&self, overrides: &FormatOverrides) -> FormatRules {
+        FormatRules {
+            capitalize: overrides.capitalize.unwrap_or(self.capitalize),
+            suffix: overrides
+                .suffix
+                .clone()
+                .unwrap_or_else(|| self.suffix.clone()),
+            normalize: overrides.normalize.unwrap_or(self.normalize),
+            locale: overrides.locale.clone().or_else(|| self.locale.clone()),
+        }
+    }
+}
+
+/// A profile's changes to `FormatRules`; unset fields keep the global rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FormatOverrides {
+    #[serde(default)]
+    pub capitalize: Option<bool>,
+    #[serde(default)]
+    pub suffix: Option<String>,
+    #[serde(default)]
+    pub normalize: Option<bool>,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Output settings for windows matching `window_class` and `window_title`
+/// (case-insensitive substrings; both must match when both are set). Unset
+/// fields keep the global setting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutputProfile {
+    pub name: String,
+    #[serde(default)]
+    pub window_class: Option<String>,
+    #[serde(default)]
+    pub window_title: Option<String>,
+    #[serde(default)]
+    pub output_mode: Option<OutputMode>,
+    #[serde(default)]
+    pub paste_shortcut: Option<String>,
+    #[serde(default)]
+    pub format: Option<FormatOverrides>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Whisper model loaded for this application.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl OutputProfile {
+    /// Whether the profile applies to a window. A profile without rules
+    /// matches nothing.
+    pub fn matches(&self, class: &str, title: &str) -> bool {
+        let contains = |haystack: &str, needle: &Option<String>| match needle {
+            Some(needle) => haystack.to_lowercase().contains(&needle.to_lowercase()),
+            None => true,
+        };
+        (self.window_class.is_some() || self.window_title.is_some())
+            && contains(class, &self.window_class)
+            && contains(title, &self.window_title)
+    }
+}
+
+impl Config {
+    /// The first profile matching the window, in `profiles` order.
+    pub fn profile_for(&self, class: &str, title: &str) -> Option<&OutputProfile> {
+        self.profiles.iter().find(|p| p.matches(class, title))
+    }
+
+    /// This config with the profile's overrides applied. The profile's model
+    /// is not applied here; dictation loads it separately.
+    pub fn with_profile(&self, profile: &OutputProfile) -> Config {
+        let mut config = self.clone();
+        if let Some(mode) = &profile.output_mode {
+            config.output_mode = mode.clone();
+        }
+        if let Some(shortcut) = &profile.paste_shortcut {
+            config.paste_shortcut = shortcut.clone();
+        }
+        if let Some(format) = &profile.format {
+            config.format = config.format.merged(format);
+        }
+        if let Some(language) = &profile.language {
+            config.language = language.clone();
+        }
+        config
+    }
+
+    /// The Whisper model to load before a session whose profile asks for
+    /// `profile_model`, given the `loaded` one: the profile's model, or
+    /// `default_model` again once no profile wants another. `None` if the
+    /// right model is loaded, or if none is and no profile asks for one.
+    pub fn model_to_load(
+        &self,
+        profile_model: Option<&str>,
+        loaded: Option<&str>,
+    ) -> Option<String> {
+        let wanted = match (profile_model, loaded) {
+            (Some(model), _) => model,
+            (None, Some(_)) => self.default_model.as_str(),
+            (None, None) => return None,
+        };
+        (loaded != Some(wanted)).then(|| wanted.to_string())
+    }
+
+    /// The format rules, with the normalization locale defaulting to the
+    /// transcription language.
+    pub fn format_rules(&self) -> FormatRules {
+        let mut rules = self.format.clone();
+        rules.locale.get_or_insert_with(|| self.language.clone());
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, class: Option<&str>, title: Option<&str>) -> OutputProfile {
+        OutputProfile {
+            name: name.to_string(),
+            window_class: class.map(str::to_string),
+            window_title: title.map(str::to_string),
+            output_mode: None,
+            paste_shortcut: None,
+            format: None,
+            language: None,
+            model: None,
+        }
+    }
+
+    // --- Matching Tests ---
+
+    #[test]
+    fn test_match_is_case_insensitive_substring() {
+        let terminal = profile("Terminal", Some("kitty"), None);
+        assert!(terminal.matches("Kitty", "~/src"));
+        assert!(!terminal.matches("firefox", "kitty docs"));
+
+        let slack = profile("Slack", None, Some("slack"));
+        assert!(slack.matches("firefox", "General | Slack — Mozilla Firefox"));
+    }
+
+    #[test]
+    fn test_match_requires_all_rules() {
+        let github = profile("GitHub", Some("firefox"), Some("GitHub"));
+        assert!(github.matches("firefox", "Pull requests · GitHub"));
+        assert!(!github.matches("firefox", "Inbox"));
+        assert!(!github.matches("chromium", "Pull requests · GitHub"));
+    }
+
+    #[test]
+    fn test_profile_without_rules_matches_nothing() {
+        assert!(!profile("Empty", None, None).matches("kitty", "shell"));
+    }
+
+    #[test]
+    fn test_first_matching_profile_wins() {
+        let config = Config {
+            profiles: vec![
+                profile("Editor", Some("code"), None),
+                profile("Any Codium", Some("codium"), None),
+            ],
+            ..Config::default()
+        };
+        assert_eq!(
+            config.profile_for("Code", "main.rs").unwrap().name,
+            "Editor"
+        );
+        assert_eq!(
+            config.profile_for("VSCodium", "").unwrap().name,
+            "Any Codium"
+        );
+        assert!(config.profile_for("firefox", "").is_none());
+    }
+
+    // --- Override Tests ---
+
+    #[test]
+    fn test_with_profile_overrides_only_set_fields() {
+        let config = Config::default();
+        let terminal = OutputProfile {
+            output_mode: Some(OutputMode::Paste),
+            paste_shortcut: Some("Ctrl+Shift+V".to_string()),
+            format: Some(FormatOverrides {
+                capitalize: Some(false),
+                normalize: Some(true),
+                ..FormatOverrides::default()
+            }),
+            ..profile("Terminal", Some("kitty"), None)
+        };
+        let applied = config.with_profile(&terminal);
+        assert_eq!(applied.output_mode, OutputMode::Paste);
+        assert_eq!(applied.paste_shortcut, "Ctrl+Shift+V");
+        assert!(!applied.format.capitalize);
+        assert!(applied.format.normalize);
+        assert_eq!(applied.language, config.language);
+        assert_eq!(applied.typing_backend, config.typing_backend);
+    }
+
+    #[test]
+    fn test_profile_format_merges_with_global_rules() {
+        let config = Config {
+            format: FormatRules {
+                suffix: "\n".to_string(),
+                normalize: true,
+                locale: Some("en-GB".to_string()),
+                ..FormatRules::default()
+            },
+            ..Config::default()
+        };
+        let editor = OutputProfile {
+            format: Some(FormatOverrides {
+                capitalize: Some(false),
+                ..FormatOverrides::default()
+            }),
+            ..profile("Editor", Some("code"), None)
+        };
+        let applied = config.with_profile(&editor);
+        assert!(!applied.format.capitalize);
+        assert!(applied.format.normalize, "global normalization is kept");
+        assert_eq!(applied.format.suffix, "\n");
+        assert_eq!(applied.format.locale.as_deref(), Some("en-GB"));
+
+        let plain = OutputProfile {
+            format: Some(FormatOverrides {
+                normalize: Some(false),
+                suffix: Some(String::new()),
+                ..FormatOverrides::default()
+            }),
+            ..editor
+        };
+        let applied = config.with_profile(&plain);
+        assert!(!applied.format.normalize);
+        assert_eq!(applied.format.suffix, "");
+        assert!(applied.format.capitalize);
+    }
+
+    #[test]
+    fn test_model_to_load_swaps_back_to_default() {
+        let config = Config {
+            default_model: "base".to_string(),
+            ..Config::default()
+        };
+        // A profile's model replaces the default...
+        assert_eq!(
+            config.model_to_load(Some("large-v3"), Some("base")),
+            Some("large-v3".to_string())
+        );
+        assert_eq!(
+            config.model_to_load(Some("large-v3"), Some("large-v3")),
+            None
+        );
+        // ...and the next session without a profile model goes back to it.
+        assert_eq!(
+            config.model_to_load(None, Some("large-v3")),
+            Some("base".to_string())
+        );
+        assert_eq!(config.model_to_load(None, Some("base")), None);
+        // Nothing loaded: only a profile's model is loaded on demand.
+        assert_eq!(config.model_to_load(None, None), None);
+        assert_eq!(
+            config.model_to_load(Some("small"), None),
+            Some("small".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_rules_locale_follows_language() {
+        let mut config = Config {
+            language: "de".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.format_rules().locale.as_deref(), Some("de"));
+        config.format.locale = Some("en-GB".to_string());
+        assert_eq!(config.format_rules().locale.as_deref(), Some("en-GB"));
+    }
+
+    #[test]
+    fn test_profile_json_defaults() {
+        let profile: OutputProfile =
+            serde_json::from_str(r#"{"name": "Chat", "window_class": "slack", "format": {}}"#)
+                .unwrap();
+        assert_eq!(profile.format, Some(FormatOverrides::default()));
+        assert!(profile.output_mode.is_none());
+        assert!(profile.model.is_none());
+    }
+}
@@ -1,10 +1,16 @@
 //! Application configuration: serialization, default values, and persistence
 //! to `~/.whispertype/config.json`.
 
+use super::profiles::{FormatRules, OutputProfile};
 use crate::audio::vad::VadBackend;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Process-wide models directory, set from `Config::models_location` at startup
+/// so `Config::models_dir()` stays a plain associated function.
+static MODELS_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -12,6 +18,15 @@ pub enum OutputMode {
     TypeIntoField,
     Clipboard,
     Both,
+    /// Append to `output_file`.
+    File,
+    /// Write to standard output (used by `whispertype transcribe`).
+    Stdout,
+    /// Push to the Unix socket or named pipe at `output_socket`.
+    Socket,
+    /// Paste through the clipboard with `paste_shortcut`, then restore the
+    /// previous clipboard text.
+    Paste,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -22,6 +37,64 @@ pub enum StreamEngineConfig {
     Moonshine,
 }
 
+/// Where Whisper inference runs. `Auto` uses the GPU when this build has CUDA
+/// support and an NVIDIA GPU is present, otherwise the CPU.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputeDevice {
+    #[default]
+    Auto,
+    Gpu,
+    Cpu,
+}
+
+/// How the `type_into_field` output injects keystrokes. `Auto` uses `wtype`
+/// or `ydotool` on Wayland sessions when installed and enigo elsewhere.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TypingBackend {
+    #[default]
+    Auto,
+    /// X11 and XWayland windows.
+    Enigo,
+    /// The zwp_virtual_keyboard_v1 protocol (wlroots compositors).
+    Wtype,
+    /// uinput, any compositor; needs the `ydotoold` daemon.
+    Ydotool,
+}
+
+/// Live rewriting of typed text. When on, the tentative text is typed as soon
+/// as it is recognized and corrected in place as the hypothesis changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveRewrite {
+    /// Only confirmed words are typed.
+    #[default]
+    Off,
+    /// Delete changed text with Backspace, then type the new text.
+    Backspace,
+    /// Select changed text with Shift+Left and type over it.
+    Select,
+}
+
+/// A user-registered Whisper GGML model (e.g. a domain fine-tune).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomModel {
+    pub id: String,
+    pub display_name: String,
+    /// Local file path (used in place) or `http(s)://` URL (downloaded on demand).
+    pub source: String,
+    /// Optional SHA-256 (hex) checked on registration or after download.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+impl CustomModel {
+    pub fn is_url(&self) -> bool {
+        self.source.starts_with("http://") || self.source.starts_with("https://")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub version: u32,
@@ -39,6 +112,103 @@ pub struct Config {
     pub first_run_complete: bool,
     #[serde(default)]
     pub stream_engine: StreamEngineConfig,
+    /// How many models may download at the same time; further downloads queue.
+    #[serde(default = "default_max_concurrent_downloads")]
+    pub max_concurrent_downloads: usize,
+    #[serde(default)]
+    pub custom_models: Vec<CustomModel>,
+    /// Rewrites HuggingFace download URLs, e.g. `https://artifacts.corp/hf/{path}`.
+    #[serde(default)]
+    pub model_mirror: Option<String>,
+    /// URL or path of a model catalog JSON overriding the built-in registry.
+    #[serde(default)]
+    pub model_catalog_url: Option<String>,
+    /// Where models are stored; `None` means `~/.whispertype/models`.
+    #[serde(default)]
+    pub models_location: Option<PathBuf>,
+    #[serde(default)]
+    pub compute_device: ComputeDevice,
+    /// Whisper CPU threads; 0 picks one per physical core (capped at 8).
+    #[serde(default)]
+    pub n_threads: usize,
+    /// CUDA device index used when running on the GPU.
+    #[serde(default)]
+    pub gpu_device: i32,
+    /// Request flash attention (falls back automatically if unsupported).
+    #[serde(default = "default_flash_attn")]
+    pub flash_attn: bool,
+    /// Outputs used alongside `output_mode`, e.g. typing plus a notes file.
+    #[serde(default)]
+    pub additional_outputs: Vec<OutputMode>,
+    /// File appended to by the `file` output; `~/` and `{date}` are expanded.
+    #[serde(default = "default_output_file")]
+    pub output_file: String,
+    /// Unix socket or named pipe written to by the `socket` output.
+    #[serde(default = "default_output_socket")]
+    pub output_socket: String,
+    #[serde(default)]
+    pub typing_backend: TypingBackend,
+    /// Shortcut sent by the `paste` output, e.g. `Ctrl+Shift+V` for terminals.
+    #[serde(default = "default_paste_shortcut")]
+    pub paste_shortcut: String,
+    /// Type tentative text and correct it in place (`type_into_field` only).
+    #[serde(default)]
+    pub live_rewrite: LiveRewrite,
+    /// Formatting of dictated text, unless a profile overrides it.
+    #[serde(default)]
+    pub format: FormatRules,
+    /// Per-application overrides, matched in order against the focused window.
+    #[serde(default)]
+    pub profiles: Vec<OutputProfile>,
+    /// Serve the local API on 127.0.0.1 (off by default).
+    #[serde(default)]
+    pub api_enabled: bool,
+    #[serde(default = "default_api_port")]
+    pub api_port: u16,
+    /// Token API clients must present; generated when the API is first enabled.
+    #[serde(default)]
+    pub api_token: String,
+    /// Serve the Wyoming ASR protocol (off by default).
+    #[serde(default)]
+    pub wyoming_enabled: bool,
+    /// Address the Wyoming server binds; it has no authentication, so use
+    /// `0.0.0.0` only on a trusted network.
+    #[serde(default = "default_wyoming_host")]
+    pub wyoming_host: String,
+    #[serde(default = "default_wyoming_port")]
+    pub wyoming_port: u16,
+}
+
+pub(crate) fn default_max_concurrent_downloads() -> usize {
+    2
+}
+
+fn default_flash_attn() -> bool {
+    true
+}
+
+fn default_output_file() -> String {
+    "~/.whispertype/notes/{date}.md".to_string()
+}
+
+fn default_output_socket() -> String {
+    "~/.whispertype/output.sock".to_string()
+}
+
+fn default_paste_shortcut() -> String {
+    "Ctrl+V".to_string()
+}
+
+fn default_api_port() -> u16 {
+    crate::api::DEFAULT_PORT
+}
+
+fn default_wyoming_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_wyoming_port() -> u16 {
+    crate::wyoming::DEFAULT_PORT
 }
 
 impl Default for Config {
@@ -57,6 +227,29 @@ impl Default for Config {
             downloaded_models: Vec::new(),
             first_run_complete: false,
             stream_engine: StreamEngineConfig::default(),
+            max_concurrent_downloads: default_max_concurrent_downloads(),
+            custom_models: Vec::new(),
+            model_mirror: None,
+            model_catalog_url: None,
+            models_location: None,
+            compute_device: ComputeDevice::default(),
+            n_threads: 0,
+            gpu_device: 0,
+            flash_attn: default_flash_attn(),
+            additional_outputs: Vec::new(),
+            output_file: default_output_file(),
+            output_socket: default_output_socket(),
+            typing_backend: TypingBackend::default(),
+            paste_shortcut: default_paste_shortcut(),
+            live_rewrite: LiveRewrite::default(),
+            format: FormatRules::default(),
+            profiles: Vec::new(),
+            api_enabled: false,
+            api_port: default_api_port(),
+            api_token: String::new(),
+            wyoming_enabled: false,
+            wyoming_host: default_wyoming_host(),
+            wyoming_port: default_wyoming_port(),
         }
     }
 }
@@ -69,10 +262,38 @@ impl Config {
             .join(".whispertype")
     }
 
+    /// Apply a config sent by the settings panel. Fields only backend
+    /// commands change keep their current values: the panel's copy may be
+    /// stale, moving the models directory takes `set_models_location`, and
+    /// the API token changes only through `regenerate_api_token`.
+    pub fn apply_update(&mut self, update: Config) {
+        let current = std::mem::replace(self, update);
+        self.models_location = current.models_location;
+        self.downloaded_models = current.downloaded_models;
+        self.custom_models = current.custom_models;
+        self.api_token = current.api_token;
+    }
+
+    /// The active models directory (`models_location` if set, otherwise the
+    /// default under `app_dir()`).
     pub fn models_dir() -> PathBuf {
+        MODELS_DIR
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(Self::default_models_dir)
+    }
+
+    pub fn default_models_dir() -> PathBuf {
         Self::app_dir().join("models")
     }
 
+    /// Point `models_dir()` at a different location. Called at startup and
+    /// after a models directory migration.
+    pub fn set_models_dir(dir: Option<PathBuf>) {
+        *MODELS_DIR.write().unwrap() = dir;
+    }
+
     pub fn config_path() -> PathBuf {
         Self::app_dir().join("config.json")
     }
@@ -120,6 +341,18 @@ impl Config {
             .map_err(|e| format!("Failed to write config: {}", e))?;
         Ok(())
     }
+
+    /// Record models as downloaded. Returns true if the list changed.
+    pub fn mark_downloaded(&mut self, model_ids: &[String]) -> bool {
+        let mut changed = false;
+        for id in model_ids {
+            if !self.downloaded_models.contains(id) {
+                self.downloaded_models.push(id.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
 }
 
 #[cfg(test)]
@@ -154,6 +387,45 @@ mod tests {
         assert!(!config.first_run_complete);
     }
 
+    #[test]
+    fn test_mark_downloaded_skips_duplicates() {
+        let mut config = Config::default();
+        let ids = vec!["tiny".to_string(), "base".to_string()];
+        assert!(config.mark_downloaded(&ids));
+        assert!(!config.mark_downloaded(&ids[..1]));
+        assert_eq!(config.downloaded_models, ids);
+    }
+
+    #[test]
+    fn test_apply_update_keeps_backend_owned_fields() {
+        let mut config = Config {
+            models_location: Some(PathBuf::from("/mnt/models")),
+            downloaded_models: vec!["base".to_string()],
+            custom_models: vec![CustomModel {
+                id: "domain".to_string(),
+                display_name: "Domain".to_string(),
+                source: "https://example.com/ggml-domain.bin".to_string(),
+                sha256: None,
+            }],
+            ..Default::default()
+        };
+
+        let update = Config {
+            language: "de".to_string(),
+            max_concurrent_downloads: 4,
+            api_token: "revoked".to_string(),
+            ..Default::default()
+        };
+        config.apply_update(update);
+
+        assert_eq!(config.language, "de");
+        assert_eq!(config.max_concurrent_downloads, 4);
+        assert_eq!(config.models_location, Some(PathBuf::from("/mnt/models")));
+        assert_eq!(config.downloaded_models, vec!["base".to_string()]);
+        assert_eq!(config.custom_models.len(), 1);
+        assert!(config.api_token.is_empty());
+    }
+
     // --- Serialization/Deserialization Tests ---
 
     #[test]
@@ -181,6 +453,43 @@ mod tests {
             downloaded_models: vec!["tiny".to_string(), "base".to_string()],
             first_run_complete: true,
             stream_engine: StreamEngineConfig::WhisperOnly,
+            max_concurrent_downloads: 2,
+            custom_models: Vec::new(),
+            model_mirror: None,
+            model_catalog_url: None,
+            models_location: None,
+            compute_device: ComputeDevice::Cpu,
+            n_threads: 6,
+            gpu_device: 1,
+            flash_attn: false,
+            additional_outputs: vec![OutputMode::File, OutputMode::Socket],
+            output_file: "/notes/{date}.txt".to_string(),
+            output_socket: "/run/dictation.sock".to_string(),
+            typing_backend: TypingBackend::Ydotool,
+            paste_shortcut: "Ctrl+Shift+V".to_string(),
+            live_rewrite: LiveRewrite::Select,
+            format: FormatRules {
+                capitalize: false,
+                suffix: "\n".to_string(),
+                normalize: true,
+                locale: Some("en-GB".to_string()),
+            },
+            profiles: vec![OutputProfile {
+                name: "Terminal".to_string(),
+                window_class: Some("kitty".to_string()),
+                window_title: None,
+                output_mode: Some(OutputMode::Paste),
+                paste_shortcut: None,
+                format: None,
+                language: Some("en".to_string()),
+                model: Some("base.en".to_string()),
+            }],
+            api_enabled: true,
+            api_port: 9000,
+            api_token: "abc123".to_string(),
+            wyoming_enabled: true,
+            wyoming_host: "0.0.0.0".to_string(),
+            wyoming_port: 10400,
         };
 
         let json = serde_json::to_string_pretty(&config).unwrap();
@@ -190,6 +499,31 @@ mod tests {
         assert_eq!(deserialized.hotkey, "Alt+D");
         assert_eq!(deserialized.default_model, "tiny");
         assert_eq!(deserialized.output_mode, OutputMode::Clipboard);
+        assert_eq!(deserialized.compute_device, ComputeDevice::Cpu);
+        assert_eq!(deserialized.n_threads, 6);
+        assert_eq!(deserialized.gpu_device, 1);
+        assert!(!deserialized.flash_attn);
+        assert_eq!(
+            deserialized.additional_outputs,
+            vec![OutputMode::File, OutputMode::Socket]
+        );
+        assert_eq!(deserialized.output_file, "/notes/{date}.txt");
+        assert_eq!(deserialized.output_socket, "/run/dictation.sock");
+        assert_eq!(deserialized.typing_backend, TypingBackend::Ydotool);
+        assert_eq!(deserialized.paste_shortcut, "Ctrl+Shift+V");
+        assert_eq!(deserialized.live_rewrite, LiveRewrite::Select);
+        assert!(!deserialized.format.capitalize);
+        assert_eq!(deserialized.format.suffix, "\n");
+        assert!(deserialized.format.normalize);
+        assert_eq!(deserialized.format.locale.as_deref(), Some("en-GB"));
+        assert_eq!(deserialized.profiles.len(), 1);
+        assert_eq!(deserialized.profiles[0].model.as_deref(), Some("base.en"));
+        assert!(deserialized.api_enabled);
+        assert_eq!(deserialized.api_port, 9000);
+        assert_eq!(deserialized.api_token, "abc123");
+        assert!(deserialized.wyoming_enabled);
+        assert_eq!(deserialized.wyoming_host, "0.0.0.0");
+        assert_eq!(deserialized.wyoming_port, 10400);
         assert_eq!(deserialized.audio_device, Some("USB Mic".to_string()));
         assert_eq!(deserialized.language, "en");
         assert!((deserialized.vad_threshold - 0.05).abs() < 1e-6);
@@ -214,6 +548,18 @@ mod tests {
 
         let json_both = serde_json::to_string(&OutputMode::Both).unwrap();
         assert_eq!(json_both, "\"both\"");
+
+        let json_file = serde_json::to_string(&OutputMode::File).unwrap();
+        assert_eq!(json_file, "\"file\"");
+
+        let json_stdout = serde_json::to_string(&OutputMode::Stdout).unwrap();
+        assert_eq!(json_stdout, "\"stdout\"");
+
+        let json_socket = serde_json::to_string(&OutputMode::Socket).unwrap();
+        assert_eq!(json_socket, "\"socket\"");
+
+        let json_paste = serde_json::to_string(&OutputMode::Paste).unwrap();
+        assert_eq!(json_paste, "\"paste\"");
     }
 
     #[test]
@@ -353,6 +699,29 @@ mod tests {
             downloaded_models: vec!["tiny".to_string()],
             first_run_complete: true,
             stream_engine: StreamEngineConfig::WhisperOnly,
+            max_concurrent_downloads: 2,
+            custom_models: Vec::new(),
+            model_mirror: None,
+            model_catalog_url: None,
+            models_location: None,
+            compute_device: ComputeDevice::default(),
+            n_threads: 0,
+            gpu_device: 0,
+            flash_attn: true,
+            additional_outputs: Vec::new(),
+            output_file: default_output_file(),
+            output_socket: default_output_socket(),
+            typing_backend: TypingBackend::default(),
+            paste_shortcut: default_paste_shortcut(),
+            live_rewrite: LiveRewrite::default(),
+            format: FormatRules::default(),
+            profiles: Vec::new(),
+            api_enabled: false,
+            api_port: default_api_port(),
+            api_token: String::new(),
+            wyoming_enabled: false,
+            wyoming_host: default_wyoming_host(),
+            wyoming_port: default_wyoming_port(),
         };
 
         // Save to temp path
@@ -461,5 +830,51 @@ mod tests {
         }"#;
         let config: Config = serde_json::from_str(json).unwrap();
         assert_eq!(config.stream_engine, StreamEngineConfig::WhisperOnly);
+        assert_eq!(config.max_concurrent_downloads, 2);
+        assert!(config.custom_models.is_empty());
+        assert!(config.model_mirror.is_none());
+        assert!(config.model_catalog_url.is_none());
+        assert!(config.models_location.is_none());
+        assert_eq!(config.compute_device, ComputeDevice::Auto);
+        assert_eq!(config.n_threads, 0);
+        assert_eq!(config.gpu_device, 0);
+        assert!(
+            config.flash_attn,
+            "flash attention stays on for old configs"
+        );
+        assert!(config.additional_outputs.is_empty());
+        assert_eq!(config.output_file, "~/.whispertype/notes/{date}.md");
+        assert_eq!(config.output_socket, "~/.whispertype/output.sock");
+        assert!(!config.api_enabled, "the API is opt-in");
+        assert_eq!(config.api_port, 7837);
+        assert!(config.api_token.is_empty());
+        assert!(!config.wyoming_enabled);
+        assert_eq!(config.wyoming_host, "127.0.0.1");
+        assert_eq!(config.wyoming_port, 10300);
+        assert_eq!(config.typing_backend, TypingBackend::Auto);
+        assert_eq!(config.paste_shortcut, "Ctrl+V");
+        assert_eq!(config.live_rewrite, LiveRewrite::Off);
+        assert_eq!(config.format, FormatRules::default());
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    fn test_custom_model_is_url() {
+        let mut model = CustomModel {
+            id: "medical".to_string(),
+            display_name: "Medical".to_string(),
+            source: "https://models.internal/ggml-medical.bin".to_string(),
+            sha256: None,
+        };
+        assert!(model.is_url());
+        model.source = "/opt/models/ggml-medical.bin".to_string();
+        assert!(!model.is_url());
+    }
+
+    #[test]
+    fn test_custom_model_sha256_is_optional() {
+        let json = r#"{"id": "x", "display_name": "X", "source": "/tmp/x.bin"}"#;
+        let model: CustomModel = serde_json::from_str(json).unwrap();
+        assert!(model.sha256.is_none());
     }
 }
@@ -0,0 +1,260 @@
+//! Session D-Bus service for desktop integration, so compositor keybindings
+//! and status bars can drive dictation without a global-shortcut grab:
+//!
+//! ```sh
+//! busctl --user call com.ponderrr.WhisperType /com/ponderrr/WhisperType \
+//!     com.ponderrr.WhisperType1 Toggle
+//! ```
+//!
+//! Methods `Start`, `Stop`, `Toggle` (returns whether dictation is running)
+//! and `Status`; signals `StatusChanged(s)` and `TranscriptReady(s)` once per
+//! finished utterance, with its formatted text. Events come from the API
+//! `EventHub`, which is fed by the app's `dictation-status` and
+//! `transcript-ready` events.
+
+use crate::api::{ApiEvent, DictationControl, EventHub};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use zbus::object_server::SignalEmitter;
+use zbus::{connection, fdo, interface, Connection};
+
+pub const BUS_NAME: &str = "com.ponderrr.WhisperType";
+pub const OBJECT_PATH: &str = "/com/ponderrr/WhisperType";
+
+struct Dictation {
+    control: Arc<dyn DictationControl>,
+    hub: Arc<EventHub>,
+}
+
+impl Dictation {
+    /// Run a control call off the async runtime; they may block.
+    async fn call<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&dyn DictationControl) -> Result<T, String> + Send + 'static,
+    ) -> fdo::Result<T> {
+        let control = self.control.clone();
+        tokio::task::spawn_blocking(move || f(control.as_ref()))
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?
+            .map_err(fdo::Error::Failed)
+    }
+}
+
+#[interface(name = "com.ponderrr.WhisperType1")]
+impl Dictation {
+    async fn start(&self) -> fdo::Result<()> {
+        self.call(|c| c.start()).await
+    }
+
+    async fn stop(&self) -> fdo::Result<()> {
+        self.call(|c| c.stop()).await
+    }
+
+    async fn toggle(&self) -> fdo::Result<bool> {
+        self.call(|c| c.toggle()).await
+    }
+
+    /// `idle`, `listening`, or `loading` while a start waits for a model.
+    async fn status(&self) -> String {
+        self.hub.status()
+    }
+
+    #[zbus(signal)]
+    async fn status_changed(emitter: &SignalEmitter<'_>, status: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn transcript_ready(emitter: &SignalEmitter<'_>, text: &str) -> zbus::Result<()>;
+}
+
+/// Emit the signal for a hub event, if it maps to one.
+async fn emit(emitter: &SignalEmitter<'_>, event: &ApiEvent) -> zbus::Result<()> {
+    match event.event.as_str() {
+        "dictation-status" => {
+            if let Some(status) = event.payload.as_str() {
+                Dictation::status_changed(emitter, status).await?;
+            }
+        }
+        "transcript-ready" => {
+            if let Some(text) = event.payload.as_str() {
+                Dictation::transcript_ready(emitter, text).await?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Build `builder` with the service attached. Returns the connection and a
+/// future that turns hub events into signals until the hub is dropped.
+pub async fn serve(
+    builder: connection::Builder<'_>,
+    control: Arc<dyn DictationControl>,
+    hub: Arc<EventHub>,
+) -> Result<(Connection, impl Future<Output = ()> + Send), String> {
+    let mut events = hub.subscribe();
+    let connection = builder
+        .serve_at(OBJECT_PATH, Dictation { control, hub })
+        .map_err(|e| e.to_string())?
+        .build()
+        .await
+        .map_err(|e| format!("D-Bus connection failed: {}", e))?;
+
+    let emitter = SignalEmitter::new(&connection, OBJECT_PATH)
+        .map_err(|e| e.to_string())?
+        .into_owned();
+    let forward = async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = emit(&emitter, &event).await {
+                        eprintln!("dbus: failed to emit signal: {}", e);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    Ok((connection, forward))
+}
+
+/// Own `BUS_NAME` on the session bus and serve until the app exits. Fails if
+/// there is no session bus or another instance holds the name.
+pub async fn run(control: Arc<dyn DictationControl>, hub: Arc<EventHub>) -> Result<(), String> {
+    let builder = connection::Builder::session()
+        .and_then(|b| b.name(BUS_NAME))
+        .map_err(|e| e.to_string())?;
+    let (_connection, forward) = serve(builder, control, hub).await?;
+    forward.await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[zbus::proxy(
+        interface = "com.ponderrr.WhisperType1",
+        default_service = "com.ponderrr.WhisperType",
+        default_path = "/com/ponderrr/WhisperType"
+    )]
+    trait WhisperType {
+        fn start(&self) -> zbus::Result<()>;
+        fn stop(&self) -> zbus::Result<()>;
+        fn toggle(&self) -> zbus::Result<bool>;
+        fn status(&self) -> zbus::Result<String>;
+        #[zbus(signal)]
+        fn status_changed(&self, status: &str) -> zbus::Result<()>;
+        #[zbus(signal)]
+        fn transcript_ready(&self, text: &str) -> zbus::Result<()>;
+    }
+
+    /// Publishes status events like the app does.
+    struct FakeControl {
+        hub: Arc<EventHub>,
+        running: AtomicBool,
+        fail: bool,
+    }
+
+    impl DictationControl for FakeControl {
+        fn start(&self) -> Result<(), String> {
+            if self.fail {
+                return Err("No model loaded".to_string());
+            }
+            self.running.store(true, Ordering::SeqCst);
+            self.hub.publish("dictation-status", json!("listening"));
+            Ok(())
+        }
+
+        fn stop(&self) -> Result<(), String> {
+            self.running.store(false, Ordering::SeqCst);
+            self.hub.publish("dictation-status", json!("idle"));
+            Ok(())
+        }
+
+        fn toggle(&self) -> Result<bool, String> {
+            if self.running.load(Ordering::SeqCst) {
+                self.stop().map(|_| false)
+            } else {
+                self.start().map(|_| true)
+            }
+        }
+    }
+
+    /// Serve over a private socket pair, so no session bus is needed.
+    async fn connect(fail: bool) -> (WhisperTypeProxy<'static>, Arc<EventHub>, Connection) {
+        let hub = Arc::new(EventHub::new());
+        let control = Arc::new(FakeControl {
+            hub: hub.clone(),
+            running: AtomicBool::new(false),
+            fail,
+        });
+        let (server, client) = tokio::net::UnixStream::pair().unwrap();
+        let builder = connection::Builder::unix_stream(server)
+            .server(zbus::Guid::generate())
+            .unwrap()
+            .p2p();
+        let service = tokio::spawn(serve(builder, control, hub.clone()));
+        let client = connection::Builder::unix_stream(client)
+            .p2p()
+            .build()
+            .await
+            .unwrap();
+        let (server, forward) = service.await.unwrap().unwrap();
+        tokio::spawn(forward);
+        let proxy = WhisperTypeProxy::new(&client).await.unwrap();
+        (proxy, hub, server)
+    }
+
+    // --- Method Tests ---
+
+    #[tokio::test]
+    async fn test_methods_control_dictation() {
+        let (proxy, _hub, _server) = connect(false).await;
+        assert_eq!(proxy.status().await.unwrap(), "idle");
+        proxy.start().await.unwrap();
+        assert_eq!(proxy.status().await.unwrap(), "listening");
+        assert!(!proxy.toggle().await.unwrap());
+        assert_eq!(proxy.status().await.unwrap(), "idle");
+        assert!(proxy.toggle().await.unwrap());
+        proxy.stop().await.unwrap();
+        assert_eq!(proxy.status().await.unwrap(), "idle");
+    }
+
+    #[tokio::test]
+    async fn test_method_errors_are_returned() {
+        let (proxy, _hub, _server) = connect(true).await;
+        let err = proxy.start().await.unwrap_err();
+        assert!(err.to_string().contains("No model loaded"), "{}", err);
+    }
+
+    // --- Signal Tests ---
+
+    #[tokio::test]
+    async fn test_signals_follow_hub_events() {
+        let (proxy, hub, _server) = connect(false).await;
+        let mut status = proxy.receive_status_changed().await.unwrap();
+        let mut transcripts = proxy.receive_transcript_ready().await.unwrap();
+
+        proxy.start().await.unwrap();
+        let signal = status.next().await.unwrap();
+        assert_eq!(signal.args().unwrap().status, "listening");
+
+        hub.publish(
+            "transcription-update",
+            json!({ "text": "hello th", "is_partial": true }),
+        );
+        hub.publish(
+            "transcription-update",
+            json!({ "text": "hello there", "is_partial": false }),
+        );
+        hub.publish("transcript-ready", json!("Hello there."));
+        // Confirmed fragments don't signal; the finished utterance does.
+        let signal = transcripts.next().await.unwrap();
+        assert_eq!(signal.args().unwrap().text, "Hello there.");
+    }
+}
@@ -0,0 +1,376 @@
+//! Accuracy evaluation: word and character error rates for a directory of WAV
+//! clips with reference transcripts, through the streaming dictation path
+//! (VAD gating, `LocalAgreement`, quality pass) and plain offline
+//! transcription.
+//!
+//! This module holds the engine-independent parts (normalization, error
+//! rates, dataset discovery, streaming replay); `runner` wires up the engines.
+
+pub mod runner;
+
+use crate::audio::AudioMessage;
+use crate::transcription::streaming::{StreamEvent, StreamingTranscriber};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// A WAV clip and its reference transcript (`name.wav` + `name.txt`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: String,
+    pub wav: PathBuf,
+    pub reference: String,
+}
+
+/// Find `*.wav` files in `dir` that have a matching `.txt` reference.
+/// Returns the samples (sorted by name) and the WAV files without a reference.
+pub fn discover(dir: &Path) -> Result<(Vec<Sample>, Vec<PathBuf>), String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut wavs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        })
+        .collect();
+    wavs.sort();
+
+    let mut samples = Vec::new();
+    let mut missing = Vec::new();
+    for wav in wavs {
+        match std::fs::read_to_string(wav.with_extension("txt")) {
+            Ok(reference) => samples.push(Sample {
+                name: wav
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                reference: reference.trim().to_string(),
+                wav,
+            }),
+            Err(_) => missing.push(wav),
+        }
+    }
+    if samples.is_empty() {
+        return Err(format!(
+            "No WAV + .txt reference pairs found in {}",
+            dir.display()
+        ));
+    }
+    Ok((samples, missing))
+}
+
+/// Normalize text for scoring: lowercase, punctuation and hyphens become
+/// spaces, apostrophes inside words are kept ("don't"), whitespace collapsed.
+pub fn normalize(text: &str) -> String {
+    let spaced: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '\'' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    spaced
+        .split_whitespace()
+        .map(|w| w.trim_matches('\''))
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Levenshtein distance (substitutions + deletions + insertions).
+pub fn edit_distance<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=hypothesis.len()).collect();
+    let mut curr = vec![0; hypothesis.len() + 1];
+    for (i, r) in reference.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, h) in hypothesis.iter().enumerate() {
+            let substitution = prev[j] + usize::from(r != h);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[hypothesis.len()]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ErrorRate {
+    pub errors: usize,
+    /// Reference length (words or characters).
+    pub length: usize,
+    pub rate: f64,
+}
+
+impl ErrorRate {
+    fn new(errors: usize, length: usize) -> Self {
+        Self {
+            errors,
+            length,
+            rate: errors as f64 / length.max(1) as f64,
+        }
+    }
+}
+
+/// Word error rate after normalization.
+pub fn wer(reference: &str, hypothesis: &str) -> ErrorRate {
+    let (r, h) = (normalize(reference), normalize(hypothesis));
+    let r: Vec<&str> = r.split_whitespace().collect();
+    let h: Vec<&str> = h.split_whitespace().collect();
+    ErrorRate::new(edit_distance(&r, &h), r.len())
+}
+
+/// Character error rate after normalization (spaces count as characters).
+pub fn cer(reference: &str, hypothesis: &str) -> ErrorRate {
+    let r: Vec<char> = normalize(reference).chars().collect();
+    let h: Vec<char> = normalize(hypothesis).chars().collect();
+    ErrorRate::new(edit_distance(&r, &h), r.len())
+}
+
+/// A transcript and its scores against the reference.
+#[derive(Debug, Clone, Serialize)]
+pub struct Hypothesis {
+    pub text: String,
+    pub wer: ErrorRate,
+    pub cer: ErrorRate,
+}
+
+impl Hypothesis {
+    pub fn score(reference: &str, text: String) -> Self {
+        Self {
+            wer: wer(reference, &text),
+            cer: cer(reference, &text),
+            text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    pub name: String,
+    pub reference: String,
+    pub streaming: Option<Hypothesis>,
+    pub offline: Option<Hypothesis>,
+}
+
+/// Corpus-level rates: total errors over total reference length.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorSummary {
+    pub wer: ErrorRate,
+    pub cer: ErrorRate,
+    pub files: usize,
+}
+
+pub fn summarize<'a>(hypotheses: impl IntoIterator<Item = &'a Hypothesis>) -> Option<ErrorSummary> {
+    let mut files = 0;
+    let (mut we, mut wl, mut ce, mut cl) = (0, 0, 0, 0);
+    for h in hypotheses {
+        files += 1;
+        we += h.wer.errors;
+        wl += h.wer.length;
+        ce += h.cer.errors;
+        cl += h.cer.length;
+    }
+    (files > 0).then(|| ErrorSummary {
+        wer: ErrorRate::new(we, wl),
+        cer: ErrorRate::new(ce, cl),
+        files,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub model_id: String,
+    /// `"whisper_only"` or `"moonshine"`.
+    pub stream_engine: String,
+    pub vad: String,
+    pub pacing: Pacing,
+    pub files: Vec<FileResult>,
+    pub streaming: Option<ErrorSummary>,
+    pub offline: Option<ErrorSummary>,
+}
+
+/// How queued audio is batched between inference steps during replay.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pacing {
+    /// One step per message, as if inference were instant. Deterministic.
+    EveryMessage,
+    /// Audio arrives in real time; each step consumes everything that arrived
+    /// while the previous step ran, as the dictation thread does.
+    RealTime,
+}
+
+/// Replay gated audio through `transcriber` and return the confirmed text,
+/// utterances joined with spaces.
+pub fn replay(
+    messages: &[AudioMessage],
+    transcriber: &mut StreamingTranscriber,
+    pacing: Pacing,
+) -> String {
+    // Arrival time of each message in ms of audio.
+    let mut audio_ms = 0.0;
+    let arrivals: Vec<f64> = messages
+        .iter()
+        .map(|m| {
+            if let AudioMessage::Segment(seg) = m {
+                audio_ms += seg.len() as f64 / 16.0;
+            }
+            audio_ms
+        })
+        .collect();
+
+    let mut confirmed = Vec::new();
+    let mut clock = 0.0f64;
+    let mut i = 0;
+    while i < messages.len() {
+        clock = clock.max(arrivals[i]);
+        let mut end_of_speech = false;
+        loop {
+            match &messages[i] {
+                AudioMessage::Segment(seg) => transcriber.feed(seg),
+                AudioMessage::EndOfSpeech => end_of_speech = true,
+            }
+            i += 1;
+            if pacing == Pacing::EveryMessage || i >= messages.len() || arrivals[i] > clock {
+                break;
+            }
+        }
+
+        let started = Instant::now();
+        for event in transcriber.step(end_of_speech) {
+            if let StreamEvent::Confirmed(text) = event {
+                confirmed.push(text);
+            }
+        }
+        if pacing == Pacing::RealTime {
+            clock += started.elapsed().as_secs_f64() * 1000.0;
+        }
+    }
+    confirmed.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::streaming::Recognizer;
+
+    // --- Normalization / Error Rate Tests ---
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("Hello, World!  It's a  well-known 'fact'."),
+            "hello world it's a well known fact"
+        );
+        assert_eq!(normalize("  ...  "), "");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance(&['a', 'b', 'c'], &['a', 'b', 'c']), 0);
+        assert_eq!(edit_distance(&['k', 'i', 't'], &['s', 'i', 't', 's']), 2);
+        assert_eq!(edit_distance::<char>(&[], &['a', 'b']), 2);
+        assert_eq!(edit_distance(&['a', 'b'], &[]), 2);
+    }
+
+    #[test]
+    fn test_wer_ignores_case_and_punctuation() {
+        let rate = wer("The cat sat on the mat.", "the cat, sat on a mat");
+        assert_eq!(rate.errors, 1);
+        assert_eq!(rate.length, 6);
+        assert!((rate.rate - 1.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_wer_counts_insertions_and_deletions() {
+        assert_eq!(wer("one two three", "one three").errors, 1);
+        assert_eq!(wer("one two", "one two two three").errors, 2);
+        assert_eq!(wer("", "").rate, 0.0);
+        assert_eq!(wer("", "noise").rate, 1.0);
+    }
+
+    #[test]
+    fn test_cer() {
+        let rate = cer("hello", "hallo");
+        assert_eq!((rate.errors, rate.length), (1, 5));
+    }
+
+    #[test]
+    fn test_summarize_weights_by_length() {
+        let a = Hypothesis::score("one two three four", "one two three four".to_string());
+        let b = Hypothesis::score("five six", "five".to_string());
+        let summary = summarize([&a, &b]).unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!((summary.wer.errors, summary.wer.length), (1, 6));
+        assert!(summarize(std::iter::empty()).is_none());
+    }
+
+    // --- Discovery Tests ---
+
+    #[test]
+    fn test_discover_pairs_wav_and_txt() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.wav"), b"").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "second clip\n").unwrap();
+        std::fs::write(dir.path().join("a.WAV"), b"").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "first").unwrap();
+        std::fs::write(dir.path().join("orphan.wav"), b"").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "").unwrap();
+
+        let (samples, missing) = discover(dir.path()).unwrap();
+        let names: Vec<&str> = samples.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(samples[1].reference, "second clip");
+        assert_eq!(missing, vec![dir.path().join("orphan.wav")]);
+    }
+
+    #[test]
+    fn test_discover_empty_dir_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(discover(dir.path()).is_err());
+    }
+
+    // --- Replay Tests ---
+
+    /// Transcribes a second of audio as one word from a fixed script, so the
+    /// output grows as the utterance buffer grows.
+    struct Words(&'static [&'static str]);
+
+    impl Recognizer for Words {
+        fn transcribe(&mut self, audio: &[f32]) -> Result<String, String> {
+            let n = (audio.len() / 16000).min(self.0.len());
+            Ok(self.0[..n].join(" "))
+        }
+    }
+
+    fn utterance(seconds: usize) -> Vec<AudioMessage> {
+        let mut messages: Vec<AudioMessage> = (0..seconds * 10)
+            .map(|_| AudioMessage::Segment(vec![0.0; 1600]))
+            .collect();
+        messages.push(AudioMessage::EndOfSpeech);
+        messages
+    }
+
+    #[test]
+    fn test_replay_every_message() {
+        let mut messages = utterance(3);
+        messages.extend(utterance(2));
+        let mut transcriber =
+            StreamingTranscriber::new(Box::new(Words(&["alpha", "beta", "gamma"])), None);
+        let text = replay(&messages, &mut transcriber, Pacing::EveryMessage);
+        assert_eq!(text, "alpha beta gamma alpha beta");
+    }
+
+    #[test]
+    fn test_replay_real_time_matches_for_fast_recognizer() {
+        let messages = utterance(3);
+        let mut transcriber =
+            StreamingTranscriber::new(Box::new(Words(&["alpha", "beta", "gamma"])), None);
+        let text = replay(&messages, &mut transcriber, Pacing::RealTime);
+        assert_eq!(text, "alpha beta gamma");
+    }
+}
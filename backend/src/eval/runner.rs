@@ -0,0 +1,121 @@
+//! Runs the evaluation against the real engines. Streaming uses the same VAD
+//! gating and `StreamingTranscriber` as the dictation thread; offline
+//! transcribes each whole clip in one Whisper pass.
+
+use super::{replay, summarize, EvalReport, FileResult, Hypothesis, Pacing, Sample};
+use crate::audio::segmenter::gate_clip;
+use crate::audio::vad::VadBackend;
+use crate::audio::wav::{read_wav, TARGET_RATE};
+use crate::config::{Config, StreamEngineConfig};
+use crate::model_manager::is_model_downloaded;
+use crate::transcription::engine::{TranscriptionEngine, WhisperRecognizer};
+use crate::transcription::models::find_model;
+use crate::transcription::moonshine::MoonshineEngine;
+use crate::transcription::options::EngineOptions;
+use crate::transcription::streaming::{Recognizer, StreamingTranscriber};
+use crate::transcription::ModelType;
+use std::sync::Arc;
+
+/// Silence appended to each clip so the last utterance reaches end of speech.
+const TRAILING_SILENCE_SECS: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct EvalOptions {
+    /// Whisper model for the quality and offline passes.
+    pub model_id: String,
+    pub language: String,
+    pub stream_engine: StreamEngineConfig,
+    pub vad_backend: VadBackend,
+    pub vad_threshold: f32,
+    pub pacing: Pacing,
+    pub streaming: bool,
+    pub offline: bool,
+    pub engine: EngineOptions,
+}
+
+/// Evaluate `samples`. `progress` is called with each sample's name before it
+/// runs, along with its index and the total.
+pub fn run(
+    samples: &[Sample],
+    options: &EvalOptions,
+    mut progress: impl FnMut(&str, usize, usize),
+) -> Result<EvalReport, String> {
+    let model = find_model(&options.model_id)
+        .ok_or_else(|| format!("Unknown model: {}", options.model_id))?;
+    if model.model_type != ModelType::WhisperGgml {
+        return Err(format!("{} is not a Whisper model", model.id));
+    }
+    if !is_model_downloaded(&model.id) {
+        return Err(format!("Model {} is not downloaded", model.id));
+    }
+    let engine = Arc::new(TranscriptionEngine::new());
+    engine.load_model(&model.model_path(), &model.id, &options.engine)?;
+
+    let mut transcriber = if options.streaming {
+        let stream = match options.stream_engine {
+            StreamEngineConfig::Moonshine => Some(Box::new(MoonshineEngine::load(
+                &Config::models_dir().join("moonshine-tiny"),
+            )?) as Box<dyn Recognizer>),
+            StreamEngineConfig::WhisperOnly => None,
+        };
+        Some(StreamingTranscriber::new(
+            Box::new(WhisperRecognizer::new(engine.clone(), &options.language)?),
+            stream,
+        ))
+    } else {
+        None
+    };
+    let mut offline = if options.offline {
+        Some(WhisperRecognizer::new(engine.clone(), &options.language)?)
+    } else {
+        None
+    };
+
+    let mut files = Vec::with_capacity(samples.len());
+    for (index, sample) in samples.iter().enumerate() {
+        progress(&sample.name, index, samples.len());
+        let mut audio = read_wav(&sample.wav)?;
+
+        let offline_text = match &mut offline {
+            Some(recognizer) => Some(recognizer.transcribe(&audio)?),
+            None => None,
+        };
+        let streaming_text = match &mut transcriber {
+            Some(transcriber) => {
+                audio.resize(
+                    audio.len() + TRAILING_SILENCE_SECS * TARGET_RATE as usize,
+                    0.0,
+                );
+                let messages = gate_clip(&audio, options.vad_backend, options.vad_threshold)?;
+                transcriber.reset();
+                Some(replay(&messages, transcriber, options.pacing))
+            }
+            None => None,
+        };
+
+        files.push(FileResult {
+            name: sample.name.clone(),
+            streaming: streaming_text.map(|t| Hypothesis::score(&sample.reference, t)),
+            offline: offline_text.map(|t| Hypothesis::score(&sample.reference, t)),
+            reference: sample.reference.clone(),
+        });
+    }
+
+    Ok(EvalReport {
+        model_id: model.id.clone(),
+        stream_engine: match options.stream_engine {
+            StreamEngineConfig::WhisperOnly => "whisper_only",
+            StreamEngineConfig::Moonshine => "moonshine",
+        }
+        .to_string(),
+        vad: match options.vad_backend {
+            VadBackend::Energy => "energy",
+            VadBackend::Silero => "silero",
+        }
+        .to_string(),
+        pacing: options.pacing,
+        streaming: summarize(files.iter().filter_map(|f| f.streaming.as_ref())),
+        offline: summarize(files.iter().filter_map(|f| f.offline.as_ref())),
+        files,
+    })
+}
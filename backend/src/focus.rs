@@ -0,0 +1,214 @@
+//! The focused window's class and title, used to pick an output profile when
+//! dictation starts. X11 reads `_NET_ACTIVE_WINDOW` from the window manager.
+//! On Wayland only wlroots-based compositors (sway, Hyprland, river, labwc,
+//! Wayfire) expose other clients' windows, through the
+//! wlr-foreign-toplevel-management protocol; GNOME and KDE do not, so no
+//! profile matches there.
+
+use crate::output::keyboard::SessionType;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FocusedWindow {
+    /// X11 `WM_CLASS` class name, or the Wayland app ID.
+    pub class: String,
+    pub title: String,
+}
+
+/// The focused window of the current session.
+pub fn focused_window() -> Result<FocusedWindow, String> {
+    match SessionType::detect() {
+        SessionType::Wayland => wlr::focused_window(),
+        SessionType::X11 => x11::focused_window(),
+        SessionType::Unknown => Err("No graphical session".to_string()),
+    }
+}
+
+mod x11 {
+    use super::FocusedWindow;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    pub fn focused_window() -> Result<FocusedWindow, String> {
+        let (conn, screen) =
+            x11rb::connect(None).map_err(|e| format!("Failed to connect to X11: {}", e))?;
+        let root = conn.setup().roots[screen].root;
+
+        let active = property(
+            &conn,
+            root,
+            atom(&conn, "_NET_ACTIVE_WINDOW")?,
+            AtomEnum::WINDOW,
+        )?;
+        let window = active
+            .get(..4)
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .filter(|&w| w != 0)
+            .ok_or("No active window")?;
+
+        // WM_CLASS is "instance\0class\0"; the class is what users recognise.
+        let wm_class = property(&conn, window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING)?;
+        let mut parts = wm_class.split(|&b| b == 0).filter(|p| !p.is_empty());
+        let instance = parts.next().unwrap_or_default();
+        let class = parts.next().unwrap_or(instance);
+
+        let utf8 = atom(&conn, "UTF8_STRING")?;
+        let mut title = property(&conn, window, atom(&conn, "_NET_WM_NAME")?, utf8)?;
+        if title.is_empty() {
+            title = property(&conn, window, AtomEnum::WM_NAME.into(), AtomEnum::STRING)?;
+        }
+
+        Ok(FocusedWindow {
+            class: String::from_utf8_lossy(class).into_owned(),
+            title: String::from_utf8_lossy(&title).into_owned(),
+        })
+    }
+
+    fn atom(conn: &RustConnection, name: &str) -> Result<Atom, String> {
+        conn.intern_atom(false, name.as_bytes())
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map(|r| r.atom)
+            .map_err(|e| e.to_string())
+    }
+
+    fn property(
+        conn: &RustConnection,
+        window: Window,
+        name: Atom,
+        kind: impl Into<Atom>,
+    ) -> Result<Vec<u8>, String> {
+        conn.get_property(false, window, name, kind, 0, 1024)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map(|r| r.value)
+            .map_err(|e| e.to_string())
+    }
+}
+
+mod wlr {
+    use super::FocusedWindow;
+    use std::collections::HashMap;
+    use wayland_client::backend::ObjectId;
+    use wayland_client::protocol::wl_registry::{self, WlRegistry};
+    use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+    use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+        zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+        zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+    };
+
+    #[derive(Default)]
+    struct Toplevel {
+        window: FocusedWindow,
+        activated: bool,
+    }
+
+    #[derive(Default)]
+    struct Toplevels {
+        manager: Option<ZwlrForeignToplevelManagerV1>,
+        windows: HashMap<ObjectId, Toplevel>,
+    }
+
+    impl Dispatch<WlRegistry, ()> for Toplevels {
+        fn event(
+            state: &mut Self,
+            registry: &WlRegistry,
+            event: wl_registry::Event,
+            _: &(),
+            _: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } = event
+            {
+                if interface == ZwlrForeignToplevelManagerV1::interface().name {
+                    state.manager = Some(registry.bind(name, version.min(3), qh, ()));
+                }
+            }
+        }
+    }
+
+    impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for Toplevels {
+        fn event(
+            state: &mut Self,
+            _: &ZwlrForeignToplevelManagerV1,
+            event: zwlr_foreign_toplevel_manager_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+                state.windows.insert(toplevel.id(), Toplevel::default());
+            }
+        }
+
+        event_created_child!(Toplevels, ZwlrForeignToplevelManagerV1, [
+            zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+        ]);
+    }
+
+    impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for Toplevels {
+        fn event(
+            state: &mut Self,
+            handle: &ZwlrForeignToplevelHandleV1,
+            event: zwlr_foreign_toplevel_handle_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            use zwlr_foreign_toplevel_handle_v1::Event;
+            let Some(toplevel) = state.windows.get_mut(&handle.id()) else {
+                return;
+            };
+            match event {
+                Event::Title { title } => toplevel.window.title = title,
+                Event::AppId { app_id } => toplevel.window.class = app_id,
+                Event::State { state: states } => {
+                    let activated = zwlr_foreign_toplevel_handle_v1::State::Activated as u32;
+                    toplevel.activated = states
+                        .chunks_exact(4)
+                        .any(|s| u32::from_ne_bytes([s[0], s[1], s[2], s[3]]) == activated);
+                }
+                Event::Closed => {
+                    state.windows.remove(&handle.id());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn focused_window() -> Result<FocusedWindow, String> {
+        let conn = Connection::connect_to_env()
+            .map_err(|e| format!("Failed to connect to Wayland: {}", e))?;
+        let mut queue = conn.new_event_queue();
+        conn.display().get_registry(&queue.handle(), ());
+
+        let mut state = Toplevels::default();
+        let mut roundtrip = |state: &mut Toplevels| {
+            queue
+                .roundtrip(state)
+                .map(|_| ())
+                .map_err(|e| format!("Wayland error: {}", e))
+        };
+        roundtrip(&mut state)?;
+        let Some(manager) = state.manager.clone() else {
+            return Err("Compositor does not expose the focused window".to_string());
+        };
+        // The first roundtrip announces the toplevels, the second delivers
+        // their title, app ID and state.
+        roundtrip(&mut state)?;
+        roundtrip(&mut state)?;
+        manager.stop();
+
+        state
+            .windows
+            .into_values()
+            .find(|t| t.activated)
+            .map(|t| t.window)
+            .ok_or_else(|| "No active window".to_string())
+    }
+}
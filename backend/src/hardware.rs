@@ -0,0 +1,556 @@
+//! Hardware probe (CPU, SIMD, RAM, NVIDIA GPU) and model recommendations based
+//! on a rough real-time-factor estimate for this machine.
+//!
+//! The RTF model is a heuristic calibrated against whisper.cpp on typical
+//! desktop hardware: inference cost scales with model file size, CPU throughput
+//! with thread count and SIMD width. It is meant for ranking, not prediction.
+
+use crate::config::{ComputeDevice, Config};
+use crate::transcription::models::{ModelType, WhisperModel};
+use crate::transcription::options::EngineOptions;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vram_total_mb: u64,
+    pub vram_free_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareInfo {
+    pub cpu_model: String,
+    pub physical_cores: usize,
+    pub logical_cores: usize,
+    /// SIMD extensions relevant to ggml (e.g. `avx2`, `avx512f`, `neon`).
+    pub simd: Vec<String>,
+    pub total_ram_mb: u64,
+    pub available_ram_mb: u64,
+    pub gpu: Option<GpuInfo>,
+    /// Whether this build was compiled with the `cuda` feature.
+    pub cuda_build: bool,
+}
+
+impl HardwareInfo {
+    pub fn has_simd(&self, name: &str) -> bool {
+        self.simd.iter().any(|s| s == name)
+    }
+
+    /// True if Whisper can actually run on the GPU in this build.
+    pub fn gpu_usable(&self) -> bool {
+        self.cuda_build && self.gpu.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Device {
+    Gpu,
+    Cpu,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelRecommendation {
+    pub model_id: String,
+    pub display_name: String,
+    pub device: Device,
+    /// Estimated seconds of compute per second of audio (lower is faster).
+    pub expected_rtf: f64,
+    /// Fits in VRAM (GPU) or available RAM (CPU).
+    pub fits: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendations {
+    pub hardware: HardwareInfo,
+    pub device: Device,
+    pub n_threads: usize,
+    /// Models sorted by expected RTF, fastest first.
+    pub models: Vec<ModelRecommendation>,
+    /// Largest Whisper model that fits and is comfortably faster than real time.
+    pub recommended: Option<String>,
+}
+
+/// RTF a model must beat to be recommended; leaves headroom for the repeated
+/// passes of streaming transcription.
+const TARGET_RTF: f64 = 0.5;
+/// Reference point: ggml-tiny on one AVX2 core.
+const TINY_BYTES: f64 = 77_691_713.0;
+const TINY_RTF_ONE_CORE: f64 = 0.15;
+/// Reference point: ggml-tiny on a mid-range CUDA GPU.
+const TINY_RTF_GPU: f64 = 0.0025;
+const MAX_AUTO_THREADS: usize = 8;
+
+static HARDWARE: OnceLock<HardwareInfo> = OnceLock::new();
+
+/// Probe once and cache; the probe shells out to `nvidia-smi`.
+pub fn info() -> &'static HardwareInfo {
+    HARDWARE.get_or_init(probe)
+}
+
+pub fn probe() -> HardwareInfo {
+    let logical_cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+    let meminfo = std::fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    let cpu = parse_cpuinfo(&cpuinfo);
+    let (total_ram_mb, available_ram_mb) = parse_meminfo(&meminfo);
+
+    HardwareInfo {
+        cpu_model: cpu.model,
+        physical_cores: cpu.physical_cores.unwrap_or(logical_cores).max(1),
+        logical_cores,
+        simd: cpu.simd,
+        total_ram_mb,
+        available_ram_mb,
+        gpu: probe_nvidia_gpu(),
+        cuda_build: cfg!(feature = "cuda"),
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct CpuInfo {
+    model: String,
+    physical_cores: Option<usize>,
+    simd: Vec<String>,
+}
+
+const SIMD_FLAGS: &[&str] = &[
+    "sse3",
+    "ssse3",
+    "sse4_1",
+    "sse4_2",
+    "avx",
+    "avx2",
+    "fma",
+    "f16c",
+    "avx512f",
+    "avx512bw",
+    "avx512_vnni",
+    "neon",
+    "asimd",
+    "asimdhp",
+    "sve",
+];
+
+fn parse_cpuinfo(text: &str) -> CpuInfo {
+    let mut info = CpuInfo::default();
+    let mut cores = std::collections::HashSet::new();
+    let mut physical_id = String::new();
+    let mut cpu_cores_field = None;
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "model name" | "Model" if info.model.is_empty() => info.model = value.to_string(),
+            "physical id" => physical_id = value.to_string(),
+            "core id" => {
+                cores.insert((physical_id.clone(), value.to_string()));
+            }
+            "cpu cores" => cpu_cores_field = value.parse().ok(),
+            "flags" | "Features" if info.simd.is_empty() => {
+                let flags: Vec<&str> = value.split_whitespace().collect();
+                info.simd = SIMD_FLAGS
+                    .iter()
+                    .filter(|f| flags.contains(f))
+                    .map(|f| f.to_string())
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    // "asimd" is how Linux reports NEON on aarch64.
+    if info.has("asimd") && !info.has("neon") {
+        info.simd.push("neon".to_string());
+    }
+    info.physical_cores = if !cores.is_empty() {
+        Some(cores.len())
+    } else {
+        cpu_cores_field
+    };
+    info
+}
+
+impl CpuInfo {
+    fn has(&self, flag: &str) -> bool {
+        self.simd.iter().any(|s| s == flag)
+    }
+}
+
+/// Returns (MemTotal, MemAvailable) in MB.
+fn parse_meminfo(text: &str) -> (u64, u64) {
+    let field = |name: &str| {
+        text.lines()
+            .find(|l| l.starts_with(name))
+            .and_then(|l| l.split_whitespace().nth(1))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb / 1024)
+            .unwrap_or(0)
+    };
+    (field("MemTotal:"), field("MemAvailable:"))
+}
+
+fn probe_nvidia_gpu() -> Option<GpuInfo> {
+    let output = std::process::Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=name,memory.total,memory.free",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the first line of `nvidia-smi --query-gpu=name,memory.total,memory.free`.
+fn parse_nvidia_smi(stdout: &str) -> Option<GpuInfo> {
+    let line = stdout.lines().next()?;
+    let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+    let name = parts.first().filter(|n| !n.is_empty())?.to_string();
+    let vram_total_mb = parts.get(1).and_then(|s| s.parse().ok()).unwrap_or(0);
+    let vram_free_mb = parts
+        .get(2)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(vram_total_mb);
+    Some(GpuInfo {
+        name,
+        vram_total_mb,
+        vram_free_mb,
+    })
+}
+
+/// Thread count used when `Config::n_threads` is 0: one per physical core,
+/// capped since whisper.cpp stops scaling beyond ~8 threads.
+pub fn auto_threads(hw: &HardwareInfo) -> usize {
+    hw.physical_cores.clamp(1, MAX_AUTO_THREADS)
+}
+
+/// Device Whisper should use for the configured preference.
+pub fn resolve_device(preference: &ComputeDevice, hw: &HardwareInfo) -> Device {
+    match preference {
+        ComputeDevice::Cpu => Device::Cpu,
+        ComputeDevice::Gpu | ComputeDevice::Auto if hw.gpu_usable() => Device::Gpu,
+        ComputeDevice::Gpu | ComputeDevice::Auto => Device::Cpu,
+    }
+}
+
+/// Engine options for the current config on this machine.
+pub fn engine_options(config: &Config, hw: &HardwareInfo) -> EngineOptions {
+    let n_threads = if config.n_threads == 0 {
+        auto_threads(hw)
+    } else {
+        config.n_threads
+    };
+    EngineOptions {
+        use_gpu: resolve_device(&config.compute_device, hw) == Device::Gpu,
+        gpu_device: config.gpu_device,
+        flash_attn: config.flash_attn,
+        n_threads,
+    }
+}
+
+fn simd_factor(hw: &HardwareInfo) -> f64 {
+    if hw.has_simd("avx512f") {
+        1.4
+    } else if hw.has_simd("avx2") && hw.has_simd("fma") {
+        1.0
+    } else if hw.has_simd("neon") {
+        0.8
+    } else if hw.has_simd("avx") {
+        0.6
+    } else {
+        0.3
+    }
+}
+
+/// Distil and turbo variants keep the large encoder but have a 2-4 layer
+/// decoder, so they run faster than their file size suggests. The encoder
+/// still dominates on short chunks, hence the modest discount.
+fn architecture_factor(model: &WhisperModel) -> f64 {
+    if model.model_type == ModelType::MoonshineOnnx {
+        0.5
+    } else if model.id.contains("distil") || model.id.contains("turbo") {
+        0.7
+    } else {
+        1.0
+    }
+}
+
+/// Estimated real-time factor of `model` on `device`.
+pub fn estimate_rtf(
+    model: &WhisperModel,
+    hw: &HardwareInfo,
+    device: Device,
+    threads: usize,
+) -> f64 {
+    let relative_size = model.size_bytes as f64 / TINY_BYTES;
+    let cost = relative_size * architecture_factor(model);
+    match device {
+        Device::Gpu => cost * TINY_RTF_GPU,
+        Device::Cpu => {
+            // Parallel efficiency drops off with more threads.
+            let parallel = (threads.max(1) as f64).powf(0.8);
+            cost * TINY_RTF_ONE_CORE / (parallel * simd_factor(hw))
+        }
+    }
+}
+
+/// Rank `models` for this machine. Moonshine models always run on the CPU.
+pub fn recommend(
+    models: &[WhisperModel],
+    hw: &HardwareInfo,
+    options: &EngineOptions,
+) -> Recommendations {
+    let device = if options.use_gpu && hw.gpu_usable() {
+        Device::Gpu
+    } else {
+        Device::Cpu
+    };
+
+    let mut ranked: Vec<ModelRecommendation> = models
+        .iter()
+        .filter(|m| m.size_bytes > 0)
+        .map(|m| {
+            let model_device = if m.model_type == ModelType::MoonshineOnnx {
+                Device::Cpu
+            } else {
+                device
+            };
+            let fits = match (model_device, &hw.gpu) {
+                (Device::Gpu, Some(gpu)) => m.vram_mb as u64 <= gpu.vram_total_mb,
+                _ => {
+                    hw.available_ram_mb == 0
+                        || m.size_bytes / (1024 * 1024) * 3 / 2 < hw.available_ram_mb
+                }
+            };
+            ModelRecommendation {
+                model_id: m.id.clone(),
+                display_name: m.display_name.clone(),
+                device: model_device,
+                expected_rtf: estimate_rtf(m, hw, model_device, options.n_threads),
+                fits,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| a.expected_rtf.total_cmp(&b.expected_rtf));
+
+    let recommended = models
+        .iter()
+        .filter(|m| m.model_type == ModelType::WhisperGgml)
+        .filter(|m| {
+            ranked
+                .iter()
+                .any(|r| r.model_id == m.id && r.fits && r.expected_rtf <= TARGET_RTF)
+        })
+        .max_by_key(|m| m.size_bytes)
+        .map(|m| m.id.clone());
+
+    Recommendations {
+        hardware: hw.clone(),
+        device,
+        n_threads: options.n_threads,
+        models: ranked,
+        recommended,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::models::get_model_registry;
+
+    fn laptop() -> HardwareInfo {
+        HardwareInfo {
+            cpu_model: "Test CPU".to_string(),
+            physical_cores: 4,
+            logical_cores: 8,
+            simd: vec!["avx2".to_string(), "fma".to_string()],
+            total_ram_mb: 16_000,
+            available_ram_mb: 12_000,
+            gpu: None,
+            cuda_build: false,
+        }
+    }
+
+    fn workstation() -> HardwareInfo {
+        HardwareInfo {
+            gpu: Some(GpuInfo {
+                name: "RTX 4080".to_string(),
+                vram_total_mb: 16_000,
+                vram_free_mb: 15_000,
+            }),
+            cuda_build: true,
+            ..laptop()
+        }
+    }
+
+    // --- Parsing Tests ---
+
+    #[test]
+    fn test_parse_cpuinfo_x86() {
+        let text = "processor\t: 0\nmodel name\t: Intel(R) Core(TM) i7\nphysical id\t: 0\n\
+                    core id\t: 0\nflags\t\t: fpu sse4_2 avx avx2 fma\n\n\
+                    processor\t: 1\nmodel name\t: Intel(R) Core(TM) i7\nphysical id\t: 0\n\
+                    core id\t: 0\nflags\t\t: fpu sse4_2 avx avx2 fma\n\n\
+                    processor\t: 2\nphysical id\t: 0\ncore id\t: 1\n";
+        let info = parse_cpuinfo(text);
+        assert_eq!(info.model, "Intel(R) Core(TM) i7");
+        assert_eq!(info.physical_cores, Some(2), "hyperthreads share a core id");
+        assert_eq!(info.simd, vec!["sse4_2", "avx", "avx2", "fma"]);
+    }
+
+    #[test]
+    fn test_parse_cpuinfo_arm_reports_neon() {
+        let info = parse_cpuinfo("processor\t: 0\nFeatures\t: fp asimd evtstrm\n");
+        assert!(info.has("neon"));
+        assert_eq!(info.physical_cores, None);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let text = "MemTotal:       16384000 kB\nMemFree:  100 kB\nMemAvailable:    8192000 kB\n";
+        assert_eq!(parse_meminfo(text), (16_000, 8_000));
+        assert_eq!(parse_meminfo(""), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let gpu = parse_nvidia_smi("NVIDIA GeForce RTX 3060, 12288, 11000\n").unwrap();
+        assert_eq!(gpu.name, "NVIDIA GeForce RTX 3060");
+        assert_eq!(gpu.vram_total_mb, 12288);
+        assert_eq!(gpu.vram_free_mb, 11000);
+        assert!(parse_nvidia_smi("").is_none());
+    }
+
+    // --- Device / Thread Resolution Tests ---
+
+    #[test]
+    fn test_resolve_device() {
+        assert_eq!(resolve_device(&ComputeDevice::Auto, &laptop()), Device::Cpu);
+        assert_eq!(resolve_device(&ComputeDevice::Gpu, &laptop()), Device::Cpu);
+        assert_eq!(
+            resolve_device(&ComputeDevice::Auto, &workstation()),
+            Device::Gpu
+        );
+        assert_eq!(
+            resolve_device(&ComputeDevice::Cpu, &workstation()),
+            Device::Cpu
+        );
+    }
+
+    #[test]
+    fn test_gpu_without_cuda_build_is_unusable() {
+        let hw = HardwareInfo {
+            cuda_build: false,
+            ..workstation()
+        };
+        assert_eq!(resolve_device(&ComputeDevice::Auto, &hw), Device::Cpu);
+    }
+
+    #[test]
+    fn test_engine_options_threads() {
+        let mut config = Config::default();
+        assert_eq!(engine_options(&config, &laptop()).n_threads, 4);
+        config.n_threads = 2;
+        assert_eq!(engine_options(&config, &laptop()).n_threads, 2);
+
+        let many = HardwareInfo {
+            physical_cores: 32,
+            ..laptop()
+        };
+        config.n_threads = 0;
+        assert_eq!(engine_options(&config, &many).n_threads, MAX_AUTO_THREADS);
+    }
+
+    // --- Recommendation Tests ---
+
+    #[test]
+    fn test_rtf_ordering_follows_model_size() {
+        let hw = laptop();
+        let registry = get_model_registry();
+        let rtf = |id: &str| {
+            let m = registry.iter().find(|m| m.id == id).unwrap();
+            estimate_rtf(m, &hw, Device::Cpu, 4)
+        };
+        assert!(rtf("tiny") < rtf("base"));
+        assert!(rtf("base") < rtf("small"));
+        assert!(rtf("small") < rtf("large-v3"));
+        assert!(rtf("distil-large-v3") < rtf("large-v3"));
+        assert!(
+            rtf("large-v3") > 1.0,
+            "large-v3 is not real time on 4 cores"
+        );
+    }
+
+    #[test]
+    fn test_recommend_cpu_laptop_picks_small_model() {
+        let hw = laptop();
+        let options = EngineOptions {
+            use_gpu: false,
+            gpu_device: 0,
+            flash_attn: true,
+            n_threads: 4,
+        };
+        let recs = recommend(get_model_registry(), &hw, &options);
+        assert_eq!(recs.device, Device::Cpu);
+        assert_eq!(recs.models.len(), get_model_registry().len());
+        assert!(recs
+            .models
+            .windows(2)
+            .all(|w| w[0].expected_rtf <= w[1].expected_rtf));
+        assert_eq!(recs.recommended.as_deref(), Some("small"));
+    }
+
+    #[test]
+    fn test_recommend_gpu_workstation_picks_large_model() {
+        let hw = workstation();
+        let options = EngineOptions {
+            use_gpu: true,
+            gpu_device: 0,
+            flash_attn: true,
+            n_threads: 4,
+        };
+        let recs = recommend(get_model_registry(), &hw, &options);
+        assert_eq!(recs.device, Device::Gpu);
+        assert_eq!(recs.recommended.as_deref(), Some("large-v3"));
+        let moonshine = recs
+            .models
+            .iter()
+            .find(|m| m.model_id == "moonshine-tiny")
+            .unwrap();
+        assert_eq!(moonshine.device, Device::Cpu);
+    }
+
+    #[test]
+    fn test_recommend_respects_vram() {
+        let hw = HardwareInfo {
+            gpu: Some(GpuInfo {
+                name: "Small GPU".to_string(),
+                vram_total_mb: 2_000,
+                vram_free_mb: 2_000,
+            }),
+            ..workstation()
+        };
+        let options = EngineOptions {
+            use_gpu: true,
+            gpu_device: 0,
+            flash_attn: true,
+            n_threads: 4,
+        };
+        let recs = recommend(get_model_registry(), &hw, &options);
+        let large = recs
+            .models
+            .iter()
+            .find(|m| m.model_id == "large-v3")
+            .unwrap();
+        assert!(!large.fits);
+        assert_eq!(recs.recommended.as_deref(), Some("distil-large-v3"));
+    }
+}
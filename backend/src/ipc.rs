@@ -0,0 +1,307 @@
+//! Local control socket of the running app. `whispertype toggle|start|stop|
+//! status|set-model|transcribe` send one JSON request line and read one JSON
+//! reply line, so compositor keybindings can run a shell command instead of
+//! relying on a global shortcut. An `flock` on a lock file next to the socket keeps the GUI
+//! single-instance: a second launch fails to take it, asks the first instance
+//! to show its window and exits.
+
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest request line accepted.
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// Error from `ControlSocket::bind` when another instance holds the lock.
+pub const ALREADY_RUNNING: &str = "WhisperType is already running";
+
+pub fn socket_path() -> PathBuf {
+    Config::app_dir().join("control.sock")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Toggle,
+    Start,
+    Stop,
+    Status,
+    SetModel {
+        model: String,
+    },
+    /// Bring the main window to the front.
+    Show,
+    /// Transcribe an audio file with the loaded model; the reply is the text.
+    Transcribe {
+        path: PathBuf,
+    },
+}
+
+impl Request {
+    /// How long the client waits for a reply; loading a model or transcribing
+    /// a long recording can be slow.
+    fn timeout(&self) -> Duration {
+        match self {
+            Request::SetModel { .. } | Request::Transcribe { .. } => Duration::from_secs(600),
+            _ => Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reply {
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Handles requests in the app. Runs on the socket's connection threads.
+pub trait RemoteControl: Send + Sync {
+    /// Returns a message for the user, e.g. the new dictation status.
+    fn handle(&self, request: Request) -> Result<String, String>;
+}
+
+/// Whether another instance is listening on `path`.
+pub fn is_running(path: &Path) -> bool {
+    UnixStream::connect(path).is_ok()
+}
+
+/// Send a request to the running instance and return its message.
+pub fn send(path: &Path, request: &Request) -> Result<String, String> {
+    let mut stream =
+        UnixStream::connect(path).map_err(|_| "WhisperType is not running".to_string())?;
+    stream
+        .set_read_timeout(Some(request.timeout()))
+        .map_err(|e| e.to_string())?;
+    let mut line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+    line.push('\n');
+    stream
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("No reply from WhisperType: {}", e))?;
+    let reply: Reply = serde_json::from_str(&reply).map_err(|e| format!("Invalid reply: {}", e))?;
+    if reply.ok {
+        Ok(reply.message)
+    } else {
+        Err(reply.message)
+    }
+}
+
+pub struct ControlSocket {
+    listener: UnixListener,
+    /// Holds the instance lock until the socket is dropped.
+    lock: File,
+}
+
+impl ControlSocket {
+    /// Take the instance lock and listen on `path`, replacing a socket file
+    /// left behind by an instance that exited. Fails with `ALREADY_RUNNING`
+    /// if another instance holds the lock.
+    pub fn bind(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let lock = lock_instance(&path.with_extension("lock"))?;
+        // With the lock held, an existing socket file belongs to an instance
+        // that exited.
+        let _ = std::fs::remove_file(path);
+        let listener = UnixListener::bind(path)
+            .map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+        Ok(Self { listener, lock })
+    }
+
+    /// Accept requests on a background thread for the rest of the process.
+    pub fn serve(self, handler: Arc<dyn RemoteControl>) -> Result<(), String> {
+        let Self { listener, lock } = self;
+        std::thread::Builder::new()
+            .name("control-socket".into())
+            .spawn(move || {
+                let _lock = lock;
+                for stream in listener.incoming().flatten() {
+                    let handler = handler.clone();
+                    // Requests like set-model can take a while; don't block others.
+                    std::thread::spawn(move || respond(stream, handler.as_ref()));
+                }
+            })
+            .map(|_| ())
+            .map_err(|e| format!("Failed to spawn control socket thread: {}", e))
+    }
+}
+
+/// Take an exclusive `flock` on `path`. The OS releases it when the file is
+/// closed, including when the process dies.
+fn lock_instance(path: &Path) -> Result<File, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    // SAFETY: flock only reads the descriptor, which `file` keeps open.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        return Err(ALREADY_RUNNING.to_string());
+    }
+    Ok(file)
+}
+
+fn respond(stream: UnixStream, handler: &dyn RemoteControl) {
+    let mut line = String::new();
+    let read = BufReader::new((&stream).take(MAX_REQUEST_BYTES)).read_line(&mut line);
+    let result = match read {
+        Ok(_) => serde_json::from_str::<Request>(&line)
+            .map_err(|e| format!("Invalid request: {}", e))
+            .and_then(|request| handler.handle(request)),
+        Err(e) => Err(e.to_string()),
+    };
+    let reply = match result {
+        Ok(message) => Reply { ok: true, message },
+        Err(message) => Reply { ok: false, message },
+    };
+    if let Ok(mut json) = serde_json::to_string(&reply) {
+        json.push('\n');
+        let _ = (&stream).write_all(json.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records requests; fails `set-model` for unknown models.
+    #[derive(Default)]
+    struct FakeControl {
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl RemoteControl for FakeControl {
+        fn handle(&self, request: Request) -> Result<String, String> {
+            self.requests.lock().unwrap().push(request.clone());
+            match request {
+                Request::SetModel { model } if model != "base" => {
+                    Err(format!("Unknown model: {}", model))
+                }
+                Request::SetModel { model } => Ok(format!("Loaded {}", model)),
+                Request::Toggle | Request::Start => Ok("listening".to_string()),
+                _ => Ok("idle".to_string()),
+            }
+        }
+    }
+
+    fn serve(path: &Path) -> Arc<FakeControl> {
+        let control = Arc::new(FakeControl::default());
+        ControlSocket::bind(path)
+            .unwrap()
+            .serve(control.clone())
+            .unwrap();
+        control
+    }
+
+    // --- Protocol Tests ---
+
+    #[test]
+    fn test_request_json() {
+        assert_eq!(
+            serde_json::to_string(&Request::SetModel {
+                model: "base".to_string()
+            })
+            .unwrap(),
+            r#"{"command":"set-model","model":"base"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"toggle"}"#).unwrap(),
+            Request::Toggle
+        );
+    }
+
+    #[test]
+    fn test_requests_reach_handler() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        let control = serve(&path);
+
+        assert_eq!(send(&path, &Request::Toggle).unwrap(), "listening");
+        assert_eq!(send(&path, &Request::Status).unwrap(), "idle");
+        let set = |model: &str| Request::SetModel {
+            model: model.to_string(),
+        };
+        assert_eq!(send(&path, &set("base")).unwrap(), "Loaded base");
+        assert_eq!(
+            send(&path, &set("huge")).unwrap_err(),
+            "Unknown model: huge"
+        );
+        assert_eq!(control.requests.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_invalid_request_gets_error_reply() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        serve(&path);
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream.write_all(b"{\"command\":\"explode\"}\n").unwrap();
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply).unwrap();
+        let reply: Reply = serde_json::from_str(&reply).unwrap();
+        assert!(!reply.ok);
+        assert!(reply.message.starts_with("Invalid request"));
+    }
+
+    #[test]
+    fn test_not_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        assert!(!is_running(&path));
+        assert_eq!(
+            send(&path, &Request::Status).unwrap_err(),
+            "WhisperType is not running"
+        );
+    }
+
+    // --- Single Instance Tests ---
+
+    #[test]
+    fn test_second_instance_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        serve(&path);
+        assert!(is_running(&path));
+        assert_eq!(ControlSocket::bind(&path).err().unwrap(), ALREADY_RUNNING);
+    }
+
+    #[test]
+    fn test_lock_refuses_second_bind_before_serving() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        // Not answering yet, but the lock is already held.
+        let first = ControlSocket::bind(&path).unwrap();
+        assert_eq!(ControlSocket::bind(&path).err().unwrap(), ALREADY_RUNNING);
+        assert!(path.exists(), "the first instance's socket is left alone");
+
+        drop(first);
+        assert!(ControlSocket::bind(&path).is_ok());
+    }
+
+    #[test]
+    fn test_stale_socket_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        // Bound but never served, then dropped: the file stays behind.
+        drop(ControlSocket::bind(&path).unwrap());
+        assert!(path.exists());
+        assert!(!is_running(&path));
+
+        serve(&path);
+        assert_eq!(send(&path, &Request::Show).unwrap(), "idle");
+    }
+}
@@ -1,11 +1,20 @@
 //! WhisperType — local AI-powered speech-to-text desktop app built on Tauri v2.
 
+pub mod api;
 pub mod audio;
+pub mod bench;
+pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod dbus;
+pub mod eval;
+pub mod focus;
+pub mod hardware;
+pub mod ipc;
 pub mod model_manager;
 pub mod output;
 pub mod transcription;
+pub mod wyoming;
 
 use std::sync::{Arc, Mutex};
 
@@ -15,6 +24,23 @@ use transcription::engine::TranscriptionEngine;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Single instance: a second launch brings the running app forward instead.
+    let socket_path = ipc::socket_path();
+    let control_socket = match ipc::ControlSocket::bind(&socket_path) {
+        Ok(socket) => Some(socket),
+        Err(e) if e == ipc::ALREADY_RUNNING => {
+            if let Err(e) = ipc::send(&socket_path, &ipc::Request::Show) {
+                eprintln!("ipc: {}", e);
+            }
+            eprintln!("{}", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("ipc: {}", e);
+            None
+        }
+    };
+
     // Initialize ONNX Runtime environment once before any sessions are created.
     // Both Silero VAD and Moonshine (transcribe-rs) use ort with load-dynamic;
     // eager init prevents races between concurrent session creation in different threads.
@@ -22,7 +48,14 @@ pub fn run() {
         eprintln!("ort: failed to initialize environment: {}", e);
     }
 
-    let config = Config::load().unwrap_or_default();
+    let mut config = Config::load().unwrap_or_default();
+    model_manager::init_registry(&config);
+    if !model_manager::sync::reconcile(&mut config).is_empty() {
+        let _ = config.save();
+    }
+    let catalog_url = config.model_catalog_url.clone();
+
+    let downloads = model_manager::DownloadManager::new(config.max_concurrent_downloads);
 
     let app_state = AppState {
         engine: Arc::new(TranscriptionEngine::new()),
@@ -31,6 +64,11 @@ pub fn run() {
         transcription_thread: Mutex::new(None),
         audio_handle: Mutex::new(None),
         last_shortcut: Mutex::new(None),
+        downloads,
+        models_watcher: Mutex::new(None),
+        model_load: transcription::loader::ModelLoadTracker::new(),
+        api: api::ApiHost::default(),
+        wyoming: Mutex::new(None),
     };
 
     tauri::Builder::default()
@@ -60,10 +98,46 @@ pub fn run() {
         )
         .manage(app_state)
         .setup(|app| {
+            use tauri::Manager;
             use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
 
             let shortcut = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::Space);
             app.global_shortcut().register(shortcut)?;
+
+            commands::models::start_models_watcher(app.handle());
+
+            commands::api::forward_events(app.handle());
+            if let Err(e) = commands::api::apply_api_config(app.handle()) {
+                eprintln!("api: {}", e);
+            }
+            if let Err(e) = commands::wyoming::apply_wyoming_config(app.handle()) {
+                eprintln!("wyoming: {}", e);
+            }
+
+            let control = Arc::new(commands::dictation::AppControl(app.handle().clone()));
+            let hub = app.state::<AppState>().api.hub.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = dbus::run(control, hub).await {
+                    eprintln!("dbus: {}", e);
+                }
+            });
+
+            if let Some(socket) = control_socket {
+                let remote = Arc::new(commands::remote::AppRemote(app.handle().clone()));
+                if let Err(e) = socket.serve(remote) {
+                    eprintln!("ipc: {}", e);
+                }
+            }
+
+            commands::models::load_default_model(app.handle());
+
+            if let Some(url) = catalog_url {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = model_manager::catalog::refresh_catalog(Some(&url)).await {
+                        eprintln!("Model catalog refresh failed: {}", e);
+                    }
+                });
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -72,13 +146,31 @@ pub fn run() {
             commands::dictation::stop_dictation,
             commands::models::list_models,
             commands::models::download_model,
+            commands::models::cancel_download,
             commands::models::delete_model,
             commands::models::load_model,
             commands::models::get_active_model,
+            commands::models::get_loading_model,
+            commands::models::get_engine_mode,
+            commands::models::run_benchmark,
+            commands::models::get_benchmark_results,
+            commands::models::add_custom_model,
+            commands::models::remove_custom_model,
+            commands::models::refresh_model_catalog,
+            commands::models::import_model,
+            commands::models::export_model_bundle,
+            commands::models::import_model_bundle,
+            commands::models::get_storage_report,
+            commands::models::cleanup_models_dir,
+            commands::models::set_models_location,
             commands::config::get_config,
             commands::config::update_config,
             commands::system::list_audio_devices,
             commands::system::get_gpu_info,
+            commands::system::get_hardware_info,
+            commands::system::recommend_models,
+            commands::api::get_api_status,
+            commands::api::regenerate_api_token,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -2,6 +2,12 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Headless subcommands (model import/export) run without starting the GUI.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = tauri_app_lib::cli::run(&args) {
+        std::process::exit(code);
+    }
+
     // Work around WebKitGTK Wayland protocol errors on some compositors (e.g. KDE Plasma).
     // Force X11 backend for GDK to use XWayland, which is stable for WebKitGTK rendering.
     #[cfg(target_os = "linux")]
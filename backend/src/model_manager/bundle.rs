@@ -0,0 +1,368 @@
+//! Portable model bundles for air-gapped machines: a directory holding model
+//! files plus a `manifest.json` with their sizes and SHA-256 checksums.
+//!
+//! ```text
+//! bundle/
+//!   manifest.json
+//!   ggml-base.bin
+//!   moonshine-tiny/encoder_model.onnx ...
+//! ```
+
+use super::custom::sha256_file;
+use super::import::{install, validate_source, ImportMode};
+use crate::transcription::models::{ModelType, WhisperModel};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const BUNDLE_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: u32,
+    pub app_version: String,
+    pub models: Vec<BundleModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleModel {
+    pub id: String,
+    pub display_name: String,
+    pub model_type: ModelType,
+    /// File or directory name inside the bundle.
+    pub filename: String,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Path relative to the bundle root.
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BundleImport {
+    pub imported: Vec<String>,
+    /// Models already installed locally, left untouched.
+    pub skipped: Vec<String>,
+}
+
+/// Copy the given installed models from `models_dir` into `dest` and write the
+/// manifest. `dest` must not already contain a bundle.
+pub fn export_bundle(
+    model_ids: &[String],
+    models: &[WhisperModel],
+    models_dir: &Path,
+    dest: &Path,
+) -> Result<BundleManifest, String> {
+    if model_ids.is_empty() {
+        return Err("No models selected for export".to_string());
+    }
+    if dest.join(MANIFEST_FILE).exists() {
+        return Err(format!("{} already contains a bundle", dest.display()));
+    }
+
+    let mut selected = Vec::new();
+    for id in model_ids {
+        let model = models
+            .iter()
+            .find(|m| &m.id == id)
+            .ok_or_else(|| format!("Unknown model: {}", id))?;
+        if model.custom {
+            return Err(format!("Custom model {} cannot be bundled", id));
+        }
+        let source = models_dir.join(&model.filename);
+        validate_source(&source, model)
+            .map_err(|e| format!("Model {} is not installed: {}", id, e))?;
+        selected.push((model, source));
+    }
+
+    std::fs::create_dir_all(dest)
+        .map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+
+    let mut manifest = BundleManifest {
+        format: BUNDLE_FORMAT,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        models: Vec::new(),
+    };
+    for (model, source) in selected {
+        let relative: Vec<String> = if model.files.is_empty() {
+            vec![model.filename.clone()]
+        } else {
+            std::fs::create_dir_all(dest.join(&model.filename)).map_err(|e| e.to_string())?;
+            model
+                .files
+                .iter()
+                .map(|(f, _)| format!("{}/{}", model.filename, f))
+                .collect()
+        };
+
+        let mut files = Vec::new();
+        for rel in relative {
+            let from = if model.files.is_empty() {
+                source.clone()
+            } else {
+                models_dir.join(&rel)
+            };
+            let to = dest.join(&rel);
+            let size_bytes = std::fs::copy(&from, &to)
+                .map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+            files.push(BundleFile {
+                sha256: sha256_file(&to)?,
+                path: rel,
+                size_bytes,
+            });
+        }
+        manifest.models.push(BundleModel {
+            id: model.id.clone(),
+            display_name: model.display_name.clone(),
+            model_type: model.model_type.clone(),
+            filename: model.filename.clone(),
+            files,
+        });
+    }
+
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(dest.join(MANIFEST_FILE), json).map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+pub fn read_manifest(bundle: &Path) -> Result<BundleManifest, String> {
+    let path = bundle.join(MANIFEST_FILE);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let manifest: BundleManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err(format!(
+            "Unsupported bundle format {} (expected {})",
+            manifest.format, BUNDLE_FORMAT
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Verify every file in the bundle against the manifest, then install each
+/// model that is not already present in `models_dir`. Nothing is installed if
+/// any checksum fails.
+pub fn import_bundle(
+    bundle: &Path,
+    models: &[WhisperModel],
+    models_dir: &Path,
+    mode: ImportMode,
+) -> Result<BundleImport, String> {
+    let manifest = read_manifest(bundle)?;
+
+    let mut pending = Vec::new();
+    for entry in &manifest.models {
+        let model = models
+            .iter()
+            .find(|m| m.id == entry.id && !m.custom)
+            .ok_or_else(|| format!("Bundle contains unknown model: {}", entry.id))?;
+        if !is_plain_relative(&entry.filename) {
+            return Err(format!("Invalid path in bundle: {}", entry.filename));
+        }
+        for file in &entry.files {
+            verify_file(bundle, file)?;
+        }
+        pending.push((model, bundle.join(&entry.filename)));
+    }
+
+    let mut result = BundleImport::default();
+    for (model, source) in pending {
+        if models_dir.join(&model.filename).exists() {
+            result.skipped.push(model.id.clone());
+            continue;
+        }
+        validate_source(&source, model)?;
+        install(&source, model, models_dir, mode)?;
+        result.imported.push(model.id.clone());
+    }
+    Ok(result)
+}
+
+fn verify_file(bundle: &Path, file: &BundleFile) -> Result<(), String> {
+    if !is_plain_relative(&file.path) {
+        return Err(format!("Invalid path in bundle: {}", file.path));
+    }
+    let path = bundle.join(&file.path);
+    let size = std::fs::metadata(&path)
+        .map_err(|e| format!("Bundle file {} is missing: {}", file.path, e))?
+        .len();
+    if size != file.size_bytes {
+        return Err(format!(
+            "Bundle file {} is {} bytes, manifest says {}",
+            file.path, size, file.size_bytes
+        ));
+    }
+    let actual = sha256_file(&path)?;
+    if actual != file.sha256.to_ascii_lowercase() {
+        return Err(format!("Checksum mismatch for bundle file {}", file.path));
+    }
+    Ok(())
+}
+
+/// Manifest paths must stay inside the bundle.
+fn is_plain_relative(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::models::get_model_registry;
+
+    fn install_fake_models(models_dir: &Path) {
+        let mut ggml = b"lmgg".to_vec();
+        ggml.extend_from_slice(&[3u8; 256]);
+        std::fs::write(models_dir.join("ggml-tiny.bin"), ggml).unwrap();
+
+        let moonshine = models_dir.join("moonshine-tiny");
+        std::fs::create_dir_all(&moonshine).unwrap();
+        for f in [
+            "encoder_model.onnx",
+            "decoder_model_merged.onnx",
+            "tokenizer.json",
+        ] {
+            std::fs::write(moonshine.join(f), f.as_bytes()).unwrap();
+        }
+    }
+
+    fn ids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_export_then_import_roundtrip() {
+        let source = tempfile::tempdir().unwrap();
+        let bundle = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        install_fake_models(source.path());
+
+        let manifest = export_bundle(
+            &ids(&["tiny", "moonshine-tiny"]),
+            get_model_registry(),
+            source.path(),
+            bundle.path(),
+        )
+        .unwrap();
+        assert_eq!(manifest.models.len(), 2);
+        assert_eq!(manifest.models[1].files.len(), 3);
+        assert!(bundle.path().join(MANIFEST_FILE).is_file());
+
+        let result = import_bundle(
+            bundle.path(),
+            get_model_registry(),
+            target.path(),
+            ImportMode::Copy,
+        )
+        .unwrap();
+        assert_eq!(result.imported, ids(&["tiny", "moonshine-tiny"]));
+        assert_eq!(
+            std::fs::read(target.path().join("ggml-tiny.bin")).unwrap(),
+            std::fs::read(source.path().join("ggml-tiny.bin")).unwrap()
+        );
+        assert!(target
+            .path()
+            .join("moonshine-tiny/tokenizer.json")
+            .is_file());
+
+        // Second import finds everything installed.
+        let again = import_bundle(
+            bundle.path(),
+            get_model_registry(),
+            target.path(),
+            ImportMode::Copy,
+        )
+        .unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_export_requires_installed_model() {
+        let source = tempfile::tempdir().unwrap();
+        let bundle = tempfile::tempdir().unwrap();
+        let err = export_bundle(
+            &ids(&["base"]),
+            get_model_registry(),
+            source.path(),
+            bundle.path(),
+        )
+        .unwrap_err();
+        assert!(err.contains("not installed"));
+    }
+
+    #[test]
+    fn test_import_rejects_corrupted_bundle() {
+        let source = tempfile::tempdir().unwrap();
+        let bundle = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        install_fake_models(source.path());
+        export_bundle(
+            &ids(&["tiny"]),
+            get_model_registry(),
+            source.path(),
+            bundle.path(),
+        )
+        .unwrap();
+
+        let mut data = std::fs::read(bundle.path().join("ggml-tiny.bin")).unwrap();
+        data[10] ^= 0xff;
+        std::fs::write(bundle.path().join("ggml-tiny.bin"), data).unwrap();
+
+        let err = import_bundle(
+            bundle.path(),
+            get_model_registry(),
+            target.path(),
+            ImportMode::Copy,
+        )
+        .unwrap_err();
+        assert!(err.contains("Checksum mismatch"));
+        assert!(!target.path().join("ggml-tiny.bin").exists());
+    }
+
+    #[test]
+    fn test_import_rejects_escaping_paths() {
+        let bundle = tempfile::tempdir().unwrap();
+        let manifest = BundleManifest {
+            format: BUNDLE_FORMAT,
+            app_version: "test".to_string(),
+            models: vec![BundleModel {
+                id: "tiny".to_string(),
+                display_name: "Tiny".to_string(),
+                model_type: ModelType::WhisperGgml,
+                filename: "../ggml-tiny.bin".to_string(),
+                files: vec![],
+            }],
+        };
+        std::fs::write(
+            bundle.path().join(MANIFEST_FILE),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let err = import_bundle(
+            bundle.path(),
+            get_model_registry(),
+            target.path(),
+            ImportMode::Copy,
+        )
+        .unwrap_err();
+        assert!(err.contains("Invalid path"));
+    }
+
+    #[test]
+    fn test_is_plain_relative() {
+        assert!(is_plain_relative("ggml-tiny.bin"));
+        assert!(is_plain_relative("moonshine-tiny/tokenizer.json"));
+        assert!(!is_plain_relative(""));
+        assert!(!is_plain_relative("/etc/passwd"));
+        assert!(!is_plain_relative("a/../../b"));
+    }
+}
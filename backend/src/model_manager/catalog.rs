@@ -0,0 +1,340 @@
+//! Remote model catalog: a JSON document (served from an internal artifact
+//! server, or a local file) that overrides or extends the built-in registry.
+//!
+//! ```json
+//! { "models": [
+//!     { "id": "tiny", "url": "https://artifacts.corp/whisper/ggml-tiny.bin" },
+//!     { "id": "medical-small", "display_name": "Medical Small",
+//!       "filename": "ggml-medical-small.bin",
+//!       "url": "https://artifacts.corp/whisper/ggml-medical-small.bin",
+//!       "size_bytes": 487601967, "vram_mb": 1000 }
+//! ] }
+//! ```
+//!
+//! Entries whose `id` matches a built-in model only replace the fields they set.
+//! New IDs must provide `display_name`, `filename` and a `url` (or `files`).
+
+use super::custom::parse_sha256;
+use crate::config::Config;
+use crate::transcription::models::{get_model_registry, set_catalog, ModelType, WhisperModel};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Deserialize)]
+pub struct Catalog {
+    pub models: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub display_name: Option<String>,
+    pub filename: Option<String>,
+    pub url: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub vram_mb: Option<u16>,
+    pub model_type: Option<ModelType>,
+    pub files: Option<Vec<(String, String)>>,
+    pub sha256: Option<String>,
+}
+
+/// Where the last successfully fetched catalog is cached, so the registry is
+/// still correct when the catalog server is unreachable at startup.
+pub fn catalog_cache_path() -> PathBuf {
+    Config::app_dir().join("catalog.json")
+}
+
+pub fn parse_catalog(json: &str) -> Result<Catalog, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid model catalog: {}", e))
+}
+
+/// Apply catalog entries on top of the built-in registry.
+pub fn merge_catalog(
+    builtin: &[WhisperModel],
+    entries: &[CatalogEntry],
+) -> Result<Vec<WhisperModel>, String> {
+    let mut models = builtin.to_vec();
+    for entry in entries {
+        match models.iter_mut().find(|m| m.id == entry.id) {
+            Some(model) => apply_entry(model, entry)?,
+            None => models.push(new_model(entry)?),
+        }
+    }
+    for model in &models {
+        check_filename(&model.id, &model.filename)?;
+        for (name, _) in &model.files {
+            check_filename(&model.id, name)?;
+        }
+    }
+    Ok(models)
+}
+
+fn apply_entry(model: &mut WhisperModel, entry: &CatalogEntry) -> Result<(), String> {
+    if let Some(v) = &entry.display_name {
+        model.display_name = v.clone();
+    }
+    if let Some(v) = &entry.filename {
+        model.filename = v.clone();
+    }
+    if let Some(v) = &entry.url {
+        model.url = v.clone();
+    }
+    if let Some(v) = entry.size_bytes {
+        model.size_bytes = v;
+    }
+    if let Some(v) = entry.vram_mb {
+        model.vram_mb = v;
+    }
+    if let Some(v) = &entry.model_type {
+        model.model_type = v.clone();
+    }
+    if let Some(v) = &entry.files {
+        model.files = v.clone();
+    }
+    if let Some(v) = &entry.sha256 {
+        let digest =
+            parse_sha256(v.trim()).map_err(|e| format!("Catalog model '{}': {}", entry.id, e))?;
+        model.sha256 = Some(digest);
+    }
+    Ok(())
+}
+
+fn new_model(entry: &CatalogEntry) -> Result<WhisperModel, String> {
+    let missing = |field: &str| format!("Catalog model '{}' is missing '{}'", entry.id, field);
+    if entry.id.trim().is_empty() {
+        return Err("Catalog model with empty ID".to_string());
+    }
+    if entry.url.is_none() && entry.files.is_none() {
+        return Err(missing("url"));
+    }
+    let mut model = WhisperModel {
+        id: entry.id.clone(),
+        display_name: entry
+            .display_name
+            .clone()
+            .ok_or_else(|| missing("display_name"))?,
+        filename: entry.filename.clone().ok_or_else(|| missing("filename"))?,
+        url: String::new(),
+        size_bytes: 0,
+        vram_mb: 0,
+        model_type: ModelType::default(),
+        files: vec![],
+        sha256: None,
+        local_path: None,
+        custom: false,
+    };
+    apply_entry(&mut model, entry)?;
+    Ok(model)
+}
+
+/// Filenames are joined onto `models_dir()`, so they must not contain paths.
+fn check_filename(id: &str, name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\\') {
+        return Err(format!(
+            "Catalog model '{}' has invalid filename '{}'",
+            id, name
+        ));
+    }
+    Ok(())
+}
+
+/// Read the catalog document from an `http(s)://` URL or a local file path.
+pub async fn fetch_catalog(source: &str) -> Result<String, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let resp = super::download::http_client()?
+            .get(source)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch model catalog: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!(
+                "Failed to fetch model catalog: HTTP {}",
+                resp.status()
+            ));
+        }
+        resp.text()
+            .await
+            .map_err(|e| format!("Failed to read model catalog: {}", e))
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read model catalog {}: {}", path, e))
+    }
+}
+
+/// Apply a catalog document to the live registry and cache it at `cache`.
+/// Returns the number of models in the merged registry.
+pub fn install_catalog(json: &str, cache: Option<&Path>) -> Result<usize, String> {
+    let catalog = parse_catalog(json)?;
+    let models = merge_catalog(get_model_registry(), &catalog.models)?;
+    let count = models.len();
+    set_catalog(Some(models));
+    if let Some(cache) = cache {
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(cache, json).map_err(|e| e.to_string())?;
+    }
+    Ok(count)
+}
+
+/// Load the cached catalog (if any) into the registry. Used at startup before
+/// the network refresh completes.
+pub fn load_cached_catalog() {
+    if let Ok(json) = std::fs::read_to_string(catalog_cache_path()) {
+        if let Err(e) = install_catalog(&json, None) {
+            eprintln!("Ignoring cached model catalog: {}", e);
+        }
+    }
+}
+
+/// Fetch the catalog at `source` and install it, or restore the built-in
+/// registry when no catalog is configured.
+pub async fn refresh_catalog(source: Option<&str>) -> Result<usize, String> {
+    match source.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(source) => {
+            let json = fetch_catalog(source).await?;
+            install_catalog(&json, Some(&catalog_cache_path()))
+        }
+        None => {
+            set_catalog(None);
+            let _ = std::fs::remove_file(catalog_cache_path());
+            Ok(get_model_registry().len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const CATALOG: &str = r#"{ "models": [
+        { "id": "tiny", "url": "http://mirror.local/ggml-tiny.bin", "sha256":
+          "ABABABABABABABABABABABABABABABABABABABABABABABABABABABABABABABAB" },
+        { "id": "medical-small", "display_name": "Medical Small",
+          "filename": "ggml-medical-small.bin",
+          "url": "http://mirror.local/ggml-medical-small.bin",
+          "size_bytes": 1000, "vram_mb": 1000 }
+    ] }"#;
+
+    /// Serve `body` with the given status to every request on 127.0.0.1.
+    async fn serve(status: u16, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut sock, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = sock.read(&mut buf).await;
+                let resp = format!(
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                sock.write_all(resp.as_bytes()).await.ok();
+            }
+        });
+        format!("http://{}/catalog.json", addr)
+    }
+
+    // --- Merge Tests ---
+
+    #[test]
+    fn test_merge_overrides_only_given_fields() {
+        let catalog = parse_catalog(CATALOG).unwrap();
+        let models = merge_catalog(get_model_registry(), &catalog.models).unwrap();
+        let tiny = models.iter().find(|m| m.id == "tiny").unwrap();
+        let builtin = get_model_registry()
+            .iter()
+            .find(|m| m.id == "tiny")
+            .unwrap();
+
+        assert_eq!(tiny.url, "http://mirror.local/ggml-tiny.bin");
+        assert_eq!(tiny.sha256, Some("ab".repeat(32)));
+        assert_eq!(tiny.filename, builtin.filename);
+        assert_eq!(tiny.size_bytes, builtin.size_bytes);
+    }
+
+    #[test]
+    fn test_merge_extends_registry() {
+        let catalog = parse_catalog(CATALOG).unwrap();
+        let models = merge_catalog(get_model_registry(), &catalog.models).unwrap();
+        assert_eq!(models.len(), get_model_registry().len() + 1);
+
+        let medical = models.iter().find(|m| m.id == "medical-small").unwrap();
+        assert_eq!(medical.filename, "ggml-medical-small.bin");
+        assert_eq!(medical.model_type, ModelType::WhisperGgml);
+        assert!(!medical.custom);
+    }
+
+    #[test]
+    fn test_merge_rejects_incomplete_new_entry() {
+        let entry = CatalogEntry {
+            id: "new".to_string(),
+            url: Some("http://mirror.local/ggml-new.bin".to_string()),
+            ..Default::default()
+        };
+        let err = merge_catalog(get_model_registry(), &[entry]).unwrap_err();
+        assert!(err.contains("display_name"));
+    }
+
+    #[test]
+    fn test_merge_rejects_path_in_filename() {
+        let entry = CatalogEntry {
+            id: "tiny".to_string(),
+            filename: Some("../../.bashrc".to_string()),
+            ..Default::default()
+        };
+        assert!(merge_catalog(get_model_registry(), &[entry]).is_err());
+    }
+
+    #[test]
+    fn test_merge_rejects_malformed_digest() {
+        let entry = CatalogEntry {
+            id: "tiny".to_string(),
+            sha256: Some("AB".to_string()),
+            ..Default::default()
+        };
+        let err = merge_catalog(get_model_registry(), &[entry]).unwrap_err();
+        assert!(err.contains("Invalid SHA-256"));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_json() {
+        assert!(parse_catalog("{ not json").is_err());
+        assert!(parse_catalog(r#"{ "models": [ { "url": "x" } ] }"#).is_err());
+    }
+
+    // --- Fetch Tests (local HTTP server) ---
+
+    #[tokio::test]
+    async fn test_fetch_catalog_from_http_server() {
+        let url = serve(200, CATALOG).await;
+        let json = fetch_catalog(&url).await.unwrap();
+        assert_eq!(parse_catalog(&json).unwrap().models.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_catalog_http_error() {
+        let url = serve(404, "").await;
+        let err = fetch_catalog(&url).await.unwrap_err();
+        assert!(err.contains("404"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_catalog_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("catalog.json");
+        std::fs::write(&path, CATALOG).unwrap();
+
+        let json = fetch_catalog(&path.to_string_lossy()).await.unwrap();
+        assert_eq!(json, CATALOG);
+        let uri = format!("file://{}", path.display());
+        assert_eq!(fetch_catalog(&uri).await.unwrap(), CATALOG);
+    }
+}
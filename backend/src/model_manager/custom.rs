@@ -0,0 +1,233 @@
+//! Validation for user-registered custom models: ID rules, source checks, GGML
+//! header sniffing, and SHA-256 verification of model files.
+
+use crate::config::CustomModel;
+use crate::transcription::models::base_models;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// First four bytes of a whisper.cpp GGML file (`0x67676d6c` little-endian).
+const GGML_MAGIC: &[u8; 4] = b"lmgg";
+
+/// Check a new custom model against the built-in registry and the already
+/// registered custom models. Returns the model with its checksum normalized
+/// to lowercase.
+pub fn validate_custom_model(
+    model: &CustomModel,
+    existing: &[CustomModel],
+) -> Result<CustomModel, String> {
+    let id = model.id.trim();
+    if id.is_empty() {
+        return Err("Custom model ID must not be empty".to_string());
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Err(format!(
+            "Invalid custom model ID '{}': use letters, digits, '-', '_' or '.'",
+            id
+        ));
+    }
+    if base_models().iter().any(|m| m.id == id) || existing.iter().any(|m| m.id == id) {
+        return Err(format!("A model with ID '{}' already exists", id));
+    }
+
+    let sha256 = match model.sha256.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(hash) => Some(parse_sha256(hash)?),
+    };
+
+    let normalized = CustomModel {
+        id: id.to_string(),
+        display_name: if model.display_name.trim().is_empty() {
+            id.to_string()
+        } else {
+            model.display_name.trim().to_string()
+        },
+        source: model.source.trim().to_string(),
+        sha256,
+    };
+
+    if !normalized.is_url() {
+        let path = Path::new(&normalized.source);
+        if !path.is_absolute() {
+            return Err(format!(
+                "Custom model path must be absolute: {}",
+                normalized.source
+            ));
+        }
+        if !path.is_file() {
+            return Err(format!("Model file not found: {}", normalized.source));
+        }
+        if !is_ggml_file(path) {
+            return Err(format!(
+                "Not a Whisper GGML model (bad magic): {}",
+                normalized.source
+            ));
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// A SHA-256 digest as 64 hex digits, returned in lowercase.
+pub fn parse_sha256(hash: &str) -> Result<String, String> {
+    if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hash.to_ascii_lowercase())
+    } else {
+        Err(format!("Invalid SHA-256 checksum: {}", hash))
+    }
+}
+
+/// True if the file starts with the GGML magic number.
+pub fn is_ggml_file(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .map(|_| &magic == GGML_MAGIC)
+        .unwrap_or(false)
+}
+
+/// Lowercase hex SHA-256 of a file. Blocking — run large files through
+/// `spawn_blocking`.
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Hash a file and compare against an expected lowercase hex SHA-256.
+/// Blocking — run large files through `spawn_blocking`.
+pub fn verify_sha256(path: &Path, expected: &str) -> Result<(), String> {
+    let actual = sha256_file(path)?;
+    if actual == expected.to_ascii_lowercase() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected,
+            actual
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url_model(id: &str) -> CustomModel {
+        CustomModel {
+            id: id.to_string(),
+            display_name: String::new(),
+            source: "https://models.internal/ggml-domain.bin".to_string(),
+            sha256: None,
+        }
+    }
+
+    fn write_ggml(dir: &Path) -> std::path::PathBuf {
+        let path = dir.join("ggml-domain.bin");
+        let mut data = GGML_MAGIC.to_vec();
+        data.extend_from_slice(&[0u8; 64]);
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_validate_accepts_url_model_and_defaults_display_name() {
+        let model = validate_custom_model(&url_model("domain-small"), &[]).unwrap();
+        assert_eq!(model.display_name, "domain-small");
+    }
+
+    #[test]
+    fn test_validate_rejects_builtin_id() {
+        let err = validate_custom_model(&url_model("tiny"), &[]).unwrap_err();
+        assert!(err.contains("already exists"));
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_custom_id() {
+        let existing = vec![url_model("domain")];
+        assert!(validate_custom_model(&url_model("domain"), &existing).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_ids() {
+        assert!(validate_custom_model(&url_model(""), &[]).is_err());
+        assert!(validate_custom_model(&url_model("../escape"), &[]).is_err());
+        assert!(validate_custom_model(&url_model("has space"), &[]).is_err());
+    }
+
+    #[test]
+    fn test_validate_normalizes_checksum() {
+        let mut model = url_model("domain");
+        model.sha256 = Some("AB".repeat(32));
+        let model = validate_custom_model(&model, &[]).unwrap();
+        assert_eq!(model.sha256, Some("ab".repeat(32)));
+
+        let mut bad = url_model("domain");
+        bad.sha256 = Some("xyz".to_string());
+        assert!(validate_custom_model(&bad, &[]).is_err());
+    }
+
+    #[test]
+    fn test_validate_local_file_checks_magic() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = write_ggml(dir.path());
+        let mut model = url_model("local");
+        model.source = good.to_string_lossy().to_string();
+        assert!(validate_custom_model(&model, &[]).is_ok());
+
+        let bad = dir.path().join("notes.txt");
+        std::fs::write(&bad, b"not a model").unwrap();
+        model.source = bad.to_string_lossy().to_string();
+        assert!(validate_custom_model(&model, &[])
+            .unwrap_err()
+            .contains("bad magic"));
+
+        model.source = dir.path().join("missing.bin").to_string_lossy().to_string();
+        assert!(validate_custom_model(&model, &[])
+            .unwrap_err()
+            .contains("not found"));
+    }
+
+    #[test]
+    fn test_validate_rejects_relative_path() {
+        let mut model = url_model("local");
+        model.source = "models/ggml-domain.bin".to_string();
+        assert!(validate_custom_model(&model, &[])
+            .unwrap_err()
+            .contains("absolute"));
+    }
+
+    #[test]
+    fn test_verify_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.bin");
+        std::fs::write(&path, b"abc").unwrap();
+        let expected = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+        assert!(verify_sha256(&path, expected).is_ok());
+        assert!(verify_sha256(&path, &expected.to_uppercase()).is_ok());
+        assert!(verify_sha256(&path, &"0".repeat(64))
+            .unwrap_err()
+            .contains("Checksum mismatch"));
+    }
+}
@@ -2,101 +2,267 @@
 //! deletion, and download-status queries. Supports both single-file (Whisper GGML)
 //! and multi-file (Moonshine ONNX) models.
 
+use super::custom::verify_sha256;
+use super::manager::DOWNLOAD_CANCELLED;
 use crate::config::Config;
-use crate::transcription::models::{get_model_registry, ModelType};
+use crate::transcription::models::{find_model, ModelType, WhisperModel};
 use futures_util::StreamExt;
-use reqwest::Client;
-use std::path::PathBuf;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
 
-pub async fn download_model(model_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
-    let registry = get_model_registry();
-    let model = registry
-        .iter()
-        .find(|m| m.id == model_id)
-        .ok_or_else(|| format!("Unknown model: {}", model_id))?;
+/// Payload of the `download-progress` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DownloadProgress {
+    pub model_id: String,
+    pub percent: f64,
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
+    pub status: DownloadStatus,
+    /// Attempt that just failed (only meaningful while `status` is `retrying`).
+    pub attempt: u32,
+    /// Delay before the next attempt, in milliseconds.
+    pub retry_in_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Downloading,
+    Retrying,
+}
 
-    let dest = Config::models_dir().join(&model.filename);
+/// Exponential backoff policy for transient download failures.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts per file, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after the given failed attempt (1-based): initial * 2^(attempt-1), capped.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Progress notifications from `Fetcher::fetch`, in bytes of the current file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TransferEvent {
+    /// `file_size` is the whole file's size as the server reports it.
+    Progress {
+        file_bytes: u64,
+        file_size: Option<u64>,
+    },
+    Retrying {
+        attempt: u32,
+        delay: Duration,
+    },
+}
+
+/// Everything needed to fetch the files of one model download.
+pub(crate) struct Fetcher {
+    pub client: Client,
+    pub policy: RetryPolicy,
+    pub token: CancellationToken,
+}
+
+enum FetchError {
+    /// Network hiccup, 5xx, timeout — worth retrying from where we left off.
+    Transient(String),
+    /// 404, bad URL, disk errors — retrying will not help.
+    Fatal(String),
+    Cancelled,
+}
+
+/// Download a registry model. Cancelling `token` stops the transfer and removes
+/// its temp files; other failures leave them in place so the next call resumes.
+/// A cancel that arrives once the model is in place is ignored.
+pub async fn download_model(
+    model_id: &str,
+    app_handle: &AppHandle,
+    token: &CancellationToken,
+) -> Result<PathBuf, String> {
+    let model = find_model(model_id).ok_or_else(|| format!("Unknown model: {}", model_id))?;
+
+    let dest = model.model_path();
 
     // Skip if already downloaded
     if is_model_downloaded(model_id) {
         return Ok(dest);
     }
 
+    // Custom models registered from a local path have nothing to download.
+    if let Some(path) = &model.local_path {
+        return Err(format!("Custom model file not found: {}", path.display()));
+    }
+
     let models_dir = Config::models_dir();
     tokio::fs::create_dir_all(&models_dir)
         .await
         .map_err(|e| format!("Failed to create models dir: {}", e))?;
 
-    if !model.files.is_empty() {
+    let fetcher = Fetcher {
+        client: http_client()?,
+        policy: RetryPolicy::default(),
+        token: token.clone(),
+    };
+    let emit = |progress: DownloadProgress| {
+        app_handle.emit("download-progress", progress).ok();
+    };
+
+    let result = if !model.files.is_empty() {
         // Multi-file model (Moonshine): create directory, download each file
-        download_multi_file(model_id, &dest, &model.files, model.size_bytes, app_handle).await?;
+        download_multi_file(
+            &fetcher,
+            model_id,
+            &dest,
+            &model.files,
+            model.size_bytes,
+            emit,
+        )
+        .await
     } else {
         // Single-file model (Whisper)
-        download_single_file(model_id, &dest, &model.url, model.size_bytes, app_handle).await?;
+        download_single_file(
+            &fetcher,
+            model_id,
+            &dest,
+            &model.url,
+            model.size_bytes,
+            model.sha256.as_deref(),
+            emit,
+        )
+        .await
+    };
+
+    match result {
+        Ok(()) => Ok(dest),
+        Err(_) if token.is_cancelled() => {
+            remove_temp_files(&model);
+            Err(DOWNLOAD_CANCELLED.to_string())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Remove the `.tmp` files of a partial download, and the model directory of a
+/// multi-file model if nothing else is in it.
+fn remove_temp_files(model: &WhisperModel) {
+    let dest = model.model_path();
+    if model.files.is_empty() {
+        std::fs::remove_file(dest.with_extension("bin.tmp")).ok();
+    } else {
+        for (filename, _) in &model.files {
+            std::fs::remove_file(dest.join(filename).with_extension("tmp")).ok();
+        }
+        // Fails (and is ignored) if completed files remain.
+        std::fs::remove_dir(&dest).ok();
     }
+}
 
-    Ok(dest)
+/// Client used for model downloads. No overall timeout (large-v3 is ~3 GB), but a
+/// stalled connection is turned into a retryable error instead of hanging forever.
+pub(crate) fn http_client() -> Result<Client, String> {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .read_timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn progress_payload(
+    model_id: &str,
+    downloaded: u64,
+    total: u64,
+    event: &TransferEvent,
+) -> DownloadProgress {
+    let (status, attempt, retry_in_ms) = match event {
+        TransferEvent::Progress { .. } => (DownloadStatus::Downloading, 0, 0),
+        TransferEvent::Retrying { attempt, delay } => {
+            (DownloadStatus::Retrying, *attempt, delay.as_millis() as u64)
+        }
+    };
+    DownloadProgress {
+        model_id: model_id.to_string(),
+        percent: if total == 0 {
+            0.0
+        } else {
+            (downloaded as f64 / total as f64) * 100.0
+        },
+        downloaded_bytes: downloaded,
+        total_bytes: total,
+        status,
+        attempt,
+        retry_in_ms,
+    }
 }
 
 async fn download_single_file(
+    fetcher: &Fetcher,
     model_id: &str,
-    dest: &PathBuf,
+    dest: &Path,
     url: &str,
     expected_size: u64,
-    app_handle: &AppHandle,
+    sha256: Option<&str>,
+    mut emit: impl FnMut(DownloadProgress),
 ) -> Result<(), String> {
-    let client = Client::new();
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Download request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Download failed with status: {}",
-            response.status()
-        ));
-    }
-
-    let total = response.content_length().unwrap_or(expected_size);
-    let mut downloaded: u64 = 0;
-
     let temp_path = dest.with_extension("bin.tmp");
-    let mut file = tokio::fs::File::create(&temp_path)
-        .await
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let mut downloaded: u64 = 0;
+    // Custom URL models have no registry size; use the server's.
+    let mut total = expected_size;
 
-    let mut stream = response.bytes_stream();
+    fetcher
+        .fetch(url, &temp_path, |event| {
+            if let TransferEvent::Progress {
+                file_bytes,
+                file_size,
+            } = event
+            {
+                downloaded = file_bytes;
+                if expected_size == 0 {
+                    total = file_size.unwrap_or(0);
+                }
+            }
+            emit(progress_payload(model_id, downloaded, total, &event));
+        })
+        .await?;
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download stream error: {}", e))?;
-        file.write_all(&chunk)
+    if let Some(expected) = sha256 {
+        let (path, expected) = (temp_path.clone(), expected.to_string());
+        let verified = tokio::task::spawn_blocking(move || verify_sha256(&path, &expected))
             .await
-            .map_err(|e| format!("Failed to write chunk: {}", e))?;
-
-        downloaded += chunk.len() as u64;
-
-        app_handle
-            .emit(
-                "download-progress",
-                serde_json::json!({
-                    "model_id": model_id,
-                    "percent": (downloaded as f64 / total as f64) * 100.0,
-                    "downloaded_bytes": downloaded,
-                    "total_bytes": total,
-                }),
-            )
-            .ok();
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = verified {
+            // A corrupt file must not be resumed next time.
+            tokio::fs::remove_file(&temp_path).await.ok();
+            return Err(e);
+        }
     }
 
-    file.flush()
-        .await
-        .map_err(|e| format!("Failed to flush file: {}", e))?;
-    drop(file);
-
+    // The last point a cancel applies; once renamed, the model is installed.
+    if fetcher.token.is_cancelled() {
+        return Err(DOWNLOAD_CANCELLED.to_string());
+    }
     tokio::fs::rename(&temp_path, dest)
         .await
         .map_err(|e| format!("Failed to rename temp file: {}", e))?;
@@ -105,19 +271,19 @@ async fn download_single_file(
 }
 
 async fn download_multi_file(
+    fetcher: &Fetcher,
     model_id: &str,
-    model_dir: &PathBuf,
+    model_dir: &Path,
     files: &[(String, String)],
     total_size: u64,
-    app_handle: &AppHandle,
+    mut emit: impl FnMut(DownloadProgress),
 ) -> Result<(), String> {
     // Create model directory
     tokio::fs::create_dir_all(model_dir)
         .await
         .map_err(|e| format!("Failed to create model dir: {}", e))?;
 
-    let client = Client::new();
-    let mut total_downloaded: u64 = 0;
+    let mut completed: u64 = 0;
 
     for (filename, url) in files {
         let file_dest = model_dir.join(filename);
@@ -125,75 +291,248 @@ async fn download_multi_file(
         // Skip files already downloaded
         if file_dest.exists() {
             if let Ok(meta) = std::fs::metadata(&file_dest) {
-                total_downloaded += meta.len();
+                completed += meta.len();
             }
             continue;
         }
 
-        let response = client
-            .get(url)
-            .send()
+        let temp_path = file_dest.with_extension("tmp");
+        let mut file_bytes: u64 = 0;
+
+        fetcher
+            .fetch(url, &temp_path, |event| {
+                if let TransferEvent::Progress { file_bytes: n, .. } = event {
+                    file_bytes = n;
+                }
+                emit(progress_payload(
+                    model_id,
+                    completed + file_bytes,
+                    total_size,
+                    &event,
+                ));
+            })
             .await
-            .map_err(|e| format!("Download request for {} failed: {}", filename, e))?;
+            .map_err(|e| format!("{} ({})", e, filename))?;
 
-        if !response.status().is_success() {
-            return Err(format!(
-                "Download of {} failed with status: {}",
-                filename,
-                response.status()
-            ));
+        completed += file_bytes;
+
+        if fetcher.token.is_cancelled() {
+            return Err(DOWNLOAD_CANCELLED.to_string());
         }
+        tokio::fs::rename(&temp_path, &file_dest)
+            .await
+            .map_err(|e| format!("Failed to rename temp file for {}: {}", filename, e))?;
+    }
 
-        let temp_path = file_dest.with_extension("tmp");
-        let mut file = tokio::fs::File::create(&temp_path)
+    Ok(())
+}
+
+impl Fetcher {
+    /// Download `url` into `temp_path`, resuming from whatever the temp file already
+    /// holds and retrying transient failures according to `policy`. The caller renames
+    /// the temp file into place once this returns `Ok`.
+    pub(crate) async fn fetch(
+        &self,
+        url: &str,
+        temp_path: &Path,
+        mut on_event: impl FnMut(TransferEvent),
+    ) -> Result<(), String> {
+        let mut attempt: u32 = 1;
+        loop {
+            match self.attempt(url, temp_path, &mut on_event).await {
+                Ok(()) => return Ok(()),
+                Err(FetchError::Fatal(e)) => return Err(e),
+                Err(FetchError::Cancelled) => return Err(DOWNLOAD_CANCELLED.to_string()),
+                Err(FetchError::Transient(e)) if attempt < self.policy.max_attempts => {
+                    let delay = self.policy.backoff(attempt);
+                    eprintln!(
+                        "download: attempt {} failed ({}), retrying in {}ms",
+                        attempt,
+                        e,
+                        delay.as_millis()
+                    );
+                    on_event(TransferEvent::Retrying { attempt, delay });
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = self.token.cancelled() => return Err(DOWNLOAD_CANCELLED.to_string()),
+                    }
+                    attempt += 1;
+                }
+                Err(FetchError::Transient(e)) => {
+                    return Err(format!("{} (gave up after {} attempts)", e, attempt))
+                }
+            }
+        }
+    }
+
+    /// One request/stream cycle. Sends a Range request if the temp file is non-empty
+    /// and appends to it on 206; a plain 200 means the server ignored the range, so the
+    /// temp file is truncated and the transfer starts over.
+    async fn attempt(
+        &self,
+        url: &str,
+        temp_path: &Path,
+        on_event: &mut impl FnMut(TransferEvent),
+    ) -> Result<(), FetchError> {
+        let offset = tokio::fs::metadata(temp_path)
             .await
-            .map_err(|e| format!("Failed to create temp file for {}: {}", filename, e))?;
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+
+        let response = tokio::select! {
+            r = request.send() => r,
+            _ = self.token.cancelled() => return Err(FetchError::Cancelled),
+        }
+        .map_err(|e| {
+            let msg = format!("Download request failed: {}", e);
+            if e.is_builder() {
+                FetchError::Fatal(msg)
+            } else {
+                FetchError::Transient(msg)
+            }
+        })?;
+
+        let status = response.status();
+        let resuming = if status == StatusCode::PARTIAL_CONTENT && offset > 0 {
+            if content_range_start(&response) != Some(offset) {
+                // Server answered a different range than we asked for; start over.
+                remove_temp(temp_path).await?;
+                return Err(FetchError::Transient(
+                    "Server returned an unexpected range".to_string(),
+                ));
+            }
+            true
+        } else if status == StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+            // Either the temp file already holds the whole resource, or it is
+            // longer than the resource (stale/corrupt). Content-Range: bytes */<len>
+            if content_range_total(&response) == Some(offset) {
+                on_event(TransferEvent::Progress {
+                    file_bytes: offset,
+                    file_size: Some(offset),
+                });
+                return Ok(());
+            }
+            remove_temp(temp_path).await?;
+            return Err(FetchError::Transient(
+                "Partial download does not match server file".to_string(),
+            ));
+        } else if status.is_success() {
+            false
+        } else if status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+        {
+            return Err(FetchError::Transient(format!(
+                "Download failed with status: {}",
+                status
+            )));
+        } else {
+            return Err(FetchError::Fatal(format!(
+                "Download failed with status: {}",
+                status
+            )));
+        };
+
+        let mut file = if resuming {
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(temp_path)
+                .await
+        } else {
+            tokio::fs::File::create(temp_path).await
+        }
+        .map_err(|e| FetchError::Fatal(format!("Failed to open temp file: {}", e)))?;
+
+        let mut written = if resuming { offset } else { 0 };
+        let file_size = if resuming {
+            content_range_total(&response)
+        } else {
+            response.content_length()
+        };
+        on_event(TransferEvent::Progress {
+            file_bytes: written,
+            file_size,
+        });
 
         let mut stream = response.bytes_stream();
 
-        while let Some(chunk) = stream.next().await {
-            let chunk =
-                chunk.map_err(|e| format!("Download stream error for {}: {}", filename, e))?;
+        loop {
+            let chunk = tokio::select! {
+                c = stream.next() => c,
+                _ = self.token.cancelled() => return Err(FetchError::Cancelled),
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+            let chunk = match chunk {
+                Ok(c) => c,
+                Err(e) => {
+                    // Keep what we have on disk so the next attempt can resume.
+                    file.flush().await.ok();
+                    return Err(FetchError::Transient(format!(
+                        "Download stream error: {}",
+                        e
+                    )));
+                }
+            };
             file.write_all(&chunk)
                 .await
-                .map_err(|e| format!("Failed to write chunk for {}: {}", filename, e))?;
-
-            total_downloaded += chunk.len() as u64;
-
-            app_handle
-                .emit(
-                    "download-progress",
-                    serde_json::json!({
-                        "model_id": model_id,
-                        "percent": (total_downloaded as f64 / total_size as f64) * 100.0,
-                        "downloaded_bytes": total_downloaded,
-                        "total_bytes": total_size,
-                    }),
-                )
-                .ok();
+                .map_err(|e| FetchError::Fatal(format!("Failed to write chunk: {}", e)))?;
+
+            written += chunk.len() as u64;
+            on_event(TransferEvent::Progress {
+                file_bytes: written,
+                file_size,
+            });
         }
 
         file.flush()
             .await
-            .map_err(|e| format!("Failed to flush file {}: {}", filename, e))?;
-        drop(file);
+            .map_err(|e| FetchError::Fatal(format!("Failed to flush file: {}", e)))?;
 
-        tokio::fs::rename(&temp_path, &file_dest)
-            .await
-            .map_err(|e| format!("Failed to rename temp file for {}: {}", filename, e))?;
+        Ok(())
     }
+}
 
-    Ok(())
+async fn remove_temp(temp_path: &Path) -> Result<(), FetchError> {
+    match tokio::fs::remove_file(temp_path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(FetchError::Fatal(format!(
+            "Failed to remove stale temp file: {}",
+            e
+        ))),
+    }
+}
+
+/// Parse `Content-Range: bytes <start>-<end>/<total>` and return `start`.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+/// Parse the `<total>` part of a `Content-Range` header.
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    value.rsplit('/').next()?.trim().parse().ok()
 }
 
 pub fn delete_model(model_id: &str) -> Result<(), String> {
-    let registry = get_model_registry();
-    let model = registry
-        .iter()
-        .find(|m| m.id == model_id)
-        .ok_or_else(|| format!("Unknown model: {}", model_id))?;
+    let model = find_model(model_id).ok_or_else(|| format!("Unknown model: {}", model_id))?;
 
-    let path = Config::models_dir().join(&model.filename);
+    // Custom models registered from a local path are the user's own files;
+    // unregistering them is `remove_custom_model`'s job, not ours.
+    if model.local_path.is_some() {
+        return Ok(());
+    }
+
+    let path = model.model_path();
     if path.exists() {
         if model.model_type == ModelType::MoonshineOnnx {
             std::fs::remove_dir_all(&path)
@@ -206,12 +545,9 @@ pub fn delete_model(model_id: &str) -> Result<(), String> {
 }
 
 pub fn is_model_downloaded(model_id: &str) -> bool {
-    let registry = get_model_registry();
-    registry
-        .iter()
-        .find(|m| m.id == model_id)
+    find_model(model_id)
         .map(|m| {
-            let base = Config::models_dir().join(&m.filename);
+            let base = m.model_path();
             if m.files.is_empty() {
                 // Single-file: check file exists and is non-empty
                 base.exists()
@@ -229,6 +565,7 @@ pub fn is_model_downloaded(model_id: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transcription::models::get_model_registry;
     use std::sync::Mutex;
 
     /// Guards tests that create/delete files in the shared moonshine-tiny directory.
@@ -340,6 +677,447 @@ mod tests {
         let _ = std::fs::remove_dir_all(&model_dir);
     }
 
+    // --- Resumable fetch Tests (local HTTP stand-in) ---
+
+    use std::sync::Arc;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Behaviour of the stand-in server for one test.
+    #[derive(Clone, Default)]
+    struct StandIn {
+        body: Vec<u8>,
+        /// Close the connection after this many body bytes on the first request.
+        truncate_first_at: Option<usize>,
+        /// Answer every request with 200 and the full body, ignoring Range.
+        ignore_range: bool,
+        /// Status codes returned (without a body) for the first N requests.
+        fail_with: Vec<u16>,
+    }
+
+    /// Minimal HTTP/1.1 server on 127.0.0.1 that understands `Range: bytes=N-`.
+    /// Returns the URL and the Range header (if any) of every request received.
+    async fn spawn_stand_in(cfg: StandIn) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let seen: Arc<Mutex<Vec<Option<String>>>> = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+
+        tokio::spawn(async move {
+            let mut request_no = 0usize;
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = sock.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buf[..n]);
+                }
+                let head = String::from_utf8_lossy(&head).to_string();
+                let range = head
+                    .lines()
+                    .find(|l| l.to_ascii_lowercase().starts_with("range:"))
+                    .map(|l| l[6..].trim().to_string());
+                seen_clone.lock().unwrap().push(range.clone());
+
+                let this_request = request_no;
+                request_no += 1;
+
+                if let Some(code) = cfg.fail_with.get(this_request) {
+                    let resp = format!(
+                        "HTTP/1.1 {} Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        code
+                    );
+                    sock.write_all(resp.as_bytes()).await.ok();
+                    continue;
+                }
+
+                let total = cfg.body.len();
+                let start = range
+                    .as_deref()
+                    .filter(|_| !cfg.ignore_range)
+                    .and_then(|r| r.strip_prefix("bytes="))
+                    .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+
+                let (status_line, from, extra) = match start {
+                    Some(s) if s >= total => {
+                        let resp = format!(
+                            "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            total
+                        );
+                        sock.write_all(resp.as_bytes()).await.ok();
+                        continue;
+                    }
+                    Some(s) => (
+                        "HTTP/1.1 206 Partial Content",
+                        s,
+                        format!("Content-Range: bytes {}-{}/{}\r\n", s, total - 1, total),
+                    ),
+                    None => ("HTTP/1.1 200 OK", 0, String::new()),
+                };
+
+                let payload = &cfg.body[from..];
+                let resp_head = format!(
+                    "{}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+                    status_line,
+                    payload.len(),
+                    extra
+                );
+                sock.write_all(resp_head.as_bytes()).await.ok();
+                let send = match cfg.truncate_first_at {
+                    Some(n) if this_request == 0 => &payload[..n.min(payload.len())],
+                    _ => payload,
+                };
+                sock.write_all(send).await.ok();
+                sock.shutdown().await.ok();
+            }
+        });
+
+        (format!("http://{}/ggml-test.bin", addr), seen)
+    }
+
+    fn test_body(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn fetcher(policy: RetryPolicy) -> Fetcher {
+        Fetcher {
+            client: Client::new(),
+            policy,
+            token: CancellationToken::new(),
+        }
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(5), Duration::from_secs(10), "capped");
+        assert_eq!(policy.backoff(64), Duration::from_secs(10), "no overflow");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_resumes_after_interrupted_stream() {
+        let body = test_body(64 * 1024);
+        let (url, seen) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            truncate_first_at: Some(20_000),
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+
+        let mut events = Vec::new();
+        fetcher(fast_policy(3))
+            .fetch(&url, &temp, |e| events.push(e))
+            .await
+            .expect("download should succeed after one retry");
+
+        assert_eq!(std::fs::read(&temp).unwrap(), body);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0], None);
+        assert_eq!(seen[1].as_deref(), Some("bytes=20000-"));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, TransferEvent::Retrying { attempt: 1, .. })));
+        assert_eq!(
+            events.last(),
+            Some(&TransferEvent::Progress {
+                file_bytes: body.len() as u64,
+                file_size: Some(body.len() as u64),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_resumes_from_existing_temp_file() {
+        let body = test_body(10_000);
+        let (url, seen) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+        std::fs::write(&temp, &body[..4_000]).unwrap();
+
+        fetcher(fast_policy(1))
+            .fetch(&url, &temp, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&temp).unwrap(), body);
+        assert_eq!(
+            seen.lock().unwrap().as_slice(),
+            &[Some("bytes=4000-".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_restarts_when_server_ignores_range() {
+        let body = test_body(10_000);
+        let (url, _) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            ignore_range: true,
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+        std::fs::write(&temp, b"stale bytes from another server").unwrap();
+
+        fetcher(fast_policy(1))
+            .fetch(&url, &temp, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read(&temp).unwrap(),
+            body,
+            "200 response must truncate the temp file, not append"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_complete_temp_file_is_accepted() {
+        let body = test_body(5_000);
+        let (url, _) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+        std::fs::write(&temp, &body).unwrap();
+
+        fetcher(fast_policy(1))
+            .fetch(&url, &temp, |_| {})
+            .await
+            .expect("416 with matching total means the temp file is complete");
+        assert_eq!(std::fs::read(&temp).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_server_errors() {
+        let body = test_body(2_000);
+        let (url, seen) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            fail_with: vec![503, 500],
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+
+        fetcher(fast_policy(5))
+            .fetch(&url, &temp, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&temp).unwrap(), body);
+        assert_eq!(seen.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_not_found_is_not_retried() {
+        let (url, seen) = spawn_stand_in(StandIn {
+            body: test_body(100),
+            fail_with: vec![404, 404, 404],
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+
+        let result = fetcher(fast_policy(3)).fetch(&url, &temp, |_| {}).await;
+        assert!(result.unwrap_err().contains("404"));
+        assert_eq!(seen.lock().unwrap().len(), 1, "4xx should fail immediately");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gives_up_after_max_attempts() {
+        let (url, seen) = spawn_stand_in(StandIn {
+            body: test_body(100),
+            fail_with: vec![503; 10],
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+
+        let mut retries = 0;
+        let result = fetcher(fast_policy(3))
+            .fetch(&url, &temp, |e| {
+                if matches!(e, TransferEvent::Retrying { .. }) {
+                    retries += 1;
+                }
+            })
+            .await;
+        assert!(result.unwrap_err().contains("gave up after 3 attempts"));
+        assert_eq!(seen.lock().unwrap().len(), 3);
+        assert_eq!(retries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cancel_during_backoff() {
+        let (url, _) = spawn_stand_in(StandIn {
+            body: test_body(100),
+            fail_with: vec![503; 10],
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let temp = dir.path().join("ggml-test.bin.tmp");
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(30),
+        };
+
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = Fetcher {
+            client: Client::new(),
+            policy,
+            token,
+        }
+        .fetch(&url, &temp, |_| {})
+        .await;
+        assert_eq!(result, Err(DOWNLOAD_CANCELLED.to_string()));
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "no full backoff"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_single_file_rejects_checksum_mismatch() {
+        let body = test_body(3_000);
+        let (url, _) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("custom-domain.bin");
+
+        let wrong = "0".repeat(64);
+        let result = download_single_file(
+            &fetcher(fast_policy(1)),
+            "domain",
+            &dest,
+            &url,
+            0,
+            Some(&wrong),
+            |_| {},
+        )
+        .await;
+        assert!(result.unwrap_err().contains("Checksum mismatch"));
+        assert!(!dest.exists());
+        assert!(
+            !dest.with_extension("bin.tmp").exists(),
+            "corrupt temp file must not be kept for resume"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_single_file_cancelled_before_rename() {
+        let body = test_body(2_000);
+        let (url, _) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("custom-domain.bin");
+        // A complete temp file: the fetch succeeds without checking the token
+        // again, so the cancel from its progress event is seen before the rename.
+        std::fs::write(dest.with_extension("bin.tmp"), &body).unwrap();
+
+        let fetcher = fetcher(fast_policy(1));
+        let token = fetcher.token.clone();
+        let result =
+            download_single_file(&fetcher, "domain", &dest, &url, 0, None, |_| token.cancel())
+                .await;
+        assert_eq!(result, Err(DOWNLOAD_CANCELLED.to_string()));
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_single_file_without_size_uses_server_size() {
+        let body = test_body(4_000);
+        let (url, _) = spawn_stand_in(StandIn {
+            body: body.clone(),
+            ..Default::default()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("custom-domain.bin");
+
+        let mut events = Vec::new();
+        download_single_file(
+            &fetcher(fast_policy(1)),
+            "domain",
+            &dest,
+            &url,
+            0,
+            None,
+            |p| events.push(p),
+        )
+        .await
+        .unwrap();
+        let last = events.last().unwrap();
+        assert_eq!(last.total_bytes, body.len() as u64);
+        assert!((last.percent - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_progress_payload_reports_retry_status() {
+        let p = progress_payload(
+            "tiny",
+            50,
+            200,
+            &TransferEvent::Retrying {
+                attempt: 2,
+                delay: Duration::from_millis(1500),
+            },
+        );
+        assert_eq!(p.status, DownloadStatus::Retrying);
+        assert_eq!(p.attempt, 2);
+        assert_eq!(p.retry_in_ms, 1500);
+        assert!((p.percent - 25.0).abs() < 1e-9);
+
+        let json = serde_json::to_value(&p).unwrap();
+        assert_eq!(json["status"], "retrying");
+        assert_eq!(json["model_id"], "tiny");
+    }
+
     // --- Path Construction Tests ---
 
     #[test]
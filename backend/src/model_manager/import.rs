@@ -0,0 +1,345 @@
+//! Import model files that already exist on disk (copied from another machine,
+//! fetched by hand, ...) into `models_dir()` so they behave like downloaded
+//! models.
+
+use super::custom::{is_ggml_file, verify_sha256};
+use crate::transcription::models::{ModelType, WhisperModel};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Copy the files into the models directory.
+    #[default]
+    Copy,
+    /// Symlink to the source instead of copying (saves space, source must stay).
+    Link,
+}
+
+/// Work out which registry model `source` is: a GGML file is matched by
+/// filename (`ggml-base.bin` → `base`), a Moonshine directory by its name.
+/// `model_id` skips the guess, but the source must still match that model.
+pub fn identify_model<'a>(
+    source: &Path,
+    model_id: Option<&str>,
+    models: &'a [WhisperModel],
+) -> Result<&'a WhisperModel, String> {
+    let model = match model_id {
+        Some(id) => models
+            .iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| format!("Unknown model: {}", id))?,
+        None => {
+            let name = source
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            models
+                .iter()
+                .find(|m| !m.custom && m.filename == name)
+                .ok_or_else(|| {
+                    format!(
+                        "Cannot tell which model {} is; pass a model ID \
+                         (or register it as a custom model)",
+                        source.display()
+                    )
+                })?
+        }
+    };
+    if model.custom {
+        return Err(format!(
+            "{} is a custom model; register its file with add_custom_model instead",
+            model.id
+        ));
+    }
+    validate_source(source, model)?;
+    Ok(model)
+}
+
+/// Check that `source` has the shape `model` expects.
+pub fn validate_source(source: &Path, model: &WhisperModel) -> Result<(), String> {
+    match model.model_type {
+        ModelType::WhisperGgml => {
+            if !source.is_file() {
+                return Err(format!("Not a file: {}", source.display()));
+            }
+            if !is_ggml_file(source) {
+                return Err(format!(
+                    "Not a Whisper GGML model (bad magic): {}",
+                    source.display()
+                ));
+            }
+            if let Some(expected) = &model.sha256 {
+                verify_sha256(source, expected)?;
+            }
+        }
+        ModelType::MoonshineOnnx => {
+            if !source.is_dir() {
+                return Err(format!(
+                    "{} needs a directory containing {}",
+                    model.id,
+                    file_list(model)
+                ));
+            }
+            for (fname, _) in &model.files {
+                let file = source.join(fname);
+                if !file.is_file() || std::fs::metadata(&file).map_or(0, |m| m.len()) == 0 {
+                    return Err(format!(
+                        "{} is missing {} (expected {})",
+                        source.display(),
+                        fname,
+                        file_list(model)
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn file_list(model: &WhisperModel) -> String {
+    model
+        .files
+        .iter()
+        .map(|(f, _)| f.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Copy or link an already validated `source` into `models_dir` under the
+/// model's registry filename. Refuses to overwrite an installed model.
+pub fn install(
+    source: &Path,
+    model: &WhisperModel,
+    models_dir: &Path,
+    mode: ImportMode,
+) -> Result<PathBuf, String> {
+    let dest = models_dir.join(&model.filename);
+    if dest.exists() || dest.is_symlink() {
+        return Err(format!(
+            "Model {} is already installed at {}",
+            model.id,
+            dest.display()
+        ));
+    }
+    std::fs::create_dir_all(models_dir)
+        .map_err(|e| format!("Failed to create {}: {}", models_dir.display(), e))?;
+
+    match mode {
+        ImportMode::Link => {
+            let source = source
+                .canonicalize()
+                .map_err(|e| format!("Failed to resolve {}: {}", source.display(), e))?;
+            link(&source, &dest)?;
+        }
+        ImportMode::Copy if model.files.is_empty() => copy_atomic(source, &dest)?,
+        ImportMode::Copy => {
+            // Stage into a temp directory so a failed copy never looks installed.
+            let staging = dest.with_extension("import.tmp");
+            let _ = std::fs::remove_dir_all(&staging);
+            std::fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+            for (fname, _) in &model.files {
+                if let Err(e) = std::fs::copy(source.join(fname), staging.join(fname)) {
+                    let _ = std::fs::remove_dir_all(&staging);
+                    return Err(format!("Failed to copy {}: {}", fname, e));
+                }
+            }
+            std::fs::rename(&staging, &dest)
+                .map_err(|e| format!("Failed to finalize import: {}", e))?;
+        }
+    }
+    Ok(dest)
+}
+
+/// Validate and install `source` into `models_dir`. Returns the model ID.
+/// Blocking — large models should go through `spawn_blocking`.
+pub fn import_model(
+    source: &Path,
+    model_id: Option<&str>,
+    mode: ImportMode,
+    models: &[WhisperModel],
+    models_dir: &Path,
+) -> Result<String, String> {
+    let model = identify_model(source, model_id, models)?;
+    install(source, model, models_dir, mode)?;
+    Ok(model.id.clone())
+}
+
+fn copy_atomic(source: &Path, dest: &Path) -> Result<(), String> {
+    let temp = dest.with_extension("bin.tmp");
+    if let Err(e) = std::fs::copy(source, &temp) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!("Failed to copy {}: {}", source.display(), e));
+    }
+    std::fs::rename(&temp, dest).map_err(|e| format!("Failed to finalize import: {}", e))
+}
+
+#[cfg(unix)]
+fn link(source: &Path, dest: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(source, dest)
+        .map_err(|e| format!("Failed to link {}: {}", dest.display(), e))
+}
+
+#[cfg(not(unix))]
+fn link(_source: &Path, _dest: &Path) -> Result<(), String> {
+    Err("Linking models is only supported on Unix; use copy instead".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::models::get_model_registry;
+
+    fn write_ggml(path: &Path) {
+        let mut data = b"lmgg".to_vec();
+        data.extend_from_slice(&[7u8; 128]);
+        std::fs::write(path, data).unwrap();
+    }
+
+    fn write_moonshine(dir: &Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        for f in [
+            "encoder_model.onnx",
+            "decoder_model_merged.onnx",
+            "tokenizer.json",
+        ] {
+            std::fs::write(dir.join(f), b"onnx").unwrap();
+        }
+    }
+
+    // --- identify_model Tests ---
+
+    #[test]
+    fn test_identify_ggml_by_filename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-base.bin");
+        write_ggml(&path);
+        let model = identify_model(&path, None, get_model_registry()).unwrap();
+        assert_eq!(model.id, "base");
+    }
+
+    #[test]
+    fn test_identify_with_explicit_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("whisper-small-q5.bin");
+        write_ggml(&path);
+        assert!(identify_model(&path, None, get_model_registry()).is_err());
+        let model = identify_model(&path, Some("small"), get_model_registry()).unwrap();
+        assert_eq!(model.id, "small");
+    }
+
+    #[test]
+    fn test_identify_rejects_non_ggml_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-tiny.bin");
+        std::fs::write(&path, b"<html>404</html>").unwrap();
+        let err = identify_model(&path, None, get_model_registry()).unwrap_err();
+        assert!(err.contains("bad magic"));
+    }
+
+    #[test]
+    fn test_identify_moonshine_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("moonshine-tiny");
+        write_moonshine(&src);
+        let model = identify_model(&src, None, get_model_registry()).unwrap();
+        assert_eq!(model.id, "moonshine-tiny");
+
+        std::fs::remove_file(src.join("tokenizer.json")).unwrap();
+        let err = identify_model(&src, None, get_model_registry()).unwrap_err();
+        assert!(err.contains("tokenizer.json"));
+    }
+
+    // --- install Tests ---
+
+    #[test]
+    fn test_import_copies_file() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let models_dir = tempfile::tempdir().unwrap();
+        let path = src_dir.path().join("ggml-tiny.bin");
+        write_ggml(&path);
+
+        let id = import_model(
+            &path,
+            None,
+            ImportMode::Copy,
+            get_model_registry(),
+            models_dir.path(),
+        )
+        .unwrap();
+        assert_eq!(id, "tiny");
+        let dest = models_dir.path().join("ggml-tiny.bin");
+        assert_eq!(std::fs::read(&dest).unwrap(), std::fs::read(&path).unwrap());
+        assert!(!models_dir.path().join("ggml-tiny.bin.tmp").exists());
+    }
+
+    #[test]
+    fn test_import_refuses_to_overwrite() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let models_dir = tempfile::tempdir().unwrap();
+        let path = src_dir.path().join("ggml-tiny.bin");
+        write_ggml(&path);
+        std::fs::write(models_dir.path().join("ggml-tiny.bin"), b"existing").unwrap();
+
+        let err = import_model(
+            &path,
+            None,
+            ImportMode::Copy,
+            get_model_registry(),
+            models_dir.path(),
+        )
+        .unwrap_err();
+        assert!(err.contains("already installed"));
+        assert_eq!(
+            std::fs::read(models_dir.path().join("ggml-tiny.bin")).unwrap(),
+            b"existing"
+        );
+    }
+
+    #[test]
+    fn test_import_copies_moonshine_directory() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let models_dir = tempfile::tempdir().unwrap();
+        let src = src_dir.path().join("exported");
+        write_moonshine(&src);
+
+        let id = import_model(
+            &src,
+            Some("moonshine-base"),
+            ImportMode::Copy,
+            get_model_registry(),
+            models_dir.path(),
+        )
+        .unwrap();
+        assert_eq!(id, "moonshine-base");
+        let dest = models_dir.path().join("moonshine-base");
+        assert!(dest.join("encoder_model.onnx").is_file());
+        assert!(dest.join("tokenizer.json").is_file());
+        assert!(!models_dir.path().join("moonshine-base.import.tmp").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_import_links_file() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let models_dir = tempfile::tempdir().unwrap();
+        let path = src_dir.path().join("ggml-tiny.bin");
+        write_ggml(&path);
+
+        import_model(
+            &path,
+            None,
+            ImportMode::Link,
+            get_model_registry(),
+            models_dir.path(),
+        )
+        .unwrap();
+        let dest = models_dir.path().join("ggml-tiny.bin");
+        assert!(dest.is_symlink());
+        assert_eq!(
+            std::fs::read_link(&dest).unwrap(),
+            path.canonicalize().unwrap()
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Download coordination: one in-flight download per model (concurrent requests
+//! for the same model share its result), a cap on how many models download at
+//! once, and per-model cancellation tokens.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::{watch, Notify};
+use tokio_util::sync::CancellationToken;
+
+/// Error string returned by downloads that were stopped via `cancel()`.
+pub const DOWNLOAD_CANCELLED: &str = "Download cancelled";
+
+type DownloadResult = Result<PathBuf, String>;
+
+struct InFlight {
+    token: CancellationToken,
+    result: watch::Receiver<Option<DownloadResult>>,
+}
+
+struct ManagerState {
+    in_flight: HashMap<String, InFlight>,
+    active: usize,
+    max_concurrent: usize,
+}
+
+pub struct DownloadManager {
+    state: Mutex<ManagerState>,
+    slot_freed: Notify,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self::new(crate::config::settings::default_max_concurrent_downloads())
+    }
+}
+
+impl DownloadManager {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Mutex::new(ManagerState {
+                in_flight: HashMap::new(),
+                active: 0,
+                max_concurrent: max_concurrent.max(1),
+            }),
+            slot_freed: Notify::new(),
+        }
+    }
+
+    /// Change the parallelism limit. Running downloads are not interrupted; the new
+    /// limit applies to downloads waiting for a slot.
+    pub fn set_max_concurrent(&self, max_concurrent: usize) {
+        self.state.lock().unwrap().max_concurrent = max_concurrent.max(1);
+        self.slot_freed.notify_waiters();
+    }
+
+    /// Run `start` for `model_id` once a download slot is free. If a download for
+    /// the same model is already queued or running, wait for it and return its
+    /// result instead of starting a second transfer into the same temp file.
+    pub async fn run<F, Fut>(&self, model_id: &str, start: F) -> DownloadResult
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = DownloadResult>,
+    {
+        let entry = {
+            let mut state = self.state.lock().unwrap();
+            match state.in_flight.get(model_id) {
+                Some(existing) => Err(existing.result.clone()),
+                None => {
+                    let token = CancellationToken::new();
+                    let (tx, rx) = watch::channel(None);
+                    state.in_flight.insert(
+                        model_id.to_string(),
+                        InFlight {
+                            token: token.clone(),
+                            result: rx,
+                        },
+                    );
+                    Ok((token, tx))
+                }
+            }
+        };
+        let (token, tx) = match entry {
+            Ok(new) => new,
+            Err(existing) => return Self::wait_for(existing).await,
+        };
+
+        // Removes the in-flight entry even if this future is dropped mid-download.
+        let _entry = InFlightGuard {
+            manager: self,
+            model_id,
+        };
+
+        let result = match self.acquire_slot(&token).await {
+            Some(_slot) => start(token.clone()).await,
+            None => Err(DOWNLOAD_CANCELLED.to_string()),
+        };
+
+        tx.send_replace(Some(result.clone()));
+        result
+    }
+
+    /// Cancel a queued or running download. Returns `false` if none was in flight.
+    pub fn cancel(&self, model_id: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        match state.in_flight.get(model_id) {
+            Some(entry) => {
+                entry.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_downloading(&self, model_id: &str) -> bool {
+        self.state.lock().unwrap().in_flight.contains_key(model_id)
+    }
+
+    /// Wait until fewer than `max_concurrent` downloads are running. Returns `None`
+    /// if the download is cancelled while still queued.
+    async fn acquire_slot(&self, token: &CancellationToken) -> Option<Slot<'_>> {
+        loop {
+            // Register for wakeups before checking, so a release between the
+            // check and the await is not missed.
+            let notified = self.slot_freed.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.active < state.max_concurrent {
+                    state.active += 1;
+                    return Some(Slot { manager: self });
+                }
+            }
+            tokio::select! {
+                _ = notified => {}
+                _ = token.cancelled() => return None,
+            }
+        }
+    }
+
+    async fn wait_for(mut rx: watch::Receiver<Option<DownloadResult>>) -> DownloadResult {
+        match rx.wait_for(|r| r.is_some()).await {
+            Ok(result) => result
+                .clone()
+                .unwrap_or_else(|| Err(DOWNLOAD_CANCELLED.into())),
+            Err(_) => Err(DOWNLOAD_CANCELLED.to_string()),
+        }
+    }
+}
+
+/// A running-download slot, released on drop.
+struct Slot<'a> {
+    manager: &'a DownloadManager,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.manager.state.lock().unwrap().active -= 1;
+        self.manager.slot_freed.notify_waiters();
+    }
+}
+
+struct InFlightGuard<'a> {
+    manager: &'a DownloadManager,
+    model_id: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.manager
+            .state
+            .lock()
+            .unwrap()
+            .in_flight
+            .remove(self.model_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_returns_result_of_start() {
+        let manager = DownloadManager::new(2);
+        let result = manager
+            .run("tiny", |_| async {
+                Ok(PathBuf::from("/models/ggml-tiny.bin"))
+            })
+            .await;
+        assert_eq!(result, Ok(PathBuf::from("/models/ggml-tiny.bin")));
+        assert!(!manager.is_downloading("tiny"), "entry removed when done");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_for_same_model_are_deduplicated() {
+        let manager = Arc::new(DownloadManager::new(2));
+        let starts = Arc::new(AtomicUsize::new(0));
+
+        let spawn = |manager: Arc<DownloadManager>, starts: Arc<AtomicUsize>| {
+            tokio::spawn(async move {
+                manager
+                    .run("base", |_| async move {
+                        starts.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok(PathBuf::from("ggml-base.bin"))
+                    })
+                    .await
+            })
+        };
+
+        let a = spawn(manager.clone(), starts.clone());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let b = spawn(manager.clone(), starts.clone());
+
+        assert_eq!(a.await.unwrap(), Ok(PathBuf::from("ggml-base.bin")));
+        assert_eq!(b.await.unwrap(), Ok(PathBuf::from("ggml-base.bin")));
+        assert_eq!(starts.load(Ordering::SeqCst), 1, "only one transfer runs");
+    }
+
+    #[tokio::test]
+    async fn test_parallelism_limit_is_respected() {
+        let manager = Arc::new(DownloadManager::new(2));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let mut handles = Vec::new();
+        for id in ["tiny", "base", "small", "medium", "large-v3"] {
+            let (manager, running, peak) = (manager.clone(), running.clone(), peak.clone());
+            handles.push(tokio::spawn(async move {
+                manager
+                    .run(id, |_| async move {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(PathBuf::from(id))
+                    })
+                    .await
+            }));
+        }
+        for h in handles {
+            assert!(h.await.unwrap().is_ok());
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_download() {
+        let manager = Arc::new(DownloadManager::new(1));
+        let m = manager.clone();
+        let handle = tokio::spawn(async move {
+            m.run("medium", |token| async move {
+                token.cancelled().await;
+                Err(DOWNLOAD_CANCELLED.to_string())
+            })
+            .await
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(manager.cancel("medium"));
+        assert_eq!(handle.await.unwrap(), Err(DOWNLOAD_CANCELLED.to_string()));
+        assert!(!manager.is_downloading("medium"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_download_never_starts() {
+        let manager = Arc::new(DownloadManager::new(1));
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let m = manager.clone();
+        let blocker = tokio::spawn(async move {
+            m.run("large-v3", |token| async move {
+                token.cancelled().await;
+                Err(DOWNLOAD_CANCELLED.to_string())
+            })
+            .await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let (m, s) = (manager.clone(), started.clone());
+        let queued = tokio::spawn(async move {
+            m.run("tiny", |_| async move {
+                s.fetch_add(1, Ordering::SeqCst);
+                Ok(PathBuf::from("tiny"))
+            })
+            .await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(manager.cancel("tiny"));
+        assert_eq!(queued.await.unwrap(), Err(DOWNLOAD_CANCELLED.to_string()));
+        assert_eq!(started.load(Ordering::SeqCst), 0);
+
+        manager.cancel("large-v3");
+        blocker.await.unwrap().ok();
+    }
+
+    #[test]
+    fn test_cancel_unknown_model_returns_false() {
+        let manager = DownloadManager::default();
+        assert!(!manager.cancel("nonexistent"));
+    }
+
+    #[tokio::test]
+    async fn test_raising_limit_releases_queued_downloads() {
+        let manager = Arc::new(DownloadManager::new(1));
+        let m = manager.clone();
+        let blocker = tokio::spawn(async move {
+            m.run("large-v3", |token| async move {
+                token.cancelled().await;
+                Err(DOWNLOAD_CANCELLED.to_string())
+            })
+            .await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let m = manager.clone();
+        let queued =
+            tokio::spawn(
+                async move { m.run("tiny", |_| async { Ok(PathBuf::from("tiny")) }).await },
+            );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!queued.is_finished(), "second download waits for a slot");
+
+        manager.set_max_concurrent(2);
+        assert_eq!(queued.await.unwrap(), Ok(PathBuf::from("tiny")));
+
+        manager.cancel("large-v3");
+        blocker.await.unwrap().ok();
+    }
+}
@@ -1,2 +1,24 @@
+pub mod bundle;
+pub mod catalog;
+pub mod custom;
 pub mod download;
+pub mod import;
+pub mod manager;
+pub mod storage;
+pub mod sync;
 pub use download::{delete_model, download_model, is_model_downloaded};
+pub use manager::{DownloadManager, DOWNLOAD_CANCELLED};
+
+use crate::config::Config;
+use crate::transcription::models::{set_custom_models, set_mirror};
+
+/// Apply the registry-related settings (models directory, custom models,
+/// mirror, cached catalog). The network catalog refresh is left to the caller.
+pub fn init_registry(config: &Config) {
+    Config::set_models_dir(config.models_location.clone());
+    set_custom_models(&config.custom_models);
+    set_mirror(config.model_mirror.clone());
+    if config.model_catalog_url.is_some() {
+        catalog::load_cached_catalog();
+    }
+}
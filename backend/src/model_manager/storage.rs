@@ -0,0 +1,382 @@
+//! Disk usage of the models directory: per-model sizes, leftovers from
+//! interrupted downloads/imports, and files that belong to no known model.
+//! Also moves the whole directory when the user picks a new location.
+
+use crate::transcription::models::WhisperModel;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUsage {
+    pub id: String,
+    pub display_name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// False for a Moonshine directory missing some of its files.
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageEntry {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Model the leftover belongs to, when it can be told from the name.
+    pub model_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageReport {
+    pub models_dir: PathBuf,
+    pub models: Vec<ModelUsage>,
+    /// `.tmp` files and staging directories left by partial downloads/imports.
+    pub orphans: Vec<StorageEntry>,
+    /// Anything else in the models directory.
+    pub unknown: Vec<StorageEntry>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupResult {
+    pub removed: Vec<PathBuf>,
+    pub freed_bytes: u64,
+}
+
+/// Walk `models_dir` and classify every entry against `models`.
+pub fn scan(models_dir: &Path, models: &[WhisperModel]) -> Result<StorageReport, String> {
+    let mut report = StorageReport {
+        models_dir: models_dir.to_path_buf(),
+        models: Vec::new(),
+        orphans: Vec::new(),
+        unknown: Vec::new(),
+        total_bytes: 0,
+    };
+    if !models_dir.exists() {
+        return Ok(report);
+    }
+
+    let entries = std::fs::read_dir(models_dir)
+        .map_err(|e| format!("Failed to read {}: {}", models_dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let size_bytes = disk_size(&path);
+        report.total_bytes += size_bytes;
+
+        if let Some(stem) = name.strip_suffix(".tmp") {
+            // ggml-base.bin.tmp (download) or moonshine-tiny.import.tmp (import staging)
+            let target = stem.strip_suffix(".import").unwrap_or(stem);
+            report.orphans.push(StorageEntry {
+                path,
+                size_bytes,
+                model_id: owner(models, target),
+            });
+            continue;
+        }
+
+        let Some(model) = models.iter().find(|m| m.filename == name) else {
+            report.unknown.push(StorageEntry {
+                path,
+                size_bytes,
+                model_id: None,
+            });
+            continue;
+        };
+
+        let mut model_bytes = size_bytes;
+        let mut complete = true;
+        if !model.files.is_empty() {
+            // Partial multi-file downloads leave `<file>.tmp` inside the model directory.
+            for inner in std::fs::read_dir(&path).into_iter().flatten().flatten() {
+                let inner_path = inner.path();
+                if inner_path.extension().is_some_and(|e| e == "tmp") {
+                    let inner_size = disk_size(&inner_path);
+                    model_bytes -= inner_size;
+                    report.orphans.push(StorageEntry {
+                        path: inner_path,
+                        size_bytes: inner_size,
+                        model_id: Some(model.id.clone()),
+                    });
+                }
+            }
+            complete = model.files.iter().all(|(f, _)| path.join(f).is_file());
+        }
+        report.models.push(ModelUsage {
+            id: model.id.clone(),
+            display_name: model.display_name.clone(),
+            path,
+            size_bytes: model_bytes,
+            complete,
+        });
+    }
+
+    report.models.sort_by(|a, b| a.id.cmp(&b.id));
+    report.orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    report.unknown.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(report)
+}
+
+fn owner(models: &[WhisperModel], filename: &str) -> Option<String> {
+    models
+        .iter()
+        .find(|m| m.filename == filename)
+        .map(|m| m.id.clone())
+}
+
+/// Size of a file, or the recursive size of a directory. Symlinks (linked
+/// imports) count as zero since their target lives elsewhere.
+pub fn disk_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if meta.is_dir() {
+        std::fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| disk_size(&e.path()))
+            .sum()
+    } else if meta.is_file() {
+        meta.len()
+    } else {
+        0
+    }
+}
+
+/// Delete the orphans in `report`, skipping those belonging to a model for
+/// which `in_use` returns true (an in-flight download still writing its `.tmp`).
+pub fn remove_orphans(report: &StorageReport, in_use: impl Fn(&str) -> bool) -> CleanupResult {
+    let mut result = CleanupResult::default();
+    for orphan in &report.orphans {
+        if orphan.model_id.as_deref().is_some_and(&in_use) {
+            continue;
+        }
+        let removed = if orphan.path.is_dir() {
+            std::fs::remove_dir_all(&orphan.path)
+        } else {
+            std::fs::remove_file(&orphan.path)
+        };
+        if removed.is_ok() {
+            result.freed_bytes += orphan.size_bytes;
+            result.removed.push(orphan.path.clone());
+        }
+    }
+    result
+}
+
+/// Move every entry of `from` into `to` (renaming where possible, copying
+/// across filesystems). Fails before moving anything if `to` already holds an
+/// entry with the same name.
+pub fn migrate_models_dir(from: &Path, to: &Path) -> Result<(), String> {
+    if to.starts_with(from) || from.starts_with(to) {
+        return Err(format!(
+            "{} and {} must not contain each other",
+            from.display(),
+            to.display()
+        ));
+    }
+    std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    if !from.exists() {
+        return Ok(());
+    }
+
+    let entries: Vec<PathBuf> = std::fs::read_dir(from)
+        .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    for path in &entries {
+        let target = to.join(path.file_name().unwrap_or_default());
+        if target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+    }
+    for path in &entries {
+        let target = to.join(path.file_name().unwrap_or_default());
+        move_entry(path, &target)?;
+    }
+    let _ = std::fs::remove_dir(from);
+    Ok(())
+}
+
+fn move_entry(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // Different filesystem: copy, then delete the original.
+    copy_recursive(from, to).map_err(|e| {
+        format!(
+            "Failed to move {} to {}: {}",
+            from.display(),
+            to.display(),
+            e
+        )
+    })?;
+    if from.is_dir() {
+        std::fs::remove_dir_all(from)
+    } else {
+        std::fs::remove_file(from)
+    }
+    .map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(from)?;
+    if meta.is_symlink() {
+        copy_symlink(from, to)
+    } else if meta.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::copy(from, to).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::models::get_model_registry;
+
+    fn populate(dir: &Path) {
+        std::fs::write(dir.join("ggml-tiny.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.join("ggml-base.bin.tmp"), vec![0u8; 40]).unwrap();
+        std::fs::write(dir.join("notes.txt"), vec![0u8; 5]).unwrap();
+
+        let moonshine = dir.join("moonshine-tiny");
+        std::fs::create_dir_all(&moonshine).unwrap();
+        std::fs::write(moonshine.join("encoder_model.onnx"), vec![0u8; 30]).unwrap();
+        std::fs::write(moonshine.join("decoder_model_merged.tmp"), vec![0u8; 20]).unwrap();
+    }
+
+    // --- scan Tests ---
+
+    #[test]
+    fn test_scan_classifies_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        populate(dir.path());
+        let report = scan(dir.path(), get_model_registry()).unwrap();
+
+        let ids: Vec<&str> = report.models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["moonshine-tiny", "tiny"]);
+        let tiny = report.models.iter().find(|m| m.id == "tiny").unwrap();
+        assert_eq!(tiny.size_bytes, 100);
+        assert!(tiny.complete);
+        let moonshine = &report.models[0];
+        assert_eq!(moonshine.size_bytes, 30, "inner .tmp not counted as model");
+        assert!(!moonshine.complete);
+
+        assert_eq!(report.orphans.len(), 2);
+        assert!(report
+            .orphans
+            .iter()
+            .any(|o| o.model_id.as_deref() == Some("base") && o.size_bytes == 40));
+        assert!(report
+            .orphans
+            .iter()
+            .any(|o| o.model_id.as_deref() == Some("moonshine-tiny")));
+
+        assert_eq!(report.unknown.len(), 1);
+        assert!(report.unknown[0].path.ends_with("notes.txt"));
+        assert_eq!(report.total_bytes, 195);
+    }
+
+    #[test]
+    fn test_scan_missing_dir_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = scan(&dir.path().join("nope"), get_model_registry()).unwrap();
+        assert!(report.models.is_empty());
+        assert_eq!(report.total_bytes, 0);
+    }
+
+    #[test]
+    fn test_scan_recognizes_import_staging() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("moonshine-base.import.tmp")).unwrap();
+        let report = scan(dir.path(), get_model_registry()).unwrap();
+        assert_eq!(report.orphans.len(), 1);
+        assert_eq!(
+            report.orphans[0].model_id.as_deref(),
+            Some("moonshine-base")
+        );
+    }
+
+    // --- Cleanup Tests ---
+
+    #[test]
+    fn test_remove_orphans_skips_in_flight_downloads() {
+        let dir = tempfile::tempdir().unwrap();
+        populate(dir.path());
+        let report = scan(dir.path(), get_model_registry()).unwrap();
+
+        let result = remove_orphans(&report, |id| id == "base");
+        assert_eq!(result.removed.len(), 1);
+        assert_eq!(result.freed_bytes, 20);
+        assert!(dir.path().join("ggml-base.bin.tmp").exists());
+        assert!(!dir
+            .path()
+            .join("moonshine-tiny/decoder_model_merged.tmp")
+            .exists());
+        assert!(dir.path().join("notes.txt").exists(), "unknown files kept");
+        assert!(dir.path().join("ggml-tiny.bin").exists());
+    }
+
+    // --- Migration Tests ---
+
+    #[test]
+    fn test_migrate_moves_everything() {
+        let root = tempfile::tempdir().unwrap();
+        let from = root.path().join("old");
+        let to = root.path().join("big-drive/models");
+        std::fs::create_dir_all(&from).unwrap();
+        populate(&from);
+
+        migrate_models_dir(&from, &to).unwrap();
+        assert!(to.join("ggml-tiny.bin").is_file());
+        assert!(to.join("moonshine-tiny/encoder_model.onnx").is_file());
+        assert!(!from.exists());
+    }
+
+    #[test]
+    fn test_migrate_refuses_conflicts_and_nesting() {
+        let root = tempfile::tempdir().unwrap();
+        let from = root.path().join("old");
+        let to = root.path().join("new");
+        std::fs::create_dir_all(&from).unwrap();
+        std::fs::create_dir_all(&to).unwrap();
+        populate(&from);
+        std::fs::write(to.join("ggml-tiny.bin"), b"other").unwrap();
+
+        assert!(migrate_models_dir(&from, &to)
+            .unwrap_err()
+            .contains("already exists"));
+        assert!(from.join("ggml-tiny.bin").exists(), "nothing moved");
+
+        assert!(migrate_models_dir(&from, &from.join("sub")).is_err());
+    }
+
+    #[test]
+    fn test_copy_recursive_copies_tree() {
+        let root = tempfile::tempdir().unwrap();
+        let src = root.path().join("src");
+        std::fs::create_dir_all(src.join("inner")).unwrap();
+        std::fs::write(src.join("inner/file.bin"), b"data").unwrap();
+        copy_recursive(&src, &root.path().join("dst")).unwrap();
+        assert_eq!(
+            std::fs::read(root.path().join("dst/inner/file.bin")).unwrap(),
+            b"data"
+        );
+    }
+}
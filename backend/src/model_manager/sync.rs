@@ -0,0 +1,188 @@
+//! Keeps `Config::downloaded_models` in step with the models directory: a
+//! reconciliation pass (run at startup) and a filesystem watcher that re-runs
+//! it when files are added or removed by hand.
+
+use super::download::is_model_downloaded;
+use crate::config::Config;
+use crate::transcription::models::all_models;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Quiet period before a burst of filesystem events triggers a reconcile.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Payload of the `models-changed` event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModelsChanged {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ModelsChanged {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compute the new downloaded list from what is on disk. Keeps the order of
+/// `recorded` and appends newly found models in `on_disk` order.
+pub fn diff_downloaded(recorded: &[String], on_disk: &[String]) -> (Vec<String>, ModelsChanged) {
+    let mut list: Vec<String> = recorded
+        .iter()
+        .filter(|id| on_disk.contains(id))
+        .cloned()
+        .collect();
+    let removed = recorded
+        .iter()
+        .filter(|id| !on_disk.contains(id))
+        .cloned()
+        .collect();
+    let added: Vec<String> = on_disk
+        .iter()
+        .filter(|id| !recorded.contains(id))
+        .cloned()
+        .collect();
+    list.extend(added.iter().cloned());
+    (list, ModelsChanged { added, removed })
+}
+
+/// Bring `config.downloaded_models` in line with the filesystem. The caller
+/// saves the config if the result is not empty.
+pub fn reconcile(config: &mut Config) -> ModelsChanged {
+    let on_disk: Vec<String> = all_models()
+        .into_iter()
+        .filter(|m| is_model_downloaded(&m.id))
+        .map(|m| m.id)
+        .collect();
+    let (list, changes) = diff_downloaded(&config.downloaded_models, &on_disk);
+    config.downloaded_models = list;
+    changes
+}
+
+/// Watches the models directory; dropping it stops the watch.
+pub struct ModelsWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Call `on_change` (debounced) whenever something in `dir` changes. Events
+/// that only touch `.tmp` files are ignored so a running download does not
+/// trigger a reconcile on every chunk.
+pub fn watch(dir: &Path, on_change: impl Fn() + Send + 'static) -> Result<ModelsWatcher, String> {
+    let (tx, rx) = mpsc::channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            if is_relevant(&event) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create models watcher: {}", e))?;
+    watcher
+        .watch(dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            loop {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(()) => continue,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            on_change();
+        }
+    });
+
+    Ok(ModelsWatcher { _watcher: watcher })
+}
+
+fn is_relevant(event: &Event) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.is_empty()
+        || event
+            .paths
+            .iter()
+            .any(|p| p.extension().is_none_or(|ext| ext != "tmp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn ids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    // --- diff_downloaded Tests ---
+
+    #[test]
+    fn test_diff_no_change() {
+        let (list, changes) = diff_downloaded(&ids(&["tiny", "base"]), &ids(&["base", "tiny"]));
+        assert_eq!(list, ids(&["tiny", "base"]));
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_diff_detects_added_and_removed() {
+        let (list, changes) = diff_downloaded(&ids(&["tiny", "base"]), &ids(&["base", "small"]));
+        assert_eq!(list, ids(&["base", "small"]));
+        assert_eq!(changes.added, ids(&["small"]));
+        assert_eq!(changes.removed, ids(&["tiny"]));
+    }
+
+    #[test]
+    fn test_diff_from_empty_config() {
+        let (list, changes) = diff_downloaded(&[], &ids(&["large-v3"]));
+        assert_eq!(list, ids(&["large-v3"]));
+        assert_eq!(changes.added, ids(&["large-v3"]));
+    }
+
+    // --- Watcher Tests ---
+
+    fn wait_for(counter: &AtomicUsize, at_least: usize) -> bool {
+        for _ in 0..60 {
+            if counter.load(Ordering::SeqCst) >= at_least {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    #[test]
+    fn test_watcher_reports_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let c = calls.clone();
+        let _watcher = watch(dir.path(), move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        std::fs::write(dir.path().join("ggml-tiny.bin"), b"lmgg").unwrap();
+        assert!(wait_for(&calls, 1), "change should be reported");
+    }
+
+    #[test]
+    fn test_watcher_ignores_tmp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let c = calls.clone();
+        let _watcher = watch(dir.path(), move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        std::fs::write(dir.path().join("ggml-base.bin.tmp"), b"partial").unwrap();
+        std::thread::sleep(DEBOUNCE * 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+}
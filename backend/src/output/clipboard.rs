@@ -1,12 +1,229 @@
-use arboard::Clipboard;
+use arboard::{Clipboard, ImageData};
+use std::time::Duration;
+
+/// How long the focused app gets to read the clipboard after the paste
+/// shortcut before the previous contents are put back.
+pub const PASTE_SETTLE: Duration = Duration::from_millis(200);
+
+/// Clipboard access used by the paste sequence, so it can be tested without
+/// a display server.
+pub trait ClipboardAccess {
+    /// `None` when the clipboard is empty or holds something other than text.
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+    /// `None` when the clipboard holds no image.
+    fn get_image(&mut self) -> Option<ImageData<'static>>;
+    fn set_image(&mut self, image: ImageData<'static>) -> Result<(), String>;
+    fn clear(&mut self) -> Result<(), String>;
+}
+
+impl ClipboardAccess for Clipboard {
+    fn get_text(&mut self) -> Option<String> {
+        Clipboard::get_text(self).ok()
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        Clipboard::set_text(self, text.to_string())
+            .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+    }
+
+    fn get_image(&mut self) -> Option<ImageData<'static>> {
+        Clipboard::get_image(self).ok()
+    }
+
+    fn set_image(&mut self, image: ImageData<'static>) -> Result<(), String> {
+        Clipboard::set_image(self, image)
+            .map_err(|e| format!("Failed to restore clipboard image: {}", e))
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        Clipboard::clear(self).map_err(|e| format!("Failed to clear clipboard: {}", e))
+    }
+}
+
+fn open() -> Result<Clipboard, String> {
+    Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))
+}
 
 pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
-    let mut clipboard =
-        Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    ClipboardAccess::set_text(&mut open()?, text)
+}
+
+/// Paste `text` through the system clipboard, keeping what it held before.
+/// `paste` sends the paste shortcut to the focused window.
+pub fn paste_text(text: &str, paste: impl FnOnce() -> Result<(), String>) -> Result<(), String> {
+    // One handle for the whole sequence: on X11 the contents we set are only
+    // served while it is alive.
+    paste_with(&mut open()?, text, paste, PASTE_SETTLE)
+}
+
+/// Clipboard contents saved across a paste.
+enum Saved {
+    Text(String),
+    Image(ImageData<'static>),
+}
+
+/// Save the clipboard, put `text` on it, run `paste`, wait `settle` for the
+/// target to read it, then restore the saved text or image. A clipboard with
+/// neither is cleared afterwards; other formats (rich text, file lists) come
+/// back only as their plain-text version, if the owner offered one.
+pub fn paste_with(
+    clipboard: &mut dyn ClipboardAccess,
+    text: &str,
+    paste: impl FnOnce() -> Result<(), String>,
+    settle: Duration,
+) -> Result<(), String> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    let saved = match clipboard.get_text() {
+        Some(text) => Some(Saved::Text(text)),
+        None => clipboard.get_image().map(Saved::Image),
+    };
+    clipboard.set_text(text)?;
+    let pasted = paste();
+    if pasted.is_ok() {
+        std::thread::sleep(settle);
+    }
+    let restored = match saved {
+        Some(Saved::Text(saved)) => clipboard.set_text(&saved),
+        Some(Saved::Image(image)) => clipboard.set_image(image),
+        None => clipboard.clear(),
+    };
+    pasted.and(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// An in-memory clipboard holding text or an image, logging every change.
+    #[derive(Default)]
+    struct FakeClipboard {
+        contents: Option<String>,
+        image: Option<ImageData<'static>>,
+        history: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl ClipboardAccess for FakeClipboard {
+        fn get_text(&mut self) -> Option<String> {
+            self.contents.clone()
+        }
+
+        fn set_text(&mut self, text: &str) -> Result<(), String> {
+            self.contents = Some(text.to_string());
+            self.image = None;
+            self.history.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+
+        fn get_image(&mut self) -> Option<ImageData<'static>> {
+            self.image.clone()
+        }
+
+        fn set_image(&mut self, image: ImageData<'static>) -> Result<(), String> {
+            self.contents = None;
+            self.image = Some(image);
+            self.history.borrow_mut().push("<image>".to_string());
+            Ok(())
+        }
+
+        fn clear(&mut self) -> Result<(), String> {
+            self.contents = None;
+            self.image = None;
+            self.history.borrow_mut().push("<clear>".to_string());
+            Ok(())
+        }
+    }
+
+    // --- Paste Tests ---
+
+    #[test]
+    fn test_paste_restores_previous_text() {
+        let mut clipboard = FakeClipboard {
+            contents: Some("copied earlier".to_string()),
+            ..Default::default()
+        };
+        let history = clipboard.history.clone();
+        let seen = RefCell::new(None);
+        paste_with(
+            &mut clipboard,
+            "hello world",
+            || {
+                // The shortcut fires while the transcript is on the clipboard.
+                *seen.borrow_mut() = history.borrow().last().cloned();
+                Ok(())
+            },
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert_eq!(seen.into_inner().as_deref(), Some("hello world"));
+        assert_eq!(clipboard.contents.as_deref(), Some("copied earlier"));
+        assert_eq!(*history.borrow(), vec!["hello world", "copied earlier"]);
+    }
+
+    #[test]
+    fn test_paste_failure_still_restores() {
+        let mut clipboard = FakeClipboard {
+            contents: Some("keep me".to_string()),
+            ..Default::default()
+        };
+        let err = paste_with(
+            &mut clipboard,
+            "text",
+            || Err("no keyboard".to_string()),
+            Duration::ZERO,
+        )
+        .unwrap_err();
+        assert_eq!(err, "no keyboard");
+        assert_eq!(clipboard.contents.as_deref(), Some("keep me"));
+    }
+
+    #[test]
+    fn test_paste_restores_previous_image() {
+        let image = ImageData {
+            width: 1,
+            height: 1,
+            bytes: vec![255, 0, 0, 255].into(),
+        };
+        let mut clipboard = FakeClipboard {
+            image: Some(image),
+            ..Default::default()
+        };
+        paste_with(&mut clipboard, "text", || Ok(()), Duration::ZERO).unwrap();
+        assert_eq!(clipboard.contents, None);
+        let restored = clipboard.image.expect("image restored");
+        assert_eq!((restored.width, restored.height), (1, 1));
+        assert_eq!(restored.bytes.as_ref(), &[255, 0, 0, 255]);
+        assert_eq!(*clipboard.history.borrow(), vec!["text", "<image>"]);
+    }
 
-    clipboard
-        .set_text(text.to_string())
-        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    #[test]
+    fn test_paste_on_empty_clipboard_clears_it() {
+        let mut clipboard = FakeClipboard::default();
+        paste_with(&mut clipboard, "text", || Ok(()), Duration::ZERO).unwrap();
+        assert_eq!(clipboard.contents, None);
+        assert_eq!(*clipboard.history.borrow(), vec!["text", "<clear>"]);
+    }
 
-    Ok(())
+    #[test]
+    fn test_paste_empty_text_does_nothing() {
+        let mut clipboard = FakeClipboard::default();
+        let pasted = RefCell::new(false);
+        paste_with(
+            &mut clipboard,
+            "",
+            || {
+                *pasted.borrow_mut() = true;
+                Ok(())
+            },
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(!*pasted.borrow());
+        assert!(clipboard.history.borrow().is_empty());
+    }
 }
@@ -0,0 +1,199 @@
+//! Applies `FormatRules` to confirmed text on its way to the output sinks.
+//! Text arrives in pieces as words are confirmed, so the formatter tracks
+//! where the current utterance starts and ends. Normalization runs first, on
+//! Whisper's text as recognized, before the joiner spaces it.
+
+use super::normalize::{Locale, Normalizer};
+use crate::config::FormatRules;
+
+pub struct Formatter {
+    rules: FormatRules,
+    /// Whether anything was output in the current utterance.
+    in_utterance: bool,
+    /// Set when normalization is on and the locale has rules.
+    normalizer: Option<Normalizer>,
+}
+
+impl Formatter {
+    pub fn new(rules: FormatRules) -> Self {
+        let locale = rules.locale.as_deref().unwrap_or("en");
+        let normalizer = match Locale::from_code(locale) {
+            Some(locale) if rules.normalize => Some(Normalizer::new(locale)),
+            _ => None,
+        };
+        Self {
+            rules,
+            in_utterance: false,
+            normalizer,
+        }
+    }
+
+    /// Normalize a confirmed piece. Words that may continue into the next
+    /// piece are held back, so the result can be empty; see `normalize_rest`.
+    pub fn normalize_piece(&mut self, text: &str) -> String {
+        match &mut self.normalizer {
+            Some(normalizer) => normalizer.piece(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// The words `normalize_piece` held back, at the end of an utterance.
+    pub fn normalize_rest(&mut self) -> String {
+        self.normalizer
+            .as_mut()
+            .map(Normalizer::finish)
+            .unwrap_or_default()
+    }
+
+    /// Normalize a whole utterance at once, for live rewriting.
+    pub fn normalize(&self, text: &str) -> String {
+        match &self.normalizer {
+            Some(normalizer) => normalizer.utterance(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Format a confirmed piece of the current utterance.
+    pub fn piece(&mut self, text: &str) -> String {
+        let first = !self.in_utterance;
+        self.in_utterance |= !text.is_empty();
+        if first && !self.rules.capitalize {
+            lowercase_first_word(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Format a whole utterance at once, for live rewriting; `ended` appends
+    /// the suffix. Does not change the piece-by-piece state.
+    pub fn utterance(&self, text: &str, ended: bool) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        let mut formatted = if self.rules.capitalize {
+            text.to_string()
+        } else {
+            lowercase_first_word(text)
+        };
+        if ended {
+            formatted.push_str(&self.rules.suffix);
+        }
+        formatted
+    }
+
+    /// Finish the utterance; returns the text to output after it.
+    pub fn end_utterance(&mut self) -> String {
+        if std::mem::take(&mut self.in_utterance) {
+            self.rules.suffix.clone()
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Lowercase the first letter of `text`, unless its first word is `I` (or
+/// `I'm`, `I'll`...) or an acronym.
+pub(super) fn lowercase_first_word(text: &str) -> String {
+    let start = text.len() - text.trim_start().len();
+    let word = text[start..].split_whitespace().next().unwrap_or_default();
+    let letters: Vec<char> = word.chars().take_while(|c| c.is_alphabetic()).collect();
+    let keep = letters == ['I'] || (letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()));
+    if keep {
+        return text.to_string();
+    }
+    let mut chars = text[start..].chars();
+    match chars.next() {
+        Some(c) => format!("{}{}{}", &text[..start], c.to_lowercase(), chars.as_str()),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(capitalize: bool, suffix: &str) -> FormatRules {
+        FormatRules {
+            capitalize,
+            suffix: suffix.to_string(),
+            ..FormatRules::default()
+        }
+    }
+
+    // --- Capitalization Tests ---
+
+    #[test]
+    fn test_default_rules_pass_text_through() {
+        let mut f = Formatter::new(FormatRules::default());
+        assert_eq!(f.piece("Hello there"), "Hello there");
+        assert_eq!(f.piece(" General"), " General");
+        assert_eq!(f.end_utterance(), "");
+    }
+
+    #[test]
+    fn test_no_capitalize_lowercases_utterance_start_only() {
+        let mut f = Formatter::new(rules(false, ""));
+        assert_eq!(f.piece("Return the value"), "return the value");
+        assert_eq!(f.piece(" Foo"), " Foo");
+        f.end_utterance();
+        assert_eq!(f.piece("Next one"), "next one");
+    }
+
+    #[test]
+    fn test_no_capitalize_keeps_i_and_acronyms() {
+        assert_eq!(lowercase_first_word("I think so"), "I think so");
+        assert_eq!(lowercase_first_word("I'm here"), "I'm here");
+        assert_eq!(lowercase_first_word("HTTP request"), "HTTP request");
+        assert_eq!(lowercase_first_word("It works"), "it works");
+        assert_eq!(lowercase_first_word(" Émile"), " émile");
+        assert_eq!(lowercase_first_word(""), "");
+    }
+
+    // --- Suffix Tests ---
+
+    #[test]
+    fn test_suffix_follows_each_utterance_with_output() {
+        let mut f = Formatter::new(rules(true, "\n"));
+        f.piece("First line.");
+        assert_eq!(f.end_utterance(), "\n");
+        // Nothing typed: no suffix for a silent utterance.
+        assert_eq!(f.end_utterance(), "");
+        f.piece("Second.");
+        assert_eq!(f.end_utterance(), "\n");
+    }
+
+    #[test]
+    fn test_whole_utterance_formatting() {
+        let f = Formatter::new(rules(false, "\n"));
+        assert_eq!(f.utterance("Tentative words", false), "tentative words");
+        assert_eq!(f.utterance("Final words.", true), "final words.\n");
+        assert_eq!(f.utterance("", true), "");
+    }
+
+    // --- Normalization Tests ---
+
+    #[test]
+    fn test_normalization_is_off_by_default() {
+        let mut f = Formatter::new(FormatRules::default());
+        assert_eq!(f.normalize_piece("twenty three"), "twenty three");
+        assert_eq!(f.normalize_rest(), "");
+    }
+
+    #[test]
+    fn test_normalization_follows_locale() {
+        let normalizing = |locale: &str| {
+            Formatter::new(FormatRules {
+                normalize: true,
+                locale: Some(locale.to_string()),
+                ..FormatRules::default()
+            })
+        };
+        let mut f = normalizing("en-US");
+        assert_eq!(f.normalize_piece("It was twenty"), "It was");
+        assert_eq!(f.normalize_rest(), "20");
+        assert_eq!(f.normalize("ten percent"), "10%");
+        assert_eq!(normalizing("de").normalize("zehn Prozent"), "10 %");
+        // No rules for French: text passes through.
+        assert_eq!(normalizing("fr").normalize("vingt"), "vingt");
+    }
+}
@@ -0,0 +1,241 @@
+//! Joins confirmed text onto what dictation already output in this session.
+//! Whisper transcribes each utterance on its own, so its text never starts
+//! with a space and always starts with a capital. The joiner inserts the
+//! space between pieces and utterances, leaves it out before punctuation and
+//! after opening brackets or line breaks, and fixes the case of an utterance
+//! that continues a sentence ("I went to the" + "Store." becomes "I went to
+//! the store.") or starts a new one.
+
+use super::format::lowercase_first_word;
+
+/// Characters that attach to the text before them.
+const CLOSING: &[char] = &[
+    '.', ',', ';', ':', '!', '?', ')', ']', '}', '…', '%', '”', '’', '»',
+];
+/// Characters the next text attaches to.
+const OPENING: &[char] = &['(', '[', '{', '“', '‘', '«', '¿', '¡'];
+/// Characters that end a sentence.
+const SENTENCE_END: &[char] = &['.', '!', '?', '…'];
+
+#[derive(Debug, Default)]
+pub struct Joiner {
+    /// Last character output in the session.
+    last: Option<char>,
+    /// Whether the output so far ends a sentence.
+    sentence_end: bool,
+    /// Whether anything was output in the current utterance.
+    in_utterance: bool,
+}
+
+impl Joiner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `text` (confirmed words) as it should be output next: with a leading
+    /// space where one is needed and, at the start of an utterance, the case
+    /// of the first word adjusted to the sentence it continues or starts.
+    /// Does not record it; see `push`.
+    pub fn join(&self, text: &str) -> String {
+        let text = text.trim();
+        let Some(first) = text.chars().next() else {
+            return String::new();
+        };
+        let Some(last) = self.last else {
+            // Nothing output yet: Whisper's text stands as is.
+            return text.to_string();
+        };
+
+        let text = if self.in_utterance {
+            text.to_string()
+        } else if self.sentence_end {
+            capitalize_first(text)
+        } else {
+            lowercase_first_word(text)
+        };
+        let attached = last.is_whitespace() || OPENING.contains(&last) || CLOSING.contains(&first);
+        if attached {
+            text
+        } else {
+            format!(" {}", text)
+        }
+    }
+
+    /// Record text that was output, whether it came from `join` or not (a
+    /// format suffix, say).
+    pub fn push(&mut self, output: &str) {
+        let Some(last) = output.chars().last() else {
+            return;
+        };
+        self.last = Some(last);
+        // Whitespace such as a newline suffix does not change the sentence.
+        let significant = output
+            .trim_end()
+            .trim_end_matches(['"', '\'', ')', ']', '}', '”', '’', '»'])
+            .chars()
+            .last();
+        if let Some(c) = significant {
+            self.sentence_end = SENTENCE_END.contains(&c);
+            self.in_utterance = true;
+        }
+    }
+
+    /// `join` and `push` in one step.
+    pub fn piece(&mut self, text: &str) -> String {
+        let joined = self.join(text);
+        self.push(&joined);
+        joined
+    }
+
+    /// The utterance ended; the next text starts a new one.
+    pub fn end_utterance(&mut self) {
+        self.in_utterance = false;
+    }
+}
+
+/// Uppercase the first letter of `text`.
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output each utterance's pieces through a fresh joiner.
+    fn joined(utterances: &[&[&str]]) -> String {
+        let mut joiner = Joiner::new();
+        let mut output = String::new();
+        for pieces in utterances {
+            for piece in *pieces {
+                output.push_str(&joiner.piece(piece));
+            }
+            joiner.end_utterance();
+        }
+        output
+    }
+
+    // --- Spacing Tests ---
+
+    #[test]
+    fn test_first_text_is_unchanged() {
+        assert_eq!(joined(&[&["  Hello there. "]]), "Hello there.");
+    }
+
+    #[test]
+    fn test_pieces_within_an_utterance_are_spaced() {
+        assert_eq!(
+            joined(&[&["Hello there", "general", "Kenobi."]]),
+            "Hello there general Kenobi."
+        );
+    }
+
+    #[test]
+    fn test_utterances_are_spaced() {
+        assert_eq!(
+            joined(&[&["Hello."], &["How are you?"]]),
+            "Hello. How are you?"
+        );
+    }
+
+    #[test]
+    fn test_no_space_before_punctuation() {
+        assert_eq!(
+            joined(&[&["I think", ","], &["so", "."], &["100", "%"]]),
+            "I think, so. 100%"
+        );
+        assert_eq!(joined(&[&["Wait"], &["…"]]), "Wait…");
+        assert_eq!(joined(&[&["(see above"], &[")"]]), "(see above)");
+    }
+
+    #[test]
+    fn test_no_space_after_opening_bracket() {
+        assert_eq!(
+            joined(&[&["He said ("], &["quietly)"]]),
+            "He said (quietly)"
+        );
+        assert_eq!(joined(&[&["She said “"], &["yes"]]), "She said “yes");
+    }
+
+    #[test]
+    fn test_no_space_after_line_break() {
+        let mut joiner = Joiner::new();
+        let first = joiner.piece("First line.");
+        joiner.push("\n");
+        joiner.end_utterance();
+        assert_eq!(
+            first + "\n" + &joiner.piece("Second line."),
+            "First line.\nSecond line."
+        );
+    }
+
+    #[test]
+    fn test_empty_text_outputs_nothing() {
+        let mut joiner = Joiner::new();
+        joiner.piece("Hello");
+        assert_eq!(joiner.piece("   "), "");
+        assert_eq!(joiner.piece("there"), " there");
+    }
+
+    // --- Case Tests ---
+
+    #[test]
+    fn test_continuation_is_lowercased() {
+        assert_eq!(
+            joined(&[&["I went to the"], &["Store and back."]]),
+            "I went to the store and back."
+        );
+        assert_eq!(joined(&[&["Well,"], &["Maybe."]]), "Well, maybe.");
+    }
+
+    #[test]
+    fn test_continuation_keeps_i_and_acronyms() {
+        assert_eq!(joined(&[&["And then"], &["I left"]]), "And then I left");
+        assert_eq!(
+            joined(&[&["Send the"], &["HTTP request"]]),
+            "Send the HTTP request"
+        );
+    }
+
+    #[test]
+    fn test_new_sentence_is_capitalized() {
+        assert_eq!(joined(&[&["Done."], &["next one"]]), "Done. Next one");
+        assert_eq!(joined(&[&["Really?"], &["ébahi."]]), "Really? Ébahi.");
+        // Closing quotes after the full stop still end the sentence.
+        assert_eq!(
+            joined(&[&["He said “stop.”"], &["then left"]]),
+            "He said “stop.” Then left"
+        );
+    }
+
+    #[test]
+    fn test_case_is_only_adjusted_at_utterance_start() {
+        assert_eq!(
+            joined(&[&["It works."], &["Yes", "Paris is nice"]]),
+            "It works. Yes Paris is nice"
+        );
+    }
+
+    #[test]
+    fn test_suffix_keeps_sentence_state() {
+        let mut joiner = Joiner::new();
+        joiner.piece("Buy milk");
+        joiner.push("\n");
+        joiner.end_utterance();
+        // The newline did not end the sentence, so this continues it.
+        assert_eq!(joiner.piece("And eggs"), "and eggs");
+    }
+
+    #[test]
+    fn test_join_does_not_record() {
+        let mut joiner = Joiner::new();
+        joiner.piece("One.");
+        joiner.end_utterance();
+        assert_eq!(joiner.join("two"), " Two");
+        assert_eq!(joiner.join("two"), " Two");
+    }
+}
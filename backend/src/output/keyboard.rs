@@ -1,6 +1,170 @@
-use enigo::{Enigo, Keyboard, Settings};
+//! Keystroke injection for the `type_into_field` output. enigo drives X11 and
+//! XWayland windows but cannot reach native Wayland windows on most
+//! compositors, so Wayland sessions type through an external helper instead:
+//! `wtype` (the zwp_virtual_keyboard_v1 protocol, wlroots compositors) or
+//! `ydotool` (uinput, any compositor; needs the `ydotoold` daemon). The same
+//! backends send key combinations such as the paste shortcut, and the editing
+//! keys live rewriting uses to correct typed text.
 
+use crate::config::TypingBackend;
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use std::process::Command;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionType {
+    X11,
+    Wayland,
+    Unknown,
+}
+
+impl SessionType {
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// `XDG_SESSION_TYPE` when it names a display server, otherwise whichever
+    /// display socket is set (`WAYLAND_DISPLAY` wins over `DISPLAY`).
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        match var("XDG_SESSION_TYPE").as_deref() {
+            Some("wayland") => return Self::Wayland,
+            Some("x11") => return Self::X11,
+            _ => {}
+        }
+        let set = |name| var(name).is_some_and(|value| !value.is_empty());
+        if set("WAYLAND_DISPLAY") {
+            Self::Wayland
+        } else if set("DISPLAY") {
+            Self::X11
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// The helper program a backend runs, if any.
+fn program(backend: &TypingBackend) -> Option<&'static str> {
+    match backend {
+        TypingBackend::Wtype => Some("wtype"),
+        TypingBackend::Ydotool => Some("ydotool"),
+        TypingBackend::Auto | TypingBackend::Enigo => None,
+    }
+}
+
+/// Backends to try in order. `Auto` on Wayland tries each installed helper,
+/// then enigo, which still reaches XWayland windows; elsewhere it is enigo.
+pub fn candidates(
+    backend: &TypingBackend,
+    session: SessionType,
+    installed: impl Fn(&str) -> bool,
+) -> Vec<TypingBackend> {
+    match backend {
+        TypingBackend::Auto if session == SessionType::Wayland => {
+            let helpers = [TypingBackend::Wtype, TypingBackend::Ydotool];
+            let mut backends: Vec<TypingBackend> = helpers
+                .into_iter()
+                .filter(|b| program(b).is_some_and(&installed))
+                .collect();
+            backends.push(TypingBackend::Enigo);
+            backends
+        }
+        TypingBackend::Auto => vec![TypingBackend::Enigo],
+        other => vec![other.clone()],
+    }
+}
+
+/// Whether `program` is an executable file on `PATH`.
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// Type with the backend auto-detected for this session.
 pub fn type_text(text: &str) -> Result<(), String> {
+    type_text_with(text, &TypingBackend::Auto)
+}
+
+/// Type with `backend`.
+pub fn type_text_with(text: &str, backend: &TypingBackend) -> Result<(), String> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    with_backend(backend, |candidate| match candidate {
+        TypingBackend::Wtype => run_helper("wtype", &["--", text]),
+        TypingBackend::Ydotool => run_helper("ydotool", &["type", "--", text]),
+        TypingBackend::Auto | TypingBackend::Enigo => type_with_enigo(text),
+    })
+}
+
+/// Press and release `shortcut` with `backend`.
+pub fn press_shortcut(shortcut: &Shortcut, backend: &TypingBackend) -> Result<(), String> {
+    with_backend(backend, |candidate| match candidate {
+        TypingBackend::Wtype => run_helper("wtype", &shortcut.wtype_args()),
+        TypingBackend::Ydotool => {
+            let mut args = vec!["key".to_string()];
+            args.extend(shortcut.ydotool_args());
+            run_helper("ydotool", &args)
+        }
+        TypingBackend::Auto | TypingBackend::Enigo => press_with_enigo(shortcut),
+    })
+}
+
+/// Press `key` `count` times, holding Shift if `shift`, with `backend`. Live
+/// rewriting uses this to delete or select text it typed.
+pub fn press_repeated(
+    key: EditKey,
+    count: usize,
+    shift: bool,
+    backend: &TypingBackend,
+) -> Result<(), String> {
+    if count == 0 {
+        return Ok(());
+    }
+    with_backend(backend, |candidate| match candidate {
+        TypingBackend::Wtype => run_helper("wtype", &key.wtype_args(count, shift)),
+        TypingBackend::Ydotool => {
+            let mut args = vec!["key".to_string()];
+            args.extend(key.ydotool_args(count, shift));
+            run_helper("ydotool", &args)
+        }
+        TypingBackend::Auto | TypingBackend::Enigo => repeat_with_enigo(key, count, shift),
+    })
+}
+
+/// Run `f` with each candidate for `backend` until one succeeds. A helper
+/// that fails (e.g. `wtype` on a compositor without the virtual keyboard
+/// protocol) sends nothing, so the next can take over.
+fn with_backend(
+    backend: &TypingBackend,
+    f: impl Fn(&TypingBackend) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for candidate in candidates(backend, SessionType::detect(), on_path) {
+        match f(&candidate) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(e),
+        }
+    }
+    Err(errors.join("; "))
+}
+
+fn run_helper<S: AsRef<std::ffi::OsStr>>(program: &str, args: &[S]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn type_with_enigo(text: &str) -> Result<(), String> {
     let mut enigo = Enigo::new(&Settings::default())
         .map_err(|e| format!("Failed to init keyboard simulator: {}", e))?;
 
@@ -10,3 +174,402 @@ pub fn type_text(text: &str) -> Result<(), String> {
 
     Ok(())
 }
+
+fn press_with_enigo(shortcut: &Shortcut) -> Result<(), String> {
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to init keyboard simulator: {}", e))?;
+
+    let key = match shortcut.key {
+        ShortcutKey::Letter(c) => Key::Unicode(c),
+        ShortcutKey::Insert => Key::Insert,
+    };
+    let modifiers: Vec<Key> = shortcut
+        .modifiers
+        .iter()
+        .map(|m| match m {
+            Modifier::Ctrl => Key::Control,
+            Modifier::Shift => Key::Shift,
+            Modifier::Alt => Key::Alt,
+            Modifier::Super => Key::Meta,
+        })
+        .collect();
+    let result = modifiers
+        .iter()
+        .try_for_each(|m| enigo.key(*m, Direction::Press))
+        .and_then(|_| enigo.key(key, Direction::Click));
+    // Release even after a failure so no modifier stays held down.
+    for m in modifiers.iter().rev() {
+        let _ = enigo.key(*m, Direction::Release);
+    }
+    result.map_err(|e| format!("Failed to press shortcut: {}", e))
+}
+
+fn repeat_with_enigo(key: EditKey, count: usize, shift: bool) -> Result<(), String> {
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to init keyboard simulator: {}", e))?;
+
+    let key = match key {
+        EditKey::Backspace => Key::Backspace,
+        EditKey::Left => Key::LeftArrow,
+    };
+    let result = (if shift {
+        enigo.key(Key::Shift, Direction::Press)
+    } else {
+        Ok(())
+    })
+    .and_then(|_| (0..count).try_for_each(|_| enigo.key(key, Direction::Click)));
+    if shift {
+        let _ = enigo.key(Key::Shift, Direction::Release);
+    }
+    result.map_err(|e| format!("Failed to press keys: {}", e))
+}
+
+/// Editing keys pressed by live rewriting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditKey {
+    Backspace,
+    Left,
+}
+
+impl EditKey {
+    /// `-M shift -k Left -k Left -m shift`: one invocation for all presses.
+    fn wtype_args(self, count: usize, shift: bool) -> Vec<String> {
+        let name = match self {
+            EditKey::Backspace => "BackSpace",
+            EditKey::Left => "Left",
+        };
+        let mut args = Vec::new();
+        if shift {
+            args.extend(["-M".to_string(), "shift".to_string()]);
+        }
+        for _ in 0..count {
+            args.extend(["-k".to_string(), name.to_string()]);
+        }
+        if shift {
+            args.extend(["-m".to_string(), "shift".to_string()]);
+        }
+        args
+    }
+
+    /// `42:1 105:1 105:0 105:1 105:0 42:0`
+    fn ydotool_args(self, count: usize, shift: bool) -> Vec<String> {
+        let code = match self {
+            EditKey::Backspace => 14,
+            EditKey::Left => 105,
+        };
+        let shift_code = Modifier::Shift.evdev_code();
+        let mut args = Vec::new();
+        if shift {
+            args.push(format!("{}:1", shift_code));
+        }
+        for _ in 0..count {
+            args.extend([format!("{}:1", code), format!("{}:0", code)]);
+        }
+        if shift {
+            args.push(format!("{}:0", shift_code));
+        }
+        args
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
+
+impl Modifier {
+    fn wtype_name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "ctrl",
+            Modifier::Shift => "shift",
+            Modifier::Alt => "alt",
+            Modifier::Super => "logo",
+        }
+    }
+
+    /// Linux input event code of the left-hand key.
+    fn evdev_code(self) -> u16 {
+        match self {
+            Modifier::Ctrl => 29,
+            Modifier::Shift => 42,
+            Modifier::Alt => 56,
+            Modifier::Super => 125,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortcutKey {
+    /// A lowercase ASCII letter.
+    Letter(char),
+    Insert,
+}
+
+impl ShortcutKey {
+    fn wtype_name(self) -> String {
+        match self {
+            ShortcutKey::Letter(c) => c.to_string(),
+            ShortcutKey::Insert => "Insert".to_string(),
+        }
+    }
+
+    /// Linux input event code (US layout positions, as uinput expects).
+    fn evdev_code(self) -> u16 {
+        const ROWS: [(&str, u16); 3] = [("qwertyuiop", 16), ("asdfghjkl", 30), ("zxcvbnm", 44)];
+        match self {
+            ShortcutKey::Insert => 110,
+            ShortcutKey::Letter(c) => ROWS
+                .iter()
+                .find_map(|(row, first)| row.find(c).map(|i| first + i as u16))
+                .expect("shortcut letters are validated when parsed"),
+        }
+    }
+}
+
+/// A key combination written like `Ctrl+Shift+V`: modifiers (`Ctrl`,
+/// `Shift`, `Alt`, `Super`) followed by a letter or `Insert`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortcut {
+    pub modifiers: Vec<Modifier>,
+    pub key: ShortcutKey,
+}
+
+impl Shortcut {
+    /// `-M ctrl -k v -m ctrl`: hold the modifiers around the key.
+    fn wtype_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for m in &self.modifiers {
+            args.extend(["-M".to_string(), m.wtype_name().to_string()]);
+        }
+        args.extend(["-k".to_string(), self.key.wtype_name()]);
+        for m in self.modifiers.iter().rev() {
+            args.extend(["-m".to_string(), m.wtype_name().to_string()]);
+        }
+        args
+    }
+
+    /// `29:1 47:1 47:0 29:0`: key-down and key-up events by code.
+    fn ydotool_args(&self) -> Vec<String> {
+        let codes: Vec<u16> = self.modifiers.iter().map(|m| m.evdev_code()).collect();
+        let key = self.key.evdev_code();
+        codes
+            .iter()
+            .map(|c| format!("{}:1", c))
+            .chain([format!("{}:1", key), format!("{}:0", key)])
+            .chain(codes.iter().rev().map(|c| format!("{}:0", c)))
+            .collect()
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let (key, modifiers) = parts.split_last().expect("split yields at least one part");
+        let modifiers = modifiers
+            .iter()
+            .map(|m| match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => Ok(Modifier::Ctrl),
+                "shift" => Ok(Modifier::Shift),
+                "alt" => Ok(Modifier::Alt),
+                "super" | "meta" | "logo" => Ok(Modifier::Super),
+                _ => Err(format!("Unknown modifier '{}' in shortcut '{}'", m, s)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = match key.to_ascii_lowercase().as_str() {
+            "insert" => ShortcutKey::Insert,
+            k if k.len() == 1 && k.as_bytes()[0].is_ascii_lowercase() => {
+                ShortcutKey::Letter(k.as_bytes()[0] as char)
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported key '{}' in shortcut '{}'; use a letter or Insert",
+                    key, s
+                ))
+            }
+        };
+        Ok(Self { modifiers, key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn session(vars: &[(&str, &str)]) -> SessionType {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        SessionType::from_env(|name| vars.get(name).cloned())
+    }
+
+    // --- Session Detection Tests ---
+
+    #[test]
+    fn test_session_type_from_xdg() {
+        assert_eq!(
+            session(&[("XDG_SESSION_TYPE", "wayland"), ("DISPLAY", ":0")]),
+            SessionType::Wayland
+        );
+        assert_eq!(
+            session(&[
+                ("XDG_SESSION_TYPE", "x11"),
+                ("WAYLAND_DISPLAY", "wayland-0")
+            ]),
+            SessionType::X11
+        );
+    }
+
+    #[test]
+    fn test_session_type_from_display_sockets() {
+        // XWayland sets DISPLAY too; the Wayland socket decides.
+        assert_eq!(
+            session(&[
+                ("XDG_SESSION_TYPE", "tty"),
+                ("WAYLAND_DISPLAY", "wayland-1"),
+                ("DISPLAY", ":0")
+            ]),
+            SessionType::Wayland
+        );
+        assert_eq!(session(&[("DISPLAY", ":0")]), SessionType::X11);
+        assert_eq!(
+            session(&[("WAYLAND_DISPLAY", ""), ("DISPLAY", "")]),
+            SessionType::Unknown
+        );
+    }
+
+    // --- Backend Selection Tests ---
+
+    #[test]
+    fn test_auto_on_wayland_prefers_installed_helpers() {
+        let all = candidates(&TypingBackend::Auto, SessionType::Wayland, |_| true);
+        assert_eq!(
+            all,
+            vec![
+                TypingBackend::Wtype,
+                TypingBackend::Ydotool,
+                TypingBackend::Enigo
+            ]
+        );
+        let ydotool_only = candidates(&TypingBackend::Auto, SessionType::Wayland, |p| {
+            p == "ydotool"
+        });
+        assert_eq!(
+            ydotool_only,
+            vec![TypingBackend::Ydotool, TypingBackend::Enigo]
+        );
+        let none = candidates(&TypingBackend::Auto, SessionType::Wayland, |_| false);
+        assert_eq!(none, vec![TypingBackend::Enigo]);
+    }
+
+    #[test]
+    fn test_auto_elsewhere_uses_enigo() {
+        for session in [SessionType::X11, SessionType::Unknown] {
+            assert_eq!(
+                candidates(&TypingBackend::Auto, session, |_| true),
+                vec![TypingBackend::Enigo]
+            );
+        }
+    }
+
+    #[test]
+    fn test_override_is_used_as_is() {
+        // An explicit choice is not second-guessed, even if not installed.
+        assert_eq!(
+            candidates(&TypingBackend::Ydotool, SessionType::X11, |_| false),
+            vec![TypingBackend::Ydotool]
+        );
+        assert_eq!(
+            candidates(&TypingBackend::Enigo, SessionType::Wayland, |_| true),
+            vec![TypingBackend::Enigo]
+        );
+    }
+
+    #[test]
+    fn test_missing_helper_reports_error() {
+        let err = run_helper("whispertype-no-such-helper", &["--", "hi"]).unwrap_err();
+        assert!(err.starts_with("Failed to run whispertype-no-such-helper"));
+    }
+
+    #[test]
+    fn test_empty_text_types_nothing() {
+        assert!(type_text_with("", &TypingBackend::Wtype).is_ok());
+    }
+
+    // --- Shortcut Tests ---
+
+    #[test]
+    fn test_parse_shortcut() {
+        let paste: Shortcut = "Ctrl+V".parse().unwrap();
+        assert_eq!(paste.modifiers, vec![Modifier::Ctrl]);
+        assert_eq!(paste.key, ShortcutKey::Letter('v'));
+
+        let terminal: Shortcut = "ctrl + shift + v".parse().unwrap();
+        assert_eq!(terminal.modifiers, vec![Modifier::Ctrl, Modifier::Shift]);
+
+        let insert: Shortcut = "Shift+Insert".parse().unwrap();
+        assert_eq!(insert.key, ShortcutKey::Insert);
+    }
+
+    #[test]
+    fn test_parse_shortcut_rejects_unknown_keys() {
+        assert!("Hyper+V"
+            .parse::<Shortcut>()
+            .unwrap_err()
+            .contains("Unknown modifier"));
+        assert!("Ctrl+F5"
+            .parse::<Shortcut>()
+            .unwrap_err()
+            .contains("Unsupported key"));
+        assert!("".parse::<Shortcut>().is_err());
+        assert!("Ctrl+".parse::<Shortcut>().is_err());
+    }
+
+    #[test]
+    fn test_shortcut_helper_args() {
+        let terminal: Shortcut = "Ctrl+Shift+V".parse().unwrap();
+        assert_eq!(
+            terminal.wtype_args().join(" "),
+            "-M ctrl -M shift -k v -m shift -m ctrl"
+        );
+        assert_eq!(
+            terminal.ydotool_args().join(" "),
+            "29:1 42:1 47:1 47:0 42:0 29:0"
+        );
+        let insert: Shortcut = "Shift+Insert".parse().unwrap();
+        assert_eq!(insert.ydotool_args().join(" "), "42:1 110:1 110:0 42:0");
+    }
+
+    // --- Edit Key Tests ---
+
+    #[test]
+    fn test_repeated_key_helper_args() {
+        assert_eq!(
+            EditKey::Backspace.wtype_args(2, false).join(" "),
+            "-k BackSpace -k BackSpace"
+        );
+        assert_eq!(
+            EditKey::Left.wtype_args(2, true).join(" "),
+            "-M shift -k Left -k Left -m shift"
+        );
+        assert_eq!(
+            EditKey::Backspace.ydotool_args(1, false).join(" "),
+            "14:1 14:0"
+        );
+        assert_eq!(
+            EditKey::Left.ydotool_args(2, true).join(" "),
+            "42:1 105:1 105:0 105:1 105:0 42:0"
+        );
+    }
+
+    #[test]
+    fn test_zero_presses_send_nothing() {
+        assert!(press_repeated(EditKey::Backspace, 0, false, &TypingBackend::Wtype).is_ok());
+    }
+}
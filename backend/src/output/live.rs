@@ -0,0 +1,312 @@
+//! Live rewriting for the `type_into_field` output. The current hypothesis is
+//! typed as soon as it is recognized and corrected in place as it changes:
+//! `LiveText` remembers exactly what it typed in the current utterance, so a
+//! correction erases only the characters after the common prefix and types
+//! the new tail. Characters here are grapheme clusters, the unit Backspace
+//! and Shift+Left move by.
+
+use super::keyboard::{self, EditKey};
+use super::sink::{self, OutputSink, OutputTargets};
+use crate::config::{Config, LiveRewrite, OutputMode, TypingBackend};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Keystrokes that edit the text just before the cursor.
+pub trait Keys: Send {
+    /// Replace the `erase` grapheme clusters before the cursor with `text`.
+    fn replace(&mut self, erase: usize, text: &str) -> Result<(), String>;
+}
+
+/// Edits the focused field with simulated keystrokes.
+pub struct KeyboardKeys {
+    method: LiveRewrite,
+    backend: TypingBackend,
+}
+
+impl KeyboardKeys {
+    pub fn new(method: &LiveRewrite, backend: &TypingBackend) -> Self {
+        Self {
+            method: method.clone(),
+            backend: backend.clone(),
+        }
+    }
+}
+
+impl Keys for KeyboardKeys {
+    fn replace(&mut self, erase: usize, text: &str) -> Result<(), String> {
+        match self.method {
+            LiveRewrite::Select if erase > 0 => {
+                keyboard::press_repeated(EditKey::Left, erase, true, &self.backend)?;
+                if text.is_empty() {
+                    // Nothing to type over the selection: delete it.
+                    keyboard::press_repeated(EditKey::Backspace, 1, false, &self.backend)
+                } else {
+                    keyboard::type_text_with(text, &self.backend)
+                }
+            }
+            _ => {
+                keyboard::press_repeated(EditKey::Backspace, erase, false, &self.backend)?;
+                keyboard::type_text_with(text, &self.backend)
+            }
+        }
+    }
+}
+
+/// The text typed for the current utterance.
+pub struct LiveText {
+    keys: Box<dyn Keys>,
+    /// Exactly what was typed in this utterance and is still in the field.
+    typed: String,
+    /// A keystroke failed, so the field's contents are unknown; editing stops
+    /// until the utterance ends rather than erasing the wrong characters.
+    lost: bool,
+}
+
+impl LiveText {
+    pub fn new(keys: Box<dyn Keys>) -> Self {
+        Self {
+            keys,
+            typed: String::new(),
+            lost: false,
+        }
+    }
+
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// Make the utterance's text in the field read `target`.
+    pub fn show(&mut self, target: &str) -> Result<(), String> {
+        if self.lost {
+            return Ok(());
+        }
+        let keep = common_prefix(&self.typed, target);
+        // Backspace and Shift+Left move by grapheme cluster, not by byte or
+        // code point: an emoji with a skin tone or an accent typed as a
+        // combining mark is one keypress.
+        let erase = self.typed[keep..].graphemes(true).count();
+        let text = &target[keep..];
+        if erase == 0 && text.is_empty() {
+            return Ok(());
+        }
+        match self.keys.replace(erase, text) {
+            Ok(()) => {
+                self.typed.truncate(keep);
+                self.typed.push_str(text);
+                Ok(())
+            }
+            Err(e) => {
+                self.lost = true;
+                Err(e)
+            }
+        }
+    }
+
+    /// End the utterance: its text stays in the field and is no longer edited.
+    pub fn commit(&mut self) {
+        self.typed.clear();
+        self.lost = false;
+    }
+}
+
+/// Byte length of the longest common prefix of `a` and `b` made of whole
+/// grapheme clusters.
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.grapheme_indices(true)
+        .zip(b.graphemes(true))
+        .find(|((_, x), y)| x != y)
+        .map(|((i, _), _)| i)
+        .unwrap_or(a.len().min(b.len()))
+}
+
+/// The output for `config`. With live rewriting on and typing among the
+/// outputs, typing moves to the returned `LiveText` and the sink keeps the
+/// other outputs, which still receive confirmed text only.
+pub fn from_config(config: &Config) -> (Box<dyn OutputSink>, Option<LiveText>) {
+    let modes = sink::modes(config);
+    let targets = OutputTargets::from_config(config);
+    let (others, typed) = without_typing(&modes);
+    if config.live_rewrite == LiveRewrite::Off || !typed {
+        return (sink::from_modes(&modes, &targets), None);
+    }
+    let keys = KeyboardKeys::new(&config.live_rewrite, &config.typing_backend);
+    (
+        sink::from_modes(&others, &targets),
+        Some(LiveText::new(Box::new(keys))),
+    )
+}
+
+/// `modes` without keystroke typing (`Both` keeps its clipboard half), and
+/// whether typing was among them.
+fn without_typing(modes: &[OutputMode]) -> (Vec<OutputMode>, bool) {
+    let mut others = Vec::new();
+    let mut typed = false;
+    for mode in modes {
+        let rest = match mode {
+            OutputMode::TypeIntoField => None,
+            OutputMode::Both => Some(OutputMode::Clipboard),
+            other => Some(other.clone()),
+        };
+        typed |= rest.as_ref() != Some(mode);
+        if let Some(rest) = rest {
+            if !others.contains(&rest) {
+                others.push(rest);
+            }
+        }
+    }
+    (others, typed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A text field with the cursor at the end, logging each replacement.
+    #[derive(Clone, Default)]
+    struct Field {
+        text: Arc<Mutex<String>>,
+        log: Arc<Mutex<Vec<(usize, String)>>>,
+        fail: Arc<Mutex<bool>>,
+    }
+
+    impl Keys for Field {
+        fn replace(&mut self, erase: usize, text: &str) -> Result<(), String> {
+            if *self.fail.lock().unwrap() {
+                return Err("keyboard gone".to_string());
+            }
+            let mut field = self.text.lock().unwrap();
+            let keep = field.graphemes(true).count() - erase;
+            *field = field.graphemes(true).take(keep).collect::<String>() + text;
+            self.log.lock().unwrap().push((erase, text.to_string()));
+            Ok(())
+        }
+    }
+
+    fn live(field: &Field) -> LiveText {
+        LiveText::new(Box::new(field.clone()))
+    }
+
+    // --- Diff Tests ---
+
+    #[test]
+    fn test_common_prefix() {
+        assert_eq!(common_prefix("hello world", "hello word"), 9);
+        assert_eq!(common_prefix("hello", "hello there"), 5);
+        assert_eq!(common_prefix("", "x"), 0);
+        // Multi-byte characters are never split.
+        assert_eq!(common_prefix("café au", "cafè"), 3);
+        // Nor are grapheme clusters: "e" differs from "e" + combining acute.
+        assert_eq!(common_prefix("cafe", "cafe\u{301}"), 3);
+    }
+
+    // --- Rewrite Tests ---
+
+    #[test]
+    fn test_show_types_only_the_changed_tail() {
+        let field = Field::default();
+        let mut text = live(&field);
+        text.show("I scream").unwrap();
+        text.show("I scream for").unwrap();
+        text.show("Ice cream for you").unwrap();
+        text.show("Ice cream for you").unwrap();
+        assert_eq!(*field.text.lock().unwrap(), "Ice cream for you");
+        assert_eq!(text.typed(), "Ice cream for you");
+        assert_eq!(
+            *field.log.lock().unwrap(),
+            vec![
+                (0, "I scream".to_string()),
+                (0, " for".to_string()),
+                (11, "ce cream for you".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_erase_counts_characters() {
+        let field = Field::default();
+        let mut text = live(&field);
+        text.show("naïve café").unwrap();
+        text.show("naïve").unwrap();
+        assert_eq!(field.log.lock().unwrap()[1], (5, String::new()));
+        assert_eq!(*field.text.lock().unwrap(), "naïve");
+    }
+
+    #[test]
+    fn test_erase_counts_grapheme_clusters() {
+        let field = Field::default();
+        let mut text = live(&field);
+        // Thumbs up with a skin tone is two code points but one Backspace.
+        text.show("Great \u{1F44D}\u{1F3FD}").unwrap();
+        text.show("Great!").unwrap();
+        assert_eq!(field.log.lock().unwrap()[1], (2, "!".to_string()));
+        assert_eq!(*field.text.lock().unwrap(), "Great!");
+
+        // A combining accent is erased with its base letter.
+        text.commit();
+        field.text.lock().unwrap().clear();
+        text.show("cafe\u{301}s").unwrap();
+        text.show("cafe!").unwrap();
+        assert_eq!(field.log.lock().unwrap()[3], (2, "e!".to_string()));
+        assert_eq!(*field.text.lock().unwrap(), "cafe!");
+    }
+
+    #[test]
+    fn test_commit_keeps_text_and_starts_fresh() {
+        let field = Field::default();
+        let mut text = live(&field);
+        text.show("First.").unwrap();
+        text.commit();
+        text.show(" Second").unwrap();
+        text.show(" Second.").unwrap();
+        assert_eq!(*field.text.lock().unwrap(), "First. Second.");
+    }
+
+    #[test]
+    fn test_failure_stops_editing_until_commit() {
+        let field = Field::default();
+        let mut text = live(&field);
+        text.show("one two").unwrap();
+        *field.fail.lock().unwrap() = true;
+        assert_eq!(text.show("one too").unwrap_err(), "keyboard gone");
+
+        // What the field holds is unknown now, so nothing is erased.
+        *field.fail.lock().unwrap() = false;
+        text.show("one").unwrap();
+        assert_eq!(*field.text.lock().unwrap(), "one two");
+
+        text.commit();
+        text.show(" three").unwrap();
+        assert_eq!(*field.text.lock().unwrap(), "one two three");
+    }
+
+    // --- Output Selection Tests ---
+
+    #[test]
+    fn test_without_typing() {
+        assert_eq!(
+            without_typing(&[OutputMode::Both, OutputMode::File]),
+            (vec![OutputMode::Clipboard, OutputMode::File], true)
+        );
+        assert_eq!(
+            without_typing(&[OutputMode::TypeIntoField]),
+            (Vec::new(), true)
+        );
+        assert_eq!(
+            without_typing(&[OutputMode::Paste, OutputMode::Clipboard]),
+            (vec![OutputMode::Paste, OutputMode::Clipboard], false)
+        );
+    }
+
+    #[test]
+    fn test_live_text_only_when_enabled_and_typing() {
+        let mut config = Config {
+            output_mode: OutputMode::TypeIntoField,
+            ..Config::default()
+        };
+        assert!(from_config(&config).1.is_none());
+        config.live_rewrite = LiveRewrite::Backspace;
+        assert!(from_config(&config).1.is_some());
+        config.output_mode = OutputMode::Clipboard;
+        assert!(from_config(&config).1.is_none());
+    }
+}
@@ -1,26 +1,32 @@
-//! Text output routing: dispatches transcribed text to keyboard simulation (enigo),
-//! system clipboard (arboard), or both, based on the user's configured output mode.
+//! Text output routing: dispatches transcribed text to keyboard simulation (enigo,
+//! or `wtype`/`ydotool` on Wayland; see `keyboard`, and `live` for live rewriting), system clipboard (arboard), a file, stdout or a local socket, based on the
+//! user's configured output modes. See `sink` for the composable sinks.
 
 pub mod clipboard;
+pub mod format;
+pub mod joiner;
 pub mod keyboard;
+pub mod live;
+pub mod normalize;
+pub mod sink;
 
 use crate::config::OutputMode;
+use sink::OutputTargets;
 
-pub fn output_text(text: &str, mode: &OutputMode) -> Result<(), String> {
-    match mode {
-        OutputMode::TypeIntoField => keyboard::type_text(text),
-        OutputMode::Clipboard => clipboard::copy_to_clipboard(text),
-        OutputMode::Both => {
-            keyboard::type_text(text)?;
-            clipboard::copy_to_clipboard(text)?;
-            Ok(())
-        }
-    }
+/// Output `text` with a single mode, e.g. the CLI's `transcribe` printing a
+/// transcript. Dictation builds its sinks with `live::from_config` instead.
+pub fn output_text(text: &str, mode: &OutputMode, targets: &OutputTargets) -> Result<(), String> {
+    sink::sink_for(mode, targets).write(text)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+
+    fn targets() -> OutputTargets {
+        OutputTargets::from_config(&Config::default())
+    }
 
     // These tests verify the output_text routing logic.
     // They cannot fully test keyboard/clipboard in a headless CI environment,
@@ -30,7 +36,7 @@ mod tests {
     fn test_output_mode_type_into_field_variant() {
         // Verify that OutputMode::TypeIntoField routes to keyboard::type_text
         // In a headless environment, enigo may fail, which is expected
-        let result = output_text("test", &OutputMode::TypeIntoField);
+        let result = output_text("test", &OutputMode::TypeIntoField, &targets());
         // We just ensure it returns a Result (may be Ok or Err depending on display server)
         let _ = result;
     }
@@ -38,13 +44,13 @@ mod tests {
     #[test]
     fn test_output_mode_clipboard_variant() {
         // In a headless environment, arboard may fail, which is expected
-        let result = output_text("test", &OutputMode::Clipboard);
+        let result = output_text("test", &OutputMode::Clipboard, &targets());
         let _ = result;
     }
 
     #[test]
     fn test_output_mode_both_variant() {
-        let result = output_text("test", &OutputMode::Both);
+        let result = output_text("test", &OutputMode::Both, &targets());
         let _ = result;
     }
 
@@ -57,29 +63,34 @@ mod tests {
             OutputMode::TypeIntoField,
             OutputMode::Clipboard,
             OutputMode::Both,
+            OutputMode::Stdout,
         ];
         for mode in &modes {
-            let _ = output_text("test", mode);
+            let _ = output_text("test", mode, &targets());
         }
     }
 
     #[test]
     fn test_output_text_with_empty_string() {
         // Empty string should not cause a panic in any mode
-        let _ = output_text("", &OutputMode::Clipboard);
-        let _ = output_text("", &OutputMode::TypeIntoField);
-        let _ = output_text("", &OutputMode::Both);
+        let _ = output_text("", &OutputMode::Clipboard, &targets());
+        let _ = output_text("", &OutputMode::TypeIntoField, &targets());
+        let _ = output_text("", &OutputMode::Both, &targets());
     }
 
     #[test]
     fn test_output_text_with_unicode() {
         // Unicode text should not cause a panic
-        let _ = output_text("Hello \u{2714} \u{1F600}", &OutputMode::Clipboard);
+        let _ = output_text(
+            "Hello \u{2714} \u{1F600}",
+            &OutputMode::Clipboard,
+            &targets(),
+        );
     }
 
     #[test]
     fn test_output_text_with_multiline() {
         // Multiline text should not cause a panic
-        let _ = output_text("line1\nline2\nline3", &OutputMode::Clipboard);
+        let _ = output_text("line1\nline2\nline3", &OutputMode::Clipboard, &targets());
     }
 }
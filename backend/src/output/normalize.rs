@@ -0,0 +1,1391 @@
+//! Inverse text normalization: spoken numbers, ordinals, currencies, times,
+//! dates, percentages and units in written form ("twenty three dollars" ->
+//! "$23", "March fifth" -> "March 5"). Whisper writes some of
+//! these itself, but not consistently. English and German are understood; the
+//! locale also decides the written conventions: separators, currency and unit
+//! placement, date order and 12- or 24-hour times.
+//!
+//! Conservative by design: numbers below ten stay words ("one of them"), and
+//! a run of number words that does not form a single number ("one two three",
+//! "three thirty" without am/pm) is left alone.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locale {
+    EnUs,
+    EnGb,
+    De,
+}
+
+impl Locale {
+    /// A language tag or Whisper language code: `en`, `en-US`, `en-GB`,
+    /// `de`, `de-AT`... `auto` is read as English. `None` for languages
+    /// without normalization rules.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_lowercase().replace('_', "-");
+        let (language, region) = code.split_once('-').unwrap_or((code.as_str(), ""));
+        match (language, region) {
+            ("en", "gb" | "ie") => Some(Locale::EnGb),
+            ("en" | "auto" | "", _) => Some(Locale::EnUs),
+            ("de", _) => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    /// `text` with every recognized spoken form written out.
+    pub fn normalize(self, text: &str) -> String {
+        let tokens = tokenize(text);
+        let mut out: Vec<String> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match self.find(&tokens, i) {
+                Some(Found::Replace(written, end)) => {
+                    out.push(format!(
+                        "{}{}{}",
+                        tokens[i].lead,
+                        written,
+                        tokens[end - 1].trail
+                    ));
+                    i = end;
+                }
+                Some(Found::Keep(end)) => {
+                    out.extend(tokens[i..end].iter().map(|t| t.raw.to_string()));
+                    i = end;
+                }
+                None => {
+                    out.push(tokens[i].raw.to_string());
+                    i += 1;
+                }
+            }
+        }
+        out.join(" ")
+    }
+
+    fn lex(self) -> &'static Lexicon {
+        match self {
+            Locale::EnUs | Locale::EnGb => &ENGLISH,
+            Locale::De => &GERMAN,
+        }
+    }
+
+    fn german(self) -> bool {
+        self == Locale::De
+    }
+
+    /// The replacement for the text starting at token `i`.
+    fn find(self, t: &[Token], i: usize) -> Option<Found> {
+        if let Some((written, end)) = self.time(t, i).or_else(|| self.date(t, i)) {
+            return Some(Found::Replace(written, end));
+        }
+        self.expression(t, i)
+    }
+
+    // --- Numbers ---
+
+    /// The morphemes of a number word and whether it is an ordinal.
+    fn morphs(self, word: &str) -> Option<(Vec<Morph>, bool)> {
+        let lex = self.lex();
+        if let Some(m) = lookup(lex.words, word) {
+            return Some((vec![m], false));
+        }
+        if self.german() {
+            return decompose(word)
+                .map(|m| (m, false))
+                .or_else(|| german_ordinal(word));
+        }
+        if let Some(m) = lookup(lex.ordinals, word) {
+            return Some((vec![m], true));
+        }
+        // Hyphenated: "twenty-three", "forty-second".
+        let parts: Vec<&str> = word.split('-').collect();
+        if parts.len() < 2 {
+            return None;
+        }
+        let (last, init) = parts.split_last()?;
+        let mut morphs = init
+            .iter()
+            .map(|p| lookup(lex.words, p).filter(|m| m.is_numeral()))
+            .collect::<Option<Vec<_>>>()?;
+        match lookup(lex.words, last).filter(|m| m.is_numeral()) {
+            Some(m) => {
+                morphs.push(m);
+                Some((morphs, false))
+            }
+            None => {
+                morphs.push(lookup(lex.ordinals, last)?);
+                Some((morphs, true))
+            }
+        }
+    }
+
+    /// Whether the word is (part of) a number on its own, unlike "a" or "and".
+    fn is_number_word(self, word: &str) -> bool {
+        self.digits(word).is_some()
+            || self
+                .morphs(word)
+                .is_some_and(|(m, _)| m.iter().any(|m| m.is_numeral()))
+    }
+
+    /// A number written in digits, e.g. `23`, `1,500` or `3.5` (`1.500`,
+    /// `3,5` in German).
+    fn digits(self, word: &str) -> Option<Num> {
+        let (group, decimal) = if self.german() {
+            ('.', ',')
+        } else {
+            (',', '.')
+        };
+        let (int, frac) = match word.split_once(decimal) {
+            Some((int, frac)) if !frac.is_empty() => (int, frac),
+            Some(_) => return None,
+            None => (word, ""),
+        };
+        let mut groups = int.split(group);
+        let first = groups.next()?;
+        let grouped = int.contains(group);
+        let valid = !first.is_empty()
+            && first.chars().all(|c| c.is_ascii_digit())
+            && (!grouped || first.len() <= 3)
+            && groups.all(|g| g.len() == 3 && g.chars().all(|c| c.is_ascii_digit()))
+            && frac.chars().all(|c| c.is_ascii_digit());
+        let digits: String = int.chars().filter(char::is_ascii_digit).collect();
+        if !valid || digits.len() > 15 {
+            return None;
+        }
+        Some(Num {
+            int: digits.parse().ok()?,
+            frac: frac.to_string(),
+            literal: Some(word.to_string()),
+            ..Num::default()
+        })
+    }
+
+    /// The longest number spelled out in words from token `i`.
+    fn cardinal(self, t: &[Token], i: usize) -> Option<(Num, usize)> {
+        let mut acc = Acc::new(self.german());
+        let mut best = None;
+        let mut j = i;
+        while j < t.len() && (j == i || joinable(t, j - 1)) {
+            let Some((morphs, ordinal)) = self.morphs(&t[j].word) else {
+                break;
+            };
+            let mut next = acc.clone();
+            if !morphs.iter().all(|&m| next.feed(m)) {
+                break;
+            }
+            acc = next;
+            j += 1;
+            if let Some(int) = acc.value() {
+                let num = Num {
+                    int,
+                    ordinal,
+                    ..Num::default()
+                };
+                best = Some((num, j));
+            }
+            if ordinal {
+                break;
+            }
+        }
+        best
+    }
+
+    /// A number in digits or words at token `i`.
+    fn number(self, t: &[Token], i: usize) -> Option<(Num, usize)> {
+        match self.digits(&t.get(i)?.word) {
+            Some(num) => Some((num, i + 1)),
+            None => self.cardinal(t, i),
+        }
+    }
+
+    /// A single digit word: "five", or "oh" for zero.
+    fn digit_word(self, word: &str) -> Option<u64> {
+        if !word.is_empty() && word == self.lex().oh {
+            return Some(0);
+        }
+        match self.morphs(word)? {
+            (m, false) if m.len() == 1 => match m[0] {
+                Morph::Digit(d) => Some(d),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// `num` followed by "point" and digit words: "three point one four".
+    fn decimal(self, t: &[Token], mut num: Num, end: usize) -> (Num, usize) {
+        let spoken = !num.ordinal && num.literal.is_none();
+        if !spoken || end >= t.len() || !joinable(t, end - 1) || t[end].word != self.lex().point {
+            return (num, end);
+        }
+        let mut frac = String::new();
+        let mut j = end + 1;
+        while j < t.len() && joinable(t, j - 1) {
+            match self.digit_word(&t[j].word) {
+                Some(d) => frac.push_str(&d.to_string()),
+                None => break,
+            }
+            j += 1;
+        }
+        if frac.is_empty() {
+            return (num, end);
+        }
+        num.frac = frac;
+        (num, j)
+    }
+
+    /// A number, possibly negative or with decimals, and what follows it.
+    fn expression(self, t: &[Token], i: usize) -> Option<Found> {
+        let lex = self.lex();
+        let negative = lex.minus.contains(&t[i].word.as_str()) && joinable(t, i);
+        let start = if negative { i + 1 } else { i };
+        let (mut num, end) = self.number(t, start)?;
+        num.negative = negative;
+        let (num, end) = self.decimal(t, num, end);
+        if num.ordinal && negative {
+            return None;
+        }
+
+        if !num.ordinal {
+            let suffixed = self
+                .currency(t, &num, end)
+                .or_else(|| self.percent(t, &num, end))
+                .or_else(|| self.unit(t, &num, end));
+            if let Some((written, end)) = suffixed {
+                return Some(Found::Replace(written, end));
+            }
+        }
+
+        if num.literal.is_some() {
+            return negative.then(|| Found::Replace(self.write(&num), end));
+        }
+        // "one two three", "three thirty": not one number, so leave them.
+        if end < t.len() && joinable(t, end - 1) && self.is_number_word(&t[end].word) {
+            let mut run_end = end + 1;
+            while run_end < t.len()
+                && joinable(t, run_end - 1)
+                && self.is_number_word(&t[run_end].word)
+            {
+                run_end += 1;
+            }
+            return Some(Found::Keep(run_end));
+        }
+        let written = if num.ordinal {
+            (num.int >= 10).then(|| self.ordinal(num.int))
+        } else {
+            (num.int >= 10 || !num.frac.is_empty() || negative).then(|| self.write(&num))
+        };
+        written.map(|w| Found::Replace(w, end))
+    }
+
+    // --- Suffixes ---
+
+    fn currency(self, t: &[Token], num: &Num, end: usize) -> Option<(String, usize)> {
+        if end >= t.len() || !joinable(t, end - 1) {
+            return None;
+        }
+        let symbol = lookup(self.lex().currencies, &t[end].word)?;
+        if symbol == "£" && self != Locale::EnGb {
+            // "pounds" is a weight in the US.
+            return None;
+        }
+
+        // "twenty dollars and fifty cents"
+        let mut cents = None;
+        let mut next = end + 1;
+        let and = end + 1;
+        if num.frac.is_empty()
+            && and + 1 < t.len()
+            && joinable(t, end)
+            && joinable(t, and)
+            && t[and].word == self.lex().and
+        {
+            if let Some((c, c_end)) = self.number(t, and + 1) {
+                let cent_word = t.get(c_end).map(|w| w.word.as_str());
+                if (1..=99).contains(&c.int)
+                    && !c.ordinal
+                    && c.frac.is_empty()
+                    && joinable(t, c_end - 1)
+                    && cent_word.is_some_and(|w| self.lex().cents.contains(&w))
+                {
+                    cents = Some(c.int);
+                    next = c_end + 1;
+                }
+            }
+        }
+
+        let mut amount = self.write(&Num {
+            negative: false,
+            ..num.clone()
+        });
+        if let Some(cents) = cents {
+            amount = format!("{}{}{:02}", amount, self.decimal_separator(), cents);
+        }
+        let sign = if num.negative { "-" } else { "" };
+        let written = match self {
+            Locale::De => format!("{}{} {}", sign, amount, symbol),
+            _ => format!("{}{}{}", sign, symbol, amount),
+        };
+        Some((written, next))
+    }
+
+    fn percent(self, t: &[Token], num: &Num, end: usize) -> Option<(String, usize)> {
+        let end = self
+            .lex()
+            .percent
+            .iter()
+            .find_map(|words| phrase(t, end, words, ""))?;
+        let written = match self {
+            Locale::De => format!("{} %", self.write(num)),
+            _ => format!("{}%", self.write(num)),
+        };
+        Some((written, end))
+    }
+
+    fn unit(self, t: &[Token], num: &Num, end: usize) -> Option<(String, usize)> {
+        let lex = self.lex();
+        let (symbol, end) = lex
+            .units
+            .iter()
+            .find_map(|(words, symbol)| Some((*symbol, phrase(t, end, words, lex.plural)?)))?;
+        // 23°, 23°C; German sets °C apart but not a bare °.
+        let attached = symbol == "°" || (symbol.starts_with('°') && !self.german());
+        let written = if attached {
+            format!("{}{}", self.write(num), symbol)
+        } else {
+            format!("{} {}", self.write(num), symbol)
+        };
+        Some((written, end))
+    }
+
+    // --- Times and Dates ---
+
+    /// An hour, hour and minutes or a day of the month at token `i`: a whole,
+    /// non-negative cardinal in `range`.
+    fn whole(
+        self,
+        t: &[Token],
+        i: usize,
+        range: std::ops::RangeInclusive<u64>,
+    ) -> Option<(u64, usize)> {
+        let (num, end) = self.number(t, i)?;
+        let whole = !num.ordinal && num.frac.is_empty() && range.contains(&num.int);
+        whole.then_some((num.int, end))
+    }
+
+    /// "three thirty p.m." -> "3:30 PM"; "fünfzehn Uhr dreißig" -> "15:30 Uhr".
+    fn time(self, t: &[Token], i: usize) -> Option<(String, usize)> {
+        if self.german() {
+            let (hour, end) = self.whole(t, i, 0..=24)?;
+            if end >= t.len() || !joinable(t, end - 1) || t[end].word != "uhr" {
+                return None;
+            }
+            let minutes = (end + 1 < t.len() && joinable(t, end))
+                .then(|| self.whole(t, end + 1, 1..=59))
+                .flatten();
+            return Some(match minutes {
+                Some((m, m_end)) => (format!("{}:{:02} Uhr", hour, m), m_end),
+                None => (format!("{} Uhr", hour), end + 1),
+            });
+        }
+
+        let (hour, end) = self.whole(t, i, 1..=12)?;
+        if end >= t.len() || !joinable(t, end - 1) {
+            return None;
+        }
+        if t[end].word == "o'clock" {
+            return Some((format!("{} o'clock", hour), end + 1));
+        }
+        let oh = t[end].word == self.lex().oh && end + 1 < t.len() && joinable(t, end);
+        let (minutes, m_end) = match self.digit_word(t.get(end + 1).map_or("", |t| &t.word)) {
+            Some(d) if oh && d > 0 => (Some(d), end + 2),
+            _ => match self.whole(t, end, 10..=59) {
+                Some((m, m_end)) => (Some(m), m_end),
+                None => (None, end),
+            },
+        };
+        if m_end >= t.len() || (m_end > end && !joinable(t, m_end - 1)) {
+            return None;
+        }
+        let marker = match t[m_end].word.as_str() {
+            "am" | "a.m" => "AM",
+            "pm" | "p.m" => "PM",
+            _ => return None,
+        };
+        let marker = match self {
+            Locale::EnGb => marker.to_lowercase(),
+            _ => marker.to_string(),
+        };
+        let written = match minutes {
+            Some(m) => format!("{}:{:02} {}", hour, m, marker),
+            None => format!("{} {}", hour, marker),
+        };
+        Some((written, m_end + 1))
+    }
+
+    /// "March fifth, twenty twenty four" -> "March 5, 2024" (US) or
+    /// "5 March 2024" (UK); "the fifth of March" -> "the 5th of March";
+    /// "am fünften März" -> "am 5. März".
+    fn date(self, t: &[Token], i: usize) -> Option<(String, usize)> {
+        let lex = self.lex();
+        let month = |j: usize| {
+            let word = &t.get(j)?.word;
+            let m = lex.months.iter().position(|m| m == word)?;
+            Some(title_case(lex.months[m]))
+        };
+
+        // Month first (English).
+        if let (false, Some(name)) = (self.german(), month(i)) {
+            if !joinable(t, i) {
+                return None;
+            }
+            let (day, ordinal, end) = self.day(t, i + 1)?;
+            let year = self.year_after(t, end);
+            // A year that did not parse ("May nineteen ninety nine" as day 19).
+            let more = end < t.len() && joinable(t, end - 1) && self.is_number_word(&t[end].word);
+            if year.is_none() && more {
+                return None;
+            }
+            // "we march twenty miles", "it may one day": the verbs, unless the
+            // day is an ordinal, a year follows or the month was capitalized.
+            let capitalized = t[i].raw[t[i].lead.len()..].starts_with(char::is_uppercase);
+            if !ordinal && year.is_none() && !capitalized {
+                return None;
+            }
+            return Some(match (self, year) {
+                (Locale::EnGb, Some((y, y_end))) => (format!("{} {} {}", day, name, y), y_end),
+                (Locale::EnGb, None) => (format!("{} {}", day, name), end),
+                (_, Some((y, y_end))) => (format!("{} {}, {}", name, day, y), y_end),
+                (_, None) => (format!("{} {}", name, day), end),
+            });
+        }
+
+        // Ordinal day first: "fifth of March", "fünften März".
+        let (day, ordinal, end) = self.day(t, i)?;
+        if !ordinal || end >= t.len() || !joinable(t, end - 1) {
+            return None;
+        }
+        let (name, m_end) = if self.german() {
+            (month(end)?, end + 1)
+        } else {
+            if t[end].word != "of" || !joinable(t, end) {
+                return None;
+            }
+            (month(end + 1)?, end + 2)
+        };
+        let written = if self.german() {
+            format!("{}. {}", day, name)
+        } else {
+            format!("{} of {}", self.ordinal(day), name)
+        };
+        Some(match self.year_after(t, m_end) {
+            Some((y, y_end)) => (format!("{} {}", written, y), y_end),
+            None => (written, m_end),
+        })
+    }
+
+    /// A day of the month: (day, ordinal, end).
+    fn day(self, t: &[Token], i: usize) -> Option<(u64, bool, usize)> {
+        let word = &t.get(i)?.word;
+        // "5th"
+        if !self.german() {
+            let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            let suffix = &word[digits.len()..];
+            if let (Ok(d), "st" | "nd" | "rd" | "th") = (digits.parse::<u64>(), suffix) {
+                return (1..=31).contains(&d).then_some((d, true, i + 1));
+            }
+        }
+        let (num, end) = self.number(t, i)?;
+        let day = num.frac.is_empty() && (1..=31).contains(&num.int);
+        day.then_some((num.int, num.ordinal, end))
+    }
+
+    /// A year following a day or month, which may be separated by a comma.
+    fn year_after(self, t: &[Token], end: usize) -> Option<(u64, usize)> {
+        let prev = t.get(end.checked_sub(1)?)?;
+        let next = t.get(end)?;
+        if !(prev.trail.is_empty() || prev.trail == ",") || !next.lead.is_empty() {
+            return None;
+        }
+        self.year(t, end)
+    }
+
+    /// "twenty twenty four", "nineteen oh five", "two thousand and one",
+    /// "zweitausendvierundzwanzig" or "2024".
+    fn year(self, t: &[Token], i: usize) -> Option<(u64, usize)> {
+        let years = 1000..=2999;
+        let word = &t.get(i)?.word;
+        if word.len() == 4 {
+            if let Some(num) = self.digits(word) {
+                return years.contains(&num.int).then_some((num.int, i + 1));
+            }
+        }
+        let (first, end) = self.cardinal(t, i)?;
+        if first.ordinal {
+            return None;
+        }
+        if years.contains(&first.int) {
+            return Some((first.int, end));
+        }
+        // Spoken in pairs (English).
+        if self.german()
+            || !(10..=99).contains(&first.int)
+            || end >= t.len()
+            || !joinable(t, end - 1)
+        {
+            return None;
+        }
+        if t[end].word == self.lex().oh && end + 1 < t.len() && joinable(t, end) {
+            let d = self.digit_word(&t[end + 1].word).filter(|&d| d > 0)?;
+            return Some((first.int * 100 + d, end + 2));
+        }
+        let (second, end) = self.whole(t, end, 10..=99)?;
+        Some((first.int * 100 + second, end))
+    }
+
+    // --- Writing ---
+
+    fn decimal_separator(self) -> char {
+        if self.german() {
+            ','
+        } else {
+            '.'
+        }
+    }
+
+    /// Digits, grouped in thousands from 10,000 up (so years stay "2024").
+    fn group(self, n: u64) -> String {
+        let digits = n.to_string();
+        if n < 10_000 {
+            return digits;
+        }
+        let separator = if self.german() { '.' } else { ',' };
+        let mut out = String::new();
+        for (k, c) in digits.chars().enumerate() {
+            if k > 0 && (digits.len() - k).is_multiple_of(3) {
+                out.push(separator);
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    fn write(self, num: &Num) -> String {
+        let mut out = String::new();
+        if num.negative {
+            out.push('-');
+        }
+        match &num.literal {
+            Some(literal) => out.push_str(literal),
+            None => {
+                out.push_str(&self.group(num.int));
+                if !num.frac.is_empty() {
+                    out.push(self.decimal_separator());
+                    out.push_str(&num.frac);
+                }
+            }
+        }
+        out
+    }
+
+    /// "21st", or "21." in German.
+    fn ordinal(self, n: u64) -> String {
+        if self.german() {
+            return format!("{}.", self.group(n));
+        }
+        let suffix = match (n % 100, n % 10) {
+            (11..=13, _) => "th",
+            (_, 1) => "st",
+            (_, 2) => "nd",
+            (_, 3) => "rd",
+            _ => "th",
+        };
+        format!("{}{}", self.group(n), suffix)
+    }
+
+    // --- Streaming ---
+
+    /// Whether the word may be part of a spoken form.
+    fn known(self, word: &str) -> bool {
+        let lex = self.lex();
+        let unit_word = |w: &str| {
+            lex.units.iter().any(|(words, _)| {
+                words
+                    .iter()
+                    .any(|u| w == *u || w.strip_suffix(lex.plural) == Some(u))
+            })
+        };
+        self.morphs(word).is_some()
+            || self.digits(word).is_some()
+            || [
+                lex.point, lex.oh, lex.and, "of", "uhr", "o'clock", "am", "a.m", "pm", "p.m",
+            ]
+            .contains(&word)
+            || lex.minus.contains(&word)
+            || lex.months.contains(&word)
+            || lookup(lex.currencies, word).is_some()
+            || lex.cents.contains(&word)
+            || lex.percent.iter().any(|p| p.contains(&word))
+            || unit_word(word)
+    }
+}
+
+/// Normalizes confirmed text as it arrives in pieces. A spoken form can span
+/// pieces ("twenty" now, "three dollars" a pass later), so trailing words that
+/// could still be part of one are held back until the next piece or the end
+/// of the utterance.
+pub struct Normalizer {
+    locale: Locale,
+    held: Vec<String>,
+}
+
+impl Normalizer {
+    pub fn new(locale: Locale) -> Self {
+        Self {
+            locale,
+            held: Vec::new(),
+        }
+    }
+
+    /// Normalize a confirmed piece; returns the text that is ready, which may
+    /// be empty.
+    pub fn piece(&mut self, text: &str) -> String {
+        self.held
+            .extend(text.split_whitespace().map(str::to_string));
+        let joined = self.held.join(" ");
+        let tokens = tokenize(&joined);
+        let mut keep = tokens.len();
+        if tokens.last().is_some_and(|t| t.trail.is_empty()) {
+            while keep > 0 && self.locale.known(&tokens[keep - 1].word) {
+                if keep < tokens.len() && !joinable(&tokens, keep - 1) {
+                    break;
+                }
+                keep -= 1;
+            }
+        }
+        let ready: Vec<String> = self.held.drain(..keep).collect();
+        self.locale.normalize(&ready.join(" "))
+    }
+
+    /// Normalize a whole utterance at once; does not touch held words.
+    pub fn utterance(&self, text: &str) -> String {
+        self.locale.normalize(text)
+    }
+
+    /// The utterance ended: normalize whatever is held back.
+    pub fn finish(&mut self) -> String {
+        let held = std::mem::take(&mut self.held);
+        self.locale.normalize(&held.join(" "))
+    }
+}
+
+enum Found {
+    /// Written form of tokens up to the end index.
+    Replace(String, usize),
+    /// Tokens up to the end index stay as they are.
+    Keep(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Num {
+    int: u64,
+    /// Decimal digits.
+    frac: String,
+    negative: bool,
+    ordinal: bool,
+    /// Already written in digits, kept as written.
+    literal: Option<String>,
+}
+
+// --- Tokens ---
+
+/// A word with the punctuation around it.
+struct Token<'a> {
+    raw: &'a str,
+    lead: &'a str,
+    trail: &'a str,
+    /// Lowercased, without the punctuation.
+    word: String,
+}
+
+const LEAD: &[char] = &['(', '[', '"', '“', '‘', '«', '¿', '¡'];
+const TRAIL: &[char] = &[
+    '.', ',', '!', '?', ';', ':', ')', ']', '"', '”', '’', '»', '…',
+];
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    text.split_whitespace()
+        .map(|raw| {
+            let rest = raw.trim_start_matches(LEAD);
+            let lead = &raw[..raw.len() - rest.len()];
+            let core = rest.trim_end_matches(TRAIL);
+            Token {
+                raw,
+                lead,
+                trail: &rest[core.len()..],
+                word: core.to_lowercase(),
+            }
+        })
+        .collect()
+}
+
+/// Whether token `k` runs into token `k + 1` without punctuation between.
+fn joinable(t: &[Token], k: usize) -> bool {
+    t[k].trail.is_empty() && t.get(k + 1).is_some_and(|next| next.lead.is_empty())
+}
+
+/// The end of `words` if they follow at token `i`; the first word may take
+/// the `plural` ending.
+fn phrase(t: &[Token], i: usize, words: &[&str], plural: &str) -> Option<usize> {
+    if i == 0 || !joinable(t, i - 1) {
+        return None;
+    }
+    for (k, expected) in words.iter().enumerate() {
+        let word = &t.get(i + k)?.word;
+        let plural_form =
+            k == 0 && !plural.is_empty() && word.strip_suffix(plural) == Some(expected);
+        if word != expected && !plural_form {
+            return None;
+        }
+        if k + 1 < words.len() && !joinable(t, i + k) {
+            return None;
+        }
+    }
+    Some(i + words.len())
+}
+
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// --- Grammar ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Morph {
+    Digit(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+    /// "and" / "und"
+    And,
+    /// "a" / "ein", only before a hundred or a scale word.
+    A,
+}
+
+impl Morph {
+    fn is_numeral(self) -> bool {
+        !matches!(self, Morph::And | Morph::A)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Last {
+    Start,
+    Zero,
+    A,
+    Digit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+    And,
+}
+
+/// Accumulates a number morpheme by morpheme, rejecting sequences that do not
+/// form one ("one two", "twenty thirty").
+#[derive(Debug, Clone)]
+struct Acc {
+    total: u64,
+    current: u64,
+    /// The last scale word; later ones must be smaller.
+    scale: u64,
+    last: Last,
+    /// German puts units before tens ("drei und zwanzig") and has no "and"
+    /// after hundreds.
+    german: bool,
+}
+
+impl Acc {
+    fn new(german: bool) -> Self {
+        Self {
+            total: 0,
+            current: 0,
+            scale: u64::MAX,
+            last: Last::Start,
+            german,
+        }
+    }
+
+    fn feed(&mut self, m: Morph) -> bool {
+        use Last::*;
+        let english = !self.german;
+        let next = match (m, self.last) {
+            (Morph::Digit(0), Start) => Zero,
+            (Morph::Digit(0), _) => return false,
+            (Morph::Digit(_), Start | Hundred | Scale) => Digit,
+            (Morph::Digit(_), Tens | And) if english => Digit,
+            (Morph::Teen(_), Start | Hundred | Scale) => Teen,
+            (Morph::Teen(_), And) if english => Teen,
+            (Morph::Tens(_), Start | Hundred | Scale | And) => Tens,
+            // A bare "hundred" or "thousand" counts one.
+            (Morph::Hundred, Start | A | Digit | Teen | Tens) if self.current < 100 => Hundred,
+            (Morph::Scale(s), Start | A | Digit | Teen | Tens | Hundred) if s < self.scale => Scale,
+            (Morph::And, Hundred | Scale) if english => And,
+            (Morph::And, Digit) if self.german => And,
+            (Morph::A, Start) => A,
+            _ => return false,
+        };
+        match m {
+            Morph::Digit(n) | Morph::Teen(n) | Morph::Tens(n) => self.current += n,
+            Morph::Hundred => self.current = self.current.max(1) * 100,
+            Morph::Scale(s) => {
+                self.total += self.current.max(1) * s;
+                self.current = 0;
+                self.scale = s;
+            }
+            Morph::A => self.current = 1,
+            Morph::And => {}
+        }
+        self.last = next;
+        true
+    }
+
+    fn value(&self) -> Option<u64> {
+        match self.last {
+            Last::Start | Last::A | Last::And => None,
+            _ => Some(self.total + self.current),
+        }
+    }
+}
+
+struct Lexicon {
+    /// Whole words.
+    words: &'static [(&'static str, Morph)],
+    /// Ordinal words, by the morpheme they stand for.
+    ordinals: &'static [(&'static str, Morph)],
+    and: &'static str,
+    point: &'static str,
+    /// Zero in times and years ("ten oh five").
+    oh: &'static str,
+    minus: &'static [&'static str],
+    months: [&'static str; 12],
+    currencies: &'static [(&'static str, &'static str)],
+    cents: &'static [&'static str],
+    percent: &'static [&'static [&'static str]],
+    /// Longer phrases first.
+    units: &'static [(&'static [&'static str], &'static str)],
+    /// Plural ending of unit words.
+    plural: &'static str,
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], word: &str) -> Option<T> {
+    table.iter().find(|(w, _)| *w == word).map(|(_, v)| *v)
+}
+
+const ENGLISH: Lexicon = Lexicon {
+    words: &[
+        ("zero", Morph::Digit(0)),
+        ("one", Morph::Digit(1)),
+        ("two", Morph::Digit(2)),
+        ("three", Morph::Digit(3)),
+        ("four", Morph::Digit(4)),
+        ("five", Morph::Digit(5)),
+        ("six", Morph::Digit(6)),
+        ("seven", Morph::Digit(7)),
+        ("eight", Morph::Digit(8)),
+        ("nine", Morph::Digit(9)),
+        ("ten", Morph::Teen(10)),
+        ("eleven", Morph::Teen(11)),
+        ("twelve", Morph::Teen(12)),
+        ("thirteen", Morph::Teen(13)),
+        ("fourteen", Morph::Teen(14)),
+        ("fifteen", Morph::Teen(15)),
+        ("sixteen", Morph::Teen(16)),
+        ("seventeen", Morph::Teen(17)),
+        ("eighteen", Morph::Teen(18)),
+        ("nineteen", Morph::Teen(19)),
+        ("twenty", Morph::Tens(20)),
+        ("thirty", Morph::Tens(30)),
+        ("forty", Morph::Tens(40)),
+        ("fifty", Morph::Tens(50)),
+        ("sixty", Morph::Tens(60)),
+        ("seventy", Morph::Tens(70)),
+        ("eighty", Morph::Tens(80)),
+        ("ninety", Morph::Tens(90)),
+        ("hundred", Morph::Hundred),
+        ("thousand", Morph::Scale(1_000)),
+        ("million", Morph::Scale(1_000_000)),
+        ("billion", Morph::Scale(1_000_000_000)),
+        ("trillion", Morph::Scale(1_000_000_000_000)),
+        ("and", Morph::And),
+        ("a", Morph::A),
+    ],
+    ordinals: &[
+        ("first", Morph::Digit(1)),
+        ("second", Morph::Digit(2)),
+        ("third", Morph::Digit(3)),
+        ("fourth", Morph::Digit(4)),
+        ("fifth", Morph::Digit(5)),
+        ("sixth", Morph::Digit(6)),
+        ("seventh", Morph::Digit(7)),
+        ("eighth", Morph::Digit(8)),
+        ("ninth", Morph::Digit(9)),
+        ("tenth", Morph::Teen(10)),
+        ("eleventh", Morph::Teen(11)),
+        ("twelfth", Morph::Teen(12)),
+        ("thirteenth", Morph::Teen(13)),
+        ("fourteenth", Morph::Teen(14)),
+        ("fifteenth", Morph::Teen(15)),
+        ("sixteenth", Morph::Teen(16)),
+        ("seventeenth", Morph::Teen(17)),
+        ("eighteenth", Morph::Teen(18)),
+        ("nineteenth", Morph::Teen(19)),
+        ("twentieth", Morph::Tens(20)),
+        ("thirtieth", Morph::Tens(30)),
+        ("fortieth", Morph::Tens(40)),
+        ("fiftieth", Morph::Tens(50)),
+        ("sixtieth", Morph::Tens(60)),
+        ("seventieth", Morph::Tens(70)),
+        ("eightieth", Morph::Tens(80)),
+        ("ninetieth", Morph::Tens(90)),
+        ("hundredth", Morph::Hundred),
+        ("thousandth", Morph::Scale(1_000)),
+        ("millionth", Morph::Scale(1_000_000)),
+    ],
+    and: "and",
+    point: "point",
+    oh: "oh",
+    minus: &["minus", "negative"],
+    months: [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ],
+    currencies: &[
+        ("dollar", "$"),
+        ("dollars", "$"),
+        ("euro", "€"),
+        ("euros", "€"),
+        ("pound", "£"),
+        ("pounds", "£"),
+    ],
+    cents: &["cent", "cents", "pence"],
+    percent: &[&["percent"], &["per", "cent"]],
+    units: &[
+        (&["kilometer", "per", "hour"], "km/h"),
+        (&["kilometre", "per", "hour"], "km/h"),
+        (&["mile", "per", "hour"], "mph"),
+        (&["degree", "celsius"], "°C"),
+        (&["degree", "fahrenheit"], "°F"),
+        (&["degree"], "°"),
+        (&["kilometer"], "km"),
+        (&["kilometre"], "km"),
+        (&["meter"], "m"),
+        (&["metre"], "m"),
+        (&["centimeter"], "cm"),
+        (&["centimetre"], "cm"),
+        (&["millimeter"], "mm"),
+        (&["millimetre"], "mm"),
+        (&["kilogram"], "kg"),
+        (&["gram"], "g"),
+        (&["milligram"], "mg"),
+        (&["liter"], "l"),
+        (&["litre"], "l"),
+        (&["milliliter"], "ml"),
+        (&["millilitre"], "ml"),
+        (&["kilobyte"], "KB"),
+        (&["megabyte"], "MB"),
+        (&["gigabyte"], "GB"),
+        (&["terabyte"], "TB"),
+    ],
+    plural: "s",
+};
+
+const GERMAN: Lexicon = Lexicon {
+    words: &[
+        ("ein", Morph::A),
+        ("eine", Morph::A),
+        ("einen", Morph::A),
+        ("einem", Morph::A),
+        ("einer", Morph::A),
+        ("und", Morph::And),
+    ],
+    // German ordinals are compounds; see `german_ordinal`.
+    ordinals: &[],
+    and: "und",
+    point: "komma",
+    oh: "",
+    minus: &["minus"],
+    months: [
+        "januar",
+        "februar",
+        "märz",
+        "april",
+        "mai",
+        "juni",
+        "juli",
+        "august",
+        "september",
+        "oktober",
+        "november",
+        "dezember",
+    ],
+    currencies: &[("euro", "€"), ("dollar", "$")],
+    cents: &["cent"],
+    percent: &[&["prozent"]],
+    units: &[
+        (&["kilometer", "pro", "stunde"], "km/h"),
+        (&["grad", "celsius"], "°C"),
+        (&["grad", "fahrenheit"], "°F"),
+        (&["grad"], "°"),
+        (&["kilometer"], "km"),
+        (&["meter"], "m"),
+        (&["zentimeter"], "cm"),
+        (&["millimeter"], "mm"),
+        (&["kilogramm"], "kg"),
+        (&["gramm"], "g"),
+        (&["milligramm"], "mg"),
+        (&["liter"], "l"),
+        (&["milliliter"], "ml"),
+        (&["kilobyte"], "KB"),
+        (&["megabyte"], "MB"),
+        (&["gigabyte"], "GB"),
+        (&["terabyte"], "TB"),
+    ],
+    plural: "n",
+};
+
+/// Parts German number words are compounded from.
+const GERMAN_PARTS: &[(&str, Morph)] = &[
+    ("null", Morph::Digit(0)),
+    ("eins", Morph::Digit(1)),
+    ("ein", Morph::Digit(1)),
+    ("zwei", Morph::Digit(2)),
+    ("zwo", Morph::Digit(2)),
+    ("drei", Morph::Digit(3)),
+    ("vier", Morph::Digit(4)),
+    ("fünf", Morph::Digit(5)),
+    ("sechs", Morph::Digit(6)),
+    ("sieben", Morph::Digit(7)),
+    ("acht", Morph::Digit(8)),
+    ("neun", Morph::Digit(9)),
+    ("zehn", Morph::Teen(10)),
+    ("elf", Morph::Teen(11)),
+    ("zwölf", Morph::Teen(12)),
+    ("dreizehn", Morph::Teen(13)),
+    ("vierzehn", Morph::Teen(14)),
+    ("fünfzehn", Morph::Teen(15)),
+    ("sechzehn", Morph::Teen(16)),
+    ("siebzehn", Morph::Teen(17)),
+    ("achtzehn", Morph::Teen(18)),
+    ("neunzehn", Morph::Teen(19)),
+    ("zwanzig", Morph::Tens(20)),
+    ("dreißig", Morph::Tens(30)),
+    ("vierzig", Morph::Tens(40)),
+    ("fünfzig", Morph::Tens(50)),
+    ("sechzig", Morph::Tens(60)),
+    ("siebzig", Morph::Tens(70)),
+    ("achtzig", Morph::Tens(80)),
+    ("neunzig", Morph::Tens(90)),
+    ("hundert", Morph::Hundred),
+    ("tausend", Morph::Scale(1_000)),
+    ("million", Morph::Scale(1_000_000)),
+    ("millionen", Morph::Scale(1_000_000)),
+    ("milliarde", Morph::Scale(1_000_000_000)),
+    ("milliarden", Morph::Scale(1_000_000_000)),
+    ("und", Morph::And),
+];
+
+/// German ordinal stems, e.g. "dritte" in "dreiundzwanzigste" is not one,
+/// "zwanzigste" is.
+const GERMAN_ORDINALS: &[(&str, Morph)] = &[
+    ("erste", Morph::Digit(1)),
+    ("zweite", Morph::Digit(2)),
+    ("dritte", Morph::Digit(3)),
+    ("vierte", Morph::Digit(4)),
+    ("fünfte", Morph::Digit(5)),
+    ("sechste", Morph::Digit(6)),
+    ("siebte", Morph::Digit(7)),
+    ("siebente", Morph::Digit(7)),
+    ("achte", Morph::Digit(8)),
+    ("neunte", Morph::Digit(9)),
+    ("zehnte", Morph::Teen(10)),
+    ("elfte", Morph::Teen(11)),
+    ("zwölfte", Morph::Teen(12)),
+    ("dreizehnte", Morph::Teen(13)),
+    ("vierzehnte", Morph::Teen(14)),
+    ("fünfzehnte", Morph::Teen(15)),
+    ("sechzehnte", Morph::Teen(16)),
+    ("siebzehnte", Morph::Teen(17)),
+    ("achtzehnte", Morph::Teen(18)),
+    ("neunzehnte", Morph::Teen(19)),
+    ("zwanzigste", Morph::Tens(20)),
+    ("dreißigste", Morph::Tens(30)),
+    ("vierzigste", Morph::Tens(40)),
+    ("fünfzigste", Morph::Tens(50)),
+    ("sechzigste", Morph::Tens(60)),
+    ("siebzigste", Morph::Tens(70)),
+    ("achtzigste", Morph::Tens(80)),
+    ("neunzigste", Morph::Tens(90)),
+    ("hundertste", Morph::Hundred),
+    ("tausendste", Morph::Scale(1_000)),
+];
+
+/// Split a German number word into its parts, longest part first:
+/// "zweihundertdreiundzwanzig" -> zwei, hundert, drei, und, zwanzig.
+fn decompose(word: &str) -> Option<Vec<Morph>> {
+    let mut morphs = Vec::new();
+    let mut rest = word;
+    while !rest.is_empty() {
+        let (part, m) = GERMAN_PARTS
+            .iter()
+            .filter(|(part, _)| rest.starts_with(part))
+            .max_by_key(|(part, _)| part.len())?;
+        morphs.push(*m);
+        rest = &rest[part.len()..];
+    }
+    (!morphs.is_empty()).then_some(morphs)
+}
+
+/// A German ordinal in any inflection: "dritte", "dreiundzwanzigsten".
+fn german_ordinal(word: &str) -> Option<(Vec<Morph>, bool)> {
+    let uninflected = word
+        .strip_suffix(['n', 'r', 's', 'm'])
+        .filter(|w| w.ends_with('e'));
+    [Some(word), uninflected]
+        .into_iter()
+        .flatten()
+        .find_map(|w| {
+            let (stem, m) = GERMAN_ORDINALS
+                .iter()
+                .filter(|(stem, _)| w.ends_with(stem))
+                .max_by_key(|(stem, _)| stem.len())?;
+            let prefix = &w[..w.len() - stem.len()];
+            let mut morphs = if prefix.is_empty() {
+                Vec::new()
+            } else {
+                decompose(prefix)?
+            };
+            morphs.push(*m);
+            Some((morphs, true))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn en(text: &str) -> String {
+        Locale::EnUs.normalize(text)
+    }
+
+    fn gb(text: &str) -> String {
+        Locale::EnGb.normalize(text)
+    }
+
+    fn de(text: &str) -> String {
+        Locale::De.normalize(text)
+    }
+
+    // --- Locale Tests ---
+
+    #[test]
+    fn test_locale_from_code() {
+        assert_eq!(Locale::from_code("en"), Some(Locale::EnUs));
+        assert_eq!(Locale::from_code("en_GB"), Some(Locale::EnGb));
+        assert_eq!(Locale::from_code("auto"), Some(Locale::EnUs));
+        assert_eq!(Locale::from_code("de-AT"), Some(Locale::De));
+        assert_eq!(Locale::from_code("fr"), None);
+    }
+
+    // --- Cardinal Tests ---
+
+    #[test]
+    fn test_cardinals() {
+        assert_eq!(en("twenty three people"), "23 people");
+        assert_eq!(en("one hundred and five"), "105");
+        assert_eq!(en("a hundred twenty-five"), "125");
+        assert_eq!(en("two thousand three hundred"), "2300");
+        assert_eq!(en("Forty two thousand"), "42,000");
+        assert_eq!(en("three million five hundred thousand"), "3,500,000");
+        assert_eq!(en("nineteen hundred"), "1900");
+        assert_eq!(en("thousand times"), "1000 times");
+    }
+
+    #[test]
+    fn test_small_numbers_stay_words() {
+        assert_eq!(en("one of them has two cats"), "one of them has two cats");
+        assert_eq!(en("a dog and a cat"), "a dog and a cat");
+        assert_eq!(en("zero"), "zero");
+    }
+
+    #[test]
+    fn test_runs_that_are_not_one_number_stay() {
+        assert_eq!(en("one two three"), "one two three");
+        assert_eq!(en("meet at three thirty"), "meet at three thirty");
+        assert_eq!(en("twenty twenty four"), "twenty twenty four");
+        // "and" only joins a number after hundreds and scales.
+        assert_eq!(en("twenty and thirty"), "20 and 30");
+    }
+
+    #[test]
+    fn test_decimals_and_negatives() {
+        assert_eq!(en("three point one four"), "3.14");
+        assert_eq!(en("zero point five"), "0.5");
+        assert_eq!(en("minus five degrees"), "-5°");
+        assert_eq!(en("minus twelve"), "-12");
+        assert_eq!(en("the point is"), "the point is");
+        assert_eq!(de("drei komma fünf"), "3,5");
+    }
+
+    #[test]
+    fn test_punctuation_is_kept() {
+        assert_eq!(en("It costs twenty dollars."), "It costs $20.");
+        assert_eq!(en("(fifteen percent)"), "(15%)");
+        // Punctuation ends a number.
+        assert_eq!(en("twenty, thirty"), "20, 30");
+    }
+
+    // --- Ordinal Tests ---
+
+    #[test]
+    fn test_ordinals() {
+        assert_eq!(en("the twenty first century"), "the 21st century");
+        assert_eq!(en("her forty-second birthday"), "her 42nd birthday");
+        assert_eq!(en("the eleventh hour"), "the 11th hour");
+        assert_eq!(en("the hundredth time"), "the 100th time");
+        assert_eq!(en("first of all, second"), "first of all, second");
+        assert_eq!(de("der dreiundzwanzigste Versuch"), "der 23. Versuch");
+        assert_eq!(de("die zweite Runde"), "die zweite Runde");
+    }
+
+    // --- Currency, Percent and Unit Tests ---
+
+    #[test]
+    fn test_currencies() {
+        assert_eq!(en("twenty three dollars"), "$23");
+        assert_eq!(en("23 dollars"), "$23");
+        assert_eq!(en("five dollars and fifty cents"), "$5.50");
+        assert_eq!(en("one dollar"), "$1");
+        assert_eq!(en("a million euros"), "€1,000,000");
+        assert_eq!(en("ten pounds of flour"), "10 pounds of flour");
+        assert_eq!(gb("ten pounds"), "£10");
+        assert_eq!(de("zwanzig Euro und fünfzig Cent"), "20,50 €");
+        assert_eq!(de("dreihundert Dollar"), "300 $");
+    }
+
+    #[test]
+    fn test_percentages() {
+        assert_eq!(en("five percent"), "5%");
+        assert_eq!(en("twelve point five per cent"), "12.5%");
+        assert_eq!(de("zehn Prozent"), "10 %");
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(en("five kilometers"), "5 km");
+        assert_eq!(en("one hundred kilometers per hour"), "100 km/h");
+        assert_eq!(en("two gigabytes"), "2 GB");
+        assert_eq!(en("twenty degrees Celsius"), "20°C");
+        assert_eq!(de("zwanzig Grad Celsius"), "20 °C");
+        assert_eq!(de("drei Kilometern"), "3 km");
+    }
+
+    // --- Time Tests ---
+
+    #[test]
+    fn test_times() {
+        assert_eq!(en("at three thirty p.m."), "at 3:30 PM.");
+        assert_eq!(en("ten oh five am"), "10:05 AM");
+        assert_eq!(en("seven pm"), "7 PM");
+        assert_eq!(en("five o'clock"), "5 o'clock");
+        assert_eq!(
+            gb("half past nothing, nine fifteen pm"),
+            "half past nothing, 9:15 pm"
+        );
+        assert_eq!(en("I am here"), "I am here");
+        assert_eq!(de("um fünfzehn Uhr dreißig"), "um 15:30 Uhr");
+        assert_eq!(de("um acht Uhr"), "um 8 Uhr");
+    }
+
+    // --- Date Tests ---
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(en("March fifth"), "March 5");
+        assert_eq!(en("on march 5th, twenty twenty four."), "on March 5, 2024.");
+        assert_eq!(en("May nineteen ninety nine"), "May nineteen ninety nine");
+        assert_eq!(gb("March fifth twenty twenty four"), "5 March 2024");
+        assert_eq!(en("the fifth of March"), "the 5th of March");
+        assert_eq!(
+            en("the first of July nineteen oh five"),
+            "the 1st of July 1905"
+        );
+        assert_eq!(en("May I ask"), "May I ask");
+        assert_eq!(en("we march twenty miles"), "we march 20 miles");
+        assert_eq!(en("it may one day"), "it may one day");
+        assert_eq!(en("due march twentieth"), "due March 20");
+        assert_eq!(en("until may 5 2025"), "until May 5, 2025");
+        assert_eq!(
+            de("am fünften März zweitausendvierundzwanzig"),
+            "am 5. März 2024"
+        );
+    }
+
+    #[test]
+    fn test_years() {
+        let year = |text: &str| {
+            let tokens = tokenize(text);
+            Locale::EnUs.year(&tokens, 0).map(|(y, _)| y)
+        };
+        assert_eq!(year("twenty twenty four"), Some(2024));
+        assert_eq!(year("two thousand and one"), Some(2001));
+        assert_eq!(year("nineteen hundred"), Some(1900));
+        assert_eq!(year("1999"), Some(1999));
+        assert_eq!(year("forty"), None);
+    }
+
+    // --- German Grammar Tests ---
+
+    #[test]
+    fn test_german_compounds() {
+        assert_eq!(de("dreiundzwanzig Leute"), "23 Leute");
+        assert_eq!(de("zweihundertfünfzig"), "250");
+        assert_eq!(de("einhunderteins"), "101");
+        assert_eq!(de("eine Million"), "1.000.000");
+        assert_eq!(de("fünfundvierzigtausend"), "45.000");
+        assert_eq!(de("ein Hund"), "ein Hund");
+        assert_eq!(de("einst"), "einst");
+    }
+
+    // --- Streaming Tests ---
+
+    #[test]
+    fn test_normalizer_holds_back_unfinished_numbers() {
+        let mut n = Normalizer::new(Locale::EnUs);
+        assert_eq!(n.piece("It costs twenty"), "It costs");
+        assert_eq!(n.piece("three dollars and"), "");
+        assert_eq!(n.piece("fifty cents today"), "$23.50 today");
+        assert_eq!(n.piece("at seven"), "at");
+        assert_eq!(n.finish(), "seven");
+        assert_eq!(n.finish(), "");
+    }
+
+    #[test]
+    fn test_normalizer_releases_at_punctuation() {
+        let mut n = Normalizer::new(Locale::EnUs);
+        assert_eq!(n.piece("about twenty."), "about 20.");
+    }
+}
@@ -0,0 +1,456 @@
+//! Output sinks: destinations for confirmed dictation text. Each `OutputMode`
+//! maps to a sink, and `MultiSink` combines several so text can be typed,
+//! logged to a daily notes file and pushed to another program at once.
+
+use super::{clipboard, keyboard};
+use crate::config::{Config, OutputMode, TypingBackend};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+pub trait OutputSink: Send {
+    fn write(&mut self, text: &str) -> Result<(), String>;
+}
+
+/// Types text into the focused field.
+pub struct KeyboardSink {
+    backend: TypingBackend,
+}
+
+impl KeyboardSink {
+    pub fn new(backend: &TypingBackend) -> Self {
+        Self {
+            backend: backend.clone(),
+        }
+    }
+}
+
+impl OutputSink for KeyboardSink {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        keyboard::type_text_with(text, &self.backend)
+    }
+}
+
+/// Replaces the clipboard contents with the text.
+pub struct ClipboardSink;
+
+impl OutputSink for ClipboardSink {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        clipboard::copy_to_clipboard(text)
+    }
+}
+
+/// Pastes text through the clipboard with a shortcut, leaving the clipboard
+/// as it was. Much faster than typing long text, and independent of the
+/// keyboard layout.
+pub struct PasteSink {
+    shortcut: String,
+    backend: TypingBackend,
+}
+
+impl PasteSink {
+    pub fn new(shortcut: &str, backend: &TypingBackend) -> Self {
+        Self {
+            shortcut: shortcut.to_string(),
+            backend: backend.clone(),
+        }
+    }
+}
+
+impl OutputSink for PasteSink {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        let shortcut: keyboard::Shortcut = self.shortcut.parse()?;
+        clipboard::paste_text(text, || keyboard::press_shortcut(&shortcut, &self.backend))
+    }
+}
+
+/// Appends text to a file. The path is a template (see `expand_path`) resolved
+/// on every write, so `{date}` rolls over to a new file each day.
+pub struct FileSink {
+    template: String,
+}
+
+impl FileSink {
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+        }
+    }
+}
+
+impl OutputSink for FileSink {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        let path = expand_path(&self.template, &today());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        file.write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Writes text to any writer and flushes it, so a reader sees each piece as
+/// soon as it is confirmed.
+pub struct WriterSink<W: Write + Send> {
+    writer: W,
+}
+
+impl<W: Write + Send> WriterSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write + Send> OutputSink for WriterSink<W> {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        self.writer
+            .write_all(text.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Failed to write output: {}", e))
+    }
+}
+
+pub type StdoutSink = WriterSink<std::io::Stdout>;
+
+pub fn stdout_sink() -> StdoutSink {
+    WriterSink::new(std::io::stdout())
+}
+
+/// Pushes text to a local Unix socket or named pipe owned by another program.
+/// Connects lazily and reconnects after a failed write, so the listener can
+/// start or restart while dictation is running.
+pub struct SocketSink {
+    path: PathBuf,
+    conn: Option<Box<dyn Write + Send>>,
+}
+
+impl SocketSink {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: expand_path(path, &today()),
+            conn: None,
+        }
+    }
+
+    fn connect(&self) -> Result<Box<dyn Write + Send>, String> {
+        let is_fifo = std::fs::metadata(&self.path)
+            .map(|m| m.file_type().is_fifo())
+            .unwrap_or(false);
+        if is_fifo {
+            // Non-blocking open fails with ENXIO when no reader has the pipe
+            // open, instead of stalling dictation until one appears.
+            let pipe = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&self.path)
+                .map_err(|e| format!("No reader on {}: {}", self.path.display(), e))?;
+            Ok(Box::new(pipe))
+        } else {
+            let stream = UnixStream::connect(&self.path)
+                .map_err(|e| format!("No listener on {}: {}", self.path.display(), e))?;
+            Ok(Box::new(stream))
+        }
+    }
+}
+
+impl OutputSink for SocketSink {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        if let Some(conn) = &mut self.conn {
+            if conn
+                .write_all(text.as_bytes())
+                .and_then(|_| conn.flush())
+                .is_ok()
+            {
+                return Ok(());
+            }
+            // The listener went away; retry once on a fresh connection.
+            self.conn = None;
+        }
+        let mut conn = self.connect()?;
+        conn.write_all(text.as_bytes())
+            .and_then(|_| conn.flush())
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+        self.conn = Some(conn);
+        Ok(())
+    }
+}
+
+/// Writes to every sink in order. A failing sink does not stop the others;
+/// its error is reported after all sinks have run.
+pub struct MultiSink {
+    sinks: Vec<Box<dyn OutputSink>>,
+}
+
+impl MultiSink {
+    pub fn new(sinks: Vec<Box<dyn OutputSink>>) -> Self {
+        Self { sinks }
+    }
+}
+
+impl OutputSink for MultiSink {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        let errors: Vec<String> = self
+            .sinks
+            .iter_mut()
+            .filter_map(|sink| sink.write(text).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// Per-mode settings: the file and socket locations used by the `File` and
+/// `Socket` modes, the keystroke backend used for typing and pasting, and the
+/// `Paste` shortcut.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTargets {
+    pub file: String,
+    pub socket: String,
+    pub typing: TypingBackend,
+    pub paste_shortcut: String,
+}
+
+impl OutputTargets {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            file: config.output_file.clone(),
+            socket: config.output_socket.clone(),
+            typing: config.typing_backend.clone(),
+            paste_shortcut: config.paste_shortcut.clone(),
+        }
+    }
+}
+
+/// The sink for a single output mode.
+pub fn sink_for(mode: &OutputMode, targets: &OutputTargets) -> Box<dyn OutputSink> {
+    match mode {
+        OutputMode::TypeIntoField => Box::new(KeyboardSink::new(&targets.typing)),
+        OutputMode::Clipboard => Box::new(ClipboardSink),
+        OutputMode::Both => Box::new(MultiSink::new(vec![
+            Box::new(KeyboardSink::new(&targets.typing)),
+            Box::new(ClipboardSink),
+        ])),
+        OutputMode::File => Box::new(FileSink::new(&targets.file)),
+        OutputMode::Stdout => Box::new(stdout_sink()),
+        OutputMode::Socket => Box::new(SocketSink::new(&targets.socket)),
+        OutputMode::Paste => Box::new(PasteSink::new(&targets.paste_shortcut, &targets.typing)),
+    }
+}
+
+/// `output_mode` plus each of `additional_outputs`, skipping duplicates.
+pub fn modes(config: &Config) -> Vec<OutputMode> {
+    let mut modes = vec![config.output_mode.clone()];
+    for mode in &config.additional_outputs {
+        if !modes.contains(mode) {
+            modes.push(mode.clone());
+        }
+    }
+    modes
+}
+
+/// The combined sink for the configured output modes.
+pub fn from_config(config: &Config) -> Box<dyn OutputSink> {
+    from_modes(&modes(config), &OutputTargets::from_config(config))
+}
+
+/// The combined sink for `modes`; with no modes, text is discarded.
+pub fn from_modes(modes: &[OutputMode], targets: &OutputTargets) -> Box<dyn OutputSink> {
+    let mut sinks: Vec<Box<dyn OutputSink>> =
+        modes.iter().map(|mode| sink_for(mode, targets)).collect();
+    if sinks.len() == 1 {
+        sinks.remove(0)
+    } else {
+        Box::new(MultiSink::new(sinks))
+    }
+}
+
+/// Expand a leading `~/` to the home directory and `{date}` to `date`.
+pub fn expand_path(template: &str, date: &str) -> PathBuf {
+    let expanded = template.replace("{date}", date);
+    match expanded.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(expanded),
+    }
+}
+
+/// Local date as `YYYY-MM-DD`.
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::os::unix::net::UnixListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Records writes; optionally fails every write.
+    struct Recording {
+        log: Arc<Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl OutputSink for Recording {
+        fn write(&mut self, text: &str) -> Result<(), String> {
+            self.log.lock().unwrap().push(text.to_string());
+            if self.fail {
+                Err(format!("failed on {:?}", text))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    // --- Path Tests ---
+
+    #[test]
+    fn test_expand_path() {
+        assert_eq!(
+            expand_path("/notes/{date}.md", "2026-10-18"),
+            PathBuf::from("/notes/2026-10-18.md")
+        );
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_path("~/out.txt", "x"), home.join("out.txt"));
+        assert_eq!(expand_path("rel/~/x", "x"), PathBuf::from("rel/~/x"));
+    }
+
+    // --- Sink Tests ---
+
+    #[test]
+    fn test_file_sink_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = format!("{}/nested/{{date}}.txt", dir.path().display());
+        let mut sink = FileSink::new(&template);
+        sink.write("hello").unwrap();
+        sink.write(" world").unwrap();
+        let path = expand_path(&template, &today());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "hello world");
+    }
+
+    #[test]
+    fn test_writer_sink() {
+        let mut sink = WriterSink::new(Vec::new());
+        sink.write("one").unwrap();
+        sink.write(" two").unwrap();
+        assert_eq!(sink.writer, b"one two");
+    }
+
+    #[test]
+    fn test_socket_sink_delivers_and_reconnects() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.sock");
+        let mut sink = SocketSink::new(path.to_str().unwrap());
+        assert!(sink.write("nobody listening").is_err());
+
+        let listener = UnixListener::bind(&path).unwrap();
+        sink.write("first").unwrap();
+        let (mut conn, _) = listener.accept().unwrap();
+        drop(listener);
+        let mut buf = [0u8; 5];
+        conn.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"first");
+
+        // The listener restarts; the sink reconnects after the broken write.
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+        let listener = UnixListener::bind(&path).unwrap();
+        let reader = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut text = String::new();
+            conn.read_to_string(&mut text).unwrap();
+            text
+        });
+        sink.write("second").unwrap();
+        drop(sink);
+        assert_eq!(reader.join().unwrap(), "second");
+    }
+
+    #[test]
+    fn test_socket_sink_fifo_without_reader_fails_fast() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.fifo");
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let mut sink = SocketSink::new(path.to_str().unwrap());
+        let err = sink.write("text").unwrap_err();
+        assert!(err.starts_with("No reader on"), "{}", err);
+    }
+
+    #[test]
+    fn test_multi_sink_writes_all_and_joins_errors() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = |fail| {
+            Box::new(Recording {
+                log: log.clone(),
+                fail,
+            }) as Box<dyn OutputSink>
+        };
+        let mut multi = MultiSink::new(vec![sink(true), sink(false), sink(true)]);
+        let err = multi.write("hi").unwrap_err();
+        assert_eq!(
+            log.lock().unwrap().len(),
+            3,
+            "a failure does not stop later sinks"
+        );
+        assert_eq!(err, "failed on \"hi\"; failed on \"hi\"");
+    }
+
+    #[test]
+    fn test_paste_sink_rejects_bad_shortcut() {
+        let mut sink = PasteSink::new("Ctrl+F5", &TypingBackend::Auto);
+        let err = sink.write("hi").unwrap_err();
+        assert!(err.contains("Unsupported key 'F5'"), "{}", err);
+    }
+
+    // --- Config Tests ---
+
+    #[test]
+    fn test_modes_from_config_deduplicates() {
+        let config = Config {
+            output_mode: OutputMode::TypeIntoField,
+            additional_outputs: vec![
+                OutputMode::File,
+                OutputMode::TypeIntoField,
+                OutputMode::Socket,
+                OutputMode::File,
+            ],
+            ..Config::default()
+        };
+        assert_eq!(
+            modes(&config),
+            vec![
+                OutputMode::TypeIntoField,
+                OutputMode::File,
+                OutputMode::Socket
+            ]
+        );
+        assert_eq!(modes(&Config::default()), vec![OutputMode::Both]);
+    }
+
+    #[test]
+    fn test_from_config_file_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            output_mode: OutputMode::File,
+            output_file: format!("{}/notes.md", dir.path().display()),
+            ..Config::default()
+        };
+        from_config(&config).write("logged").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("notes.md")).unwrap(),
+            "logged"
+        );
+    }
+}
@@ -49,6 +49,17 @@ impl LocalAgreement {
             };
         }
 
+        // A pass shorter than the confirmed words (e.g. a hypothesis cut
+        // short) confirms nothing; keep the confirmed words as the previous
+        // pass so finalize has nothing left over.
+        if curr_words.len() < self.confirmed_count {
+            self.prev_words.truncate(self.confirmed_count);
+            return AgreementResult {
+                newly_confirmed: String::new(),
+                tentative: String::new(),
+            };
+        }
+
         // Find longest common prefix starting from confirmed_count
         let mut lcp_end = self.confirmed_count;
         while lcp_end < curr_words.len() && lcp_end < self.prev_words.len() {
@@ -292,6 +303,17 @@ mod tests {
         assert_eq!(result.tentative, "");
     }
 
+    #[test]
+    fn test_pass_shorter_than_confirmed_words() {
+        let mut la = LocalAgreement::new();
+        la.process("one two three");
+        la.process("one two three four");
+        let result = la.process("one");
+        assert_eq!(result.newly_confirmed, "");
+        assert_eq!(result.tentative, "");
+        assert_eq!(la.finalize(), "");
+    }
+
     #[test]
     fn test_identical_consecutive_passes() {
         let mut la = LocalAgreement::new();
@@ -1,17 +1,27 @@
 //! Whisper inference engine wrapping whisper-rs. Manages model loading/unloading
 //! and runs greedy transcription on 16kHz f32 audio chunks.
 
+use super::options::{EngineMode, EngineOptions};
+use super::streaming::Recognizer;
 use std::path::Path;
+use std::sync::Arc;
 use std::sync::Mutex;
 use whisper_rs::{
     FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
 };
 
 pub struct TranscriptionEngine {
-    ctx: Mutex<Option<WhisperContext>>,
+    /// Shared so `transcribe_file` can run without holding the lock.
+    ctx: Mutex<Option<Arc<WhisperContext>>>,
     active_model: Mutex<Option<String>>,
+    /// How the active model was loaded; `transcribe` takes its thread count
+    /// from here.
+    mode: Mutex<Option<EngineMode>>,
 }
 
+/// Threads used if `transcribe` runs without a recorded mode.
+const DEFAULT_THREADS: usize = 4;
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TranscriptionSegment {
     pub text: String,
@@ -30,10 +40,19 @@ impl TranscriptionEngine {
         Self {
             ctx: Mutex::new(None),
             active_model: Mutex::new(None),
+            mode: Mutex::new(None),
         }
     }
 
-    pub fn load_model(&self, model_path: &Path, model_id: &str) -> Result<(), String> {
+    /// Load a GGML model with `options`, returning the mode it ended up in.
+    /// With flash attention requested, a context that fails to load is retried
+    /// without it.
+    pub fn load_model(
+        &self,
+        model_path: &Path,
+        model_id: &str,
+        options: &EngineOptions,
+    ) -> Result<EngineMode, String> {
         // Drop existing context first to free VRAM
         {
             let mut ctx = self.ctx.lock().map_err(|e| e.to_string())?;
@@ -42,43 +61,46 @@ impl TranscriptionEngine {
 
         let path_str = model_path.to_str().ok_or("Invalid model path")?;
 
-        // Try with flash attention enabled first, fall back if it fails
-        let new_ctx = {
+        let mut last_error = String::new();
+        let mut loaded = None;
+        for &flash_attn in options.flash_attn_attempts() {
             let mut params = WhisperContextParameters::default();
-            params.use_gpu(true);
-            params.flash_attn(true);
+            params.use_gpu(options.use_gpu);
+            params.gpu_device(options.gpu_device);
+            params.flash_attn(flash_attn);
 
             match WhisperContext::new_with_params(path_str, params) {
                 Ok(ctx) => {
-                    eprintln!("whisper: context created with flash_attn=true");
-                    ctx
+                    loaded = Some((ctx, options.mode(flash_attn)));
+                    break;
                 }
                 Err(e) => {
                     eprintln!(
-                        "whisper: flash_attn failed ({}), falling back to non-flash",
-                        e
+                        "whisper: context with flash_attn={} failed ({})",
+                        flash_attn, e
                     );
-                    let mut fallback_params = WhisperContextParameters::default();
-                    fallback_params.use_gpu(true);
-
-                    let ctx = WhisperContext::new_with_params(path_str, fallback_params).map_err(
-                        |e| format!("Failed to load whisper model '{}': {}", model_id, e),
-                    )?;
-                    eprintln!("whisper: context created with flash_attn=false (fallback)");
-                    ctx
+                    last_error = e.to_string();
                 }
             }
-        };
+        }
+        let (new_ctx, mode) = loaded.ok_or_else(|| {
+            format!(
+                "Failed to load whisper model '{}': {}",
+                model_id, last_error
+            )
+        })?;
+        eprintln!("whisper: context created with {:?}", mode);
 
         {
             let mut ctx = self.ctx.lock().map_err(|e| e.to_string())?;
-            *ctx = Some(new_ctx);
+            *ctx = Some(Arc::new(new_ctx));
         }
         {
             let mut active = self.active_model.lock().map_err(|e| e.to_string())?;
             *active = Some(model_id.to_string());
         }
-        Ok(())
+        *self.mode.lock().map_err(|e| e.to_string())? = Some(mode.clone());
+        Ok(mode)
     }
 
     /// Unload the model and free VRAM. SAFETY: The transcription thread must be
@@ -89,6 +111,7 @@ impl TranscriptionEngine {
         *ctx = None;
         let mut active = self.active_model.lock().map_err(|e| e.to_string())?;
         *active = None;
+        *self.mode.lock().map_err(|e| e.to_string())? = None;
         Ok(())
     }
 
@@ -96,6 +119,11 @@ impl TranscriptionEngine {
         self.active_model.lock().ok().and_then(|m| m.clone())
     }
 
+    /// Device, flash-attention and thread settings of the loaded model.
+    pub fn get_engine_mode(&self) -> Option<EngineMode> {
+        self.mode.lock().ok().and_then(|m| m.clone())
+    }
+
     pub fn is_loaded(&self) -> bool {
         self.ctx.lock().map(|c| c.is_some()).unwrap_or(false)
     }
@@ -138,6 +166,10 @@ impl TranscriptionEngine {
         params.set_suppress_blank(true);
         params.set_suppress_nst(true);
         params.set_no_context(true);
+        let n_threads = self
+            .get_engine_mode()
+            .map_or(DEFAULT_THREADS, |m| m.n_threads);
+        params.set_n_threads(n_threads.max(1) as i32);
 
         #[cfg(debug_assertions)]
         let start = std::time::Instant::now();
@@ -154,33 +186,118 @@ impl TranscriptionEngine {
             audio_data.len() as f64 / 16000.0
         );
 
-        let num_segments = state.full_n_segments();
+        collect_segments(state)
+    }
+
+    /// Transcribe a whole recording with segment timestamps (in centiseconds),
+    /// e.g. an uploaded file. Uses a temporary state on a clone of the context,
+    /// so the lock is held only to take the clone: dictation can start and the
+    /// model can be unloaded meanwhile, and the context this request uses
+    /// stays alive until it finishes.
+    pub fn transcribe_file(
+        &self,
+        audio_data: &[f32],
+        language: &str,
+        prompt: Option<&str>,
+    ) -> Result<Vec<TranscriptionSegment>, String> {
+        let ctx = self
+            .ctx
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("No model loaded. Load a model before transcribing.")?;
+        // Declared after `ctx`, so the state drops first.
+        let mut state = ctx
+            .create_state()
+            .map_err(|e| format!("Failed to create whisper state: {}", e))?;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        if language != "auto" {
+            params.set_language(Some(language));
+        }
+        if let Some(prompt) = prompt {
+            params.set_initial_prompt(prompt);
+        }
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_suppress_blank(true);
+        params.set_suppress_nst(true);
+        let n_threads = self
+            .get_engine_mode()
+            .map_or(DEFAULT_THREADS, |m| m.n_threads);
+        params.set_n_threads(n_threads.max(1) as i32);
 
-        let mut segments = Vec::new();
-        for i in 0..num_segments {
-            let segment = state
-                .get_segment(i)
-                .ok_or_else(|| format!("Segment {} out of bounds", i))?;
+        state
+            .full(params, audio_data)
+            .map_err(|e| format!("Transcription failed: {}", e))?;
+        collect_segments(&state)
+    }
+}
 
-            let text = segment
-                .to_str()
-                .map_err(|e| format!("Failed to get segment text: {}", e))?;
+fn collect_segments(state: &WhisperState) -> Result<Vec<TranscriptionSegment>, String> {
+    let num_segments = state.full_n_segments();
 
-            let trimmed = text.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
+    let mut segments = Vec::new();
+    for i in 0..num_segments {
+        let segment = state
+            .get_segment(i)
+            .ok_or_else(|| format!("Segment {} out of bounds", i))?;
 
-            let start = segment.start_timestamp();
-            let end = segment.end_timestamp();
+        let text = segment
+            .to_str()
+            .map_err(|e| format!("Failed to get segment text: {}", e))?;
 
-            segments.push(TranscriptionSegment {
-                text: trimmed.to_string(),
-                start,
-                end,
-            });
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            continue;
         }
 
-        Ok(segments)
+        let start = segment.start_timestamp();
+        let end = segment.end_timestamp();
+
+        segments.push(TranscriptionSegment {
+            text: trimmed.to_string(),
+            start,
+            end,
+        });
+    }
+
+    Ok(segments)
+}
+
+/// A Whisper inference state bound to an engine, usable as a streaming
+/// `Recognizer`. Create it after the model is loaded; like any state it must be
+/// dropped before the model is unloaded.
+pub struct WhisperRecognizer {
+    // Declared first so the state drops before the engine reference.
+    state: WhisperState,
+    engine: Arc<TranscriptionEngine>,
+    language: String,
+}
+
+impl WhisperRecognizer {
+    pub fn new(engine: Arc<TranscriptionEngine>, language: &str) -> Result<Self, String> {
+        Ok(Self {
+            state: engine.create_inference_state()?,
+            engine,
+            language: language.to_string(),
+        })
+    }
+}
+
+impl Recognizer for WhisperRecognizer {
+    fn transcribe(&mut self, audio: &[f32]) -> Result<String, String> {
+        let segments = self
+            .engine
+            .transcribe(&mut self.state, audio, &self.language)?;
+        Ok(segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim()
+            .to_string())
     }
 }
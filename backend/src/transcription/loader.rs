@@ -0,0 +1,189 @@
+//! Tracks in-progress model loads so dictation can queue behind them instead
+//! of failing with "No model loaded".
+
+use super::options::EngineMode;
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelLoadStatus {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+/// Payload of the `model-load` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelLoadEvent {
+    pub model_id: String,
+    pub status: ModelLoadStatus,
+    /// Time spent loading (0 while `loading`).
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+    /// How the model is running (set when `loaded`).
+    pub engine: Option<EngineMode>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Loads started but not finished (a second load waits on `serial`).
+    pending: usize,
+    /// Most recently requested model.
+    model_id: Option<String>,
+    /// Dictation was requested while loading; start it once the load succeeds.
+    start_queued: bool,
+}
+
+#[derive(Default)]
+pub struct ModelLoadTracker {
+    inner: Mutex<Inner>,
+    serial: Mutex<()>,
+}
+
+impl ModelLoadTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a load as started.
+    pub fn begin(&self, model_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pending += 1;
+        inner.model_id = Some(model_id.to_string());
+    }
+
+    /// Held for the duration of the actual load so two loads never race on the
+    /// engine's context.
+    pub fn serialize(&self) -> MutexGuard<'_, ()> {
+        self.serial.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Mark a load as finished. Returns true if dictation was queued and this was
+    /// the last pending load; the queued request is consumed either way once no
+    /// loads remain.
+    pub fn finish(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pending = inner.pending.saturating_sub(1);
+        if inner.pending > 0 {
+            return false;
+        }
+        inner.model_id = None;
+        std::mem::take(&mut inner.start_queued)
+    }
+
+    /// The model currently being loaded, if any.
+    pub fn loading_model(&self) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        if inner.pending > 0 {
+            inner.model_id.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Toggle a queued dictation start. Returns `None` if nothing is loading
+    /// (the caller should start right away), otherwise whether a start is now
+    /// queued.
+    pub fn toggle_queued_start(&self) -> Option<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pending == 0 {
+            return None;
+        }
+        inner.start_queued = !inner.start_queued;
+        Some(inner.start_queued)
+    }
+
+    /// Queue a dictation start if a model is loading. Returns false if nothing
+    /// is loading (the caller should start right away). Unlike
+    /// `toggle_queued_start`, a second call leaves the start queued.
+    pub fn queue_start(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pending == 0 {
+            return false;
+        }
+        inner.start_queued = true;
+        true
+    }
+
+    /// Drop a queued start (e.g. on an explicit stop).
+    pub fn cancel_queued_start(&self) -> bool {
+        std::mem::take(&mut self.inner.lock().unwrap().start_queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_tracker_does_not_queue() {
+        let tracker = ModelLoadTracker::new();
+        assert_eq!(tracker.loading_model(), None);
+        assert_eq!(tracker.toggle_queued_start(), None);
+    }
+
+    #[test]
+    fn test_toggle_during_load_queues_start() {
+        let tracker = ModelLoadTracker::new();
+        tracker.begin("large-v3");
+        assert_eq!(tracker.loading_model().as_deref(), Some("large-v3"));
+        assert_eq!(tracker.toggle_queued_start(), Some(true));
+        assert!(tracker.finish(), "queued start is handed to the loader");
+        assert_eq!(tracker.loading_model(), None);
+        assert!(!tracker.finish(), "consumed");
+    }
+
+    #[test]
+    fn test_second_toggle_cancels_queued_start() {
+        let tracker = ModelLoadTracker::new();
+        tracker.begin("base");
+        assert_eq!(tracker.toggle_queued_start(), Some(true));
+        assert_eq!(tracker.toggle_queued_start(), Some(false));
+        assert!(!tracker.finish());
+    }
+
+    #[test]
+    fn test_queued_start_waits_for_last_load() {
+        let tracker = ModelLoadTracker::new();
+        tracker.begin("tiny");
+        tracker.begin("base");
+        tracker.toggle_queued_start();
+        assert!(!tracker.finish(), "one load still pending");
+        assert_eq!(tracker.loading_model().as_deref(), Some("base"));
+        assert!(tracker.finish());
+    }
+
+    #[test]
+    fn test_queue_start_is_idempotent() {
+        let tracker = ModelLoadTracker::new();
+        assert!(!tracker.queue_start(), "nothing loading");
+        tracker.begin("base");
+        assert!(tracker.queue_start());
+        assert!(tracker.queue_start());
+        assert!(tracker.finish(), "two starts leave the start queued");
+    }
+
+    #[test]
+    fn test_cancel_queued_start() {
+        let tracker = ModelLoadTracker::new();
+        tracker.begin("tiny");
+        tracker.toggle_queued_start();
+        assert!(tracker.cancel_queued_start());
+        assert!(!tracker.finish());
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let event = ModelLoadEvent {
+            model_id: "tiny".to_string(),
+            status: ModelLoadStatus::Failed,
+            elapsed_ms: 12,
+            error: Some("boom".to_string()),
+            engine: None,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["error"], "boom");
+    }
+}
@@ -1,5 +1,9 @@
 pub mod agreement;
 pub mod engine;
+pub mod loader;
 pub mod models;
 pub mod moonshine;
+pub mod options;
+pub mod session;
+pub mod streaming;
 pub use models::{get_model_registry, ModelType, WhisperModel};
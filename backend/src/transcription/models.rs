@@ -1,8 +1,15 @@
 //! Static registry of available ASR models (Whisper GGML + Moonshine ONNX)
-//! with HuggingFace download URLs and size metadata.
+//! with HuggingFace download URLs and size metadata, plus user-registered
+//! custom GGML models loaded from `Config::custom_models`.
+//!
+//! The built-in entries can be overridden or extended by a remote catalog
+//! (see `model_manager::catalog`), and HuggingFace URLs can be rewritten to
+//! point at a mirror (`Config::model_mirror`).
 
+use crate::config::{Config, CustomModel};
 use serde::{Deserialize, Serialize};
-use std::sync::LazyLock;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -28,8 +35,79 @@ pub struct WhisperModel {
     /// Empty for single-file models (Whisper GGML).
     #[serde(default)]
     pub files: Vec<(String, String)>,
+    /// Expected SHA-256 of the model file (lowercase hex), verified after download.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Absolute location for custom models used in place outside `models_dir()`.
+    #[serde(default)]
+    pub local_path: Option<PathBuf>,
+    /// True for user-registered models (see `Config::custom_models`).
+    #[serde(default)]
+    pub custom: bool,
 }
 
+/// Language codes accepted by multilingual Whisper models.
+pub const WHISPER_LANGUAGES: &[&str] = &[
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it",
+    "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur",
+    "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr", "az", "sl", "kn",
+    "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw", "gl", "mr", "pa", "si",
+    "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln",
+    "ha", "ba", "jw", "su", "yue",
+];
+
+impl WhisperModel {
+    /// Languages the model can transcribe. Moonshine, distilled and `.en`
+    /// Whisper models are English-only.
+    pub fn languages(&self) -> &'static [&'static str] {
+        let english_only = self.model_type == ModelType::MoonshineOnnx
+            || self.id.starts_with("distil-")
+            || self.filename.contains(".en.");
+        if english_only {
+            &WHISPER_LANGUAGES[..1]
+        } else {
+            WHISPER_LANGUAGES
+        }
+    }
+
+    /// On-disk location of the model file (or directory, for multi-file models).
+    pub fn model_path(&self) -> PathBuf {
+        self.local_path
+            .clone()
+            .unwrap_or_else(|| Config::models_dir().join(&self.filename))
+    }
+
+    /// Build the registry entry for a user-registered GGML model. `source` is
+    /// either an `http(s)://` URL (downloaded into `models_dir()` as
+    /// `custom-<id>.bin`) or a local file path used in place.
+    pub fn from_custom(custom: &CustomModel) -> Self {
+        let (url, local_path, size_bytes) = if custom.is_url() {
+            (custom.source.clone(), None, 0)
+        } else {
+            let path = PathBuf::from(&custom.source);
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            (String::new(), Some(path), size)
+        };
+        Self {
+            id: custom.id.clone(),
+            display_name: custom.display_name.clone(),
+            filename: format!("custom-{}.bin", custom.id),
+            url,
+            size_bytes,
+            vram_mb: 0, // unknown
+            model_type: ModelType::WhisperGgml,
+            files: vec![],
+            sha256: custom.sha256.clone(),
+            local_path,
+            custom: true,
+        }
+    }
+}
+
+/// Prefix rewritten by `apply_mirror`.
+pub const HF_BASE: &str = "https://huggingface.co/";
+
 const HF_MOONSHINE: &str =
     "https://huggingface.co/UsefulSensors/moonshine/resolve/main/onnx/merged";
 
@@ -46,6 +124,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 1000,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "base".to_string(),
@@ -57,6 +138,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 1000,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "small".to_string(),
@@ -68,6 +152,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 1500,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "medium".to_string(),
@@ -79,6 +166,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 3000,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "large-v3".to_string(),
@@ -90,6 +180,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 6000,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "distil-large-v3".to_string(),
@@ -101,6 +194,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 2000,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "large-v3-turbo".to_string(),
@@ -112,6 +208,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
             vram_mb: 2500,
             model_type: ModelType::WhisperGgml,
             files: vec![],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         // --- Moonshine ONNX models ---
         WhisperModel {
@@ -136,6 +235,9 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
                     format!("{}/base/float/tokenizer.json", HF_MOONSHINE),
                 ),
             ],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
         WhisperModel {
             id: "moonshine-base".to_string(),
@@ -159,14 +261,94 @@ static MODEL_REGISTRY: LazyLock<Vec<WhisperModel>> = LazyLock::new(|| {
                     format!("{}/base/float/tokenizer.json", HF_MOONSHINE),
                 ),
             ],
+            sha256: None,
+            local_path: None,
+            custom: false,
         },
     ]
 });
 
+/// User-registered models, mirrored from `Config::custom_models`.
+static CUSTOM_MODELS: LazyLock<RwLock<Vec<WhisperModel>>> =
+    LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Remote catalog (replaces the built-in list when set) and mirror template.
+#[derive(Default)]
+struct RemoteRegistry {
+    catalog: Option<Vec<WhisperModel>>,
+    mirror: Option<String>,
+}
+
+static REMOTE: LazyLock<RwLock<RemoteRegistry>> =
+    LazyLock::new(|| RwLock::new(RemoteRegistry::default()));
+
+/// The compiled-in registry, ignoring any catalog or mirror.
 pub fn get_model_registry() -> &'static [WhisperModel] {
     &MODEL_REGISTRY
 }
 
+/// Replace the built-in entries with a merged catalog (see
+/// `model_manager::catalog::merge_catalog`). `None` restores the built-ins.
+pub fn set_catalog(models: Option<Vec<WhisperModel>>) {
+    REMOTE.write().unwrap().catalog = models;
+}
+
+/// Set the mirror used to rewrite HuggingFace download URLs.
+pub fn set_mirror(mirror: Option<String>) {
+    REMOTE.write().unwrap().mirror = mirror.filter(|m| !m.trim().is_empty());
+}
+
+/// Rewrite a HuggingFace URL onto a mirror. A template containing `{path}` has it
+/// replaced by the part after `https://huggingface.co/`; any other template is
+/// treated as a base URL and the path is appended. Other URLs are unchanged.
+pub fn apply_mirror(url: &str, mirror: &str) -> String {
+    let Some(path) = url.strip_prefix(HF_BASE) else {
+        return url.to_string();
+    };
+    if mirror.contains("{path}") {
+        mirror.replace("{path}", path)
+    } else {
+        format!("{}/{}", mirror.trim_end_matches('/'), path)
+    }
+}
+
+/// Built-in (or catalog) models with the mirror applied to their URLs.
+pub fn base_models() -> Vec<WhisperModel> {
+    let remote = REMOTE.read().unwrap();
+    let mut models = remote
+        .catalog
+        .clone()
+        .unwrap_or_else(|| get_model_registry().to_vec());
+    if let Some(mirror) = &remote.mirror {
+        for model in &mut models {
+            model.url = apply_mirror(&model.url, mirror);
+            for (_, url) in &mut model.files {
+                *url = apply_mirror(url, mirror);
+            }
+        }
+    }
+    models
+}
+
+/// Replace the custom-model part of the registry. Called at startup and whenever
+/// `Config::custom_models` changes.
+pub fn set_custom_models(custom: &[CustomModel]) {
+    let models = custom.iter().map(WhisperModel::from_custom).collect();
+    *CUSTOM_MODELS.write().unwrap() = models;
+}
+
+/// Built-in (or catalog) registry followed by custom models.
+pub fn all_models() -> Vec<WhisperModel> {
+    let mut models = base_models();
+    models.extend(CUSTOM_MODELS.read().unwrap().iter().cloned());
+    models
+}
+
+/// Look up a built-in or custom model by ID.
+pub fn find_model(model_id: &str) -> Option<WhisperModel> {
+    all_models().into_iter().find(|m| m.id == model_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,8 +580,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_model_languages() {
+        assert_eq!(find_model("tiny").unwrap().languages().len(), 100);
+        assert_eq!(find_model("distil-large-v3").unwrap().languages(), ["en"]);
+        assert_eq!(find_model("moonshine-base").unwrap().languages(), ["en"]);
+    }
+
     #[test]
     fn test_model_type_default_is_whisper() {
         assert_eq!(ModelType::default(), ModelType::WhisperGgml);
     }
+
+    #[test]
+    fn test_builtin_models_are_not_custom() {
+        for model in get_model_registry() {
+            assert!(!model.custom, "{} should be built-in", model.id);
+            assert!(model.local_path.is_none());
+        }
+    }
+
+    #[test]
+    fn test_from_custom_url_model() {
+        let model = WhisperModel::from_custom(&CustomModel {
+            id: "medical-small".to_string(),
+            display_name: "Medical Small".to_string(),
+            source: "https://models.example.com/ggml-medical-small.bin".to_string(),
+            sha256: Some("ab12".to_string()),
+        });
+        assert!(model.custom);
+        assert_eq!(model.model_type, ModelType::WhisperGgml);
+        assert_eq!(
+            model.url,
+            "https://models.example.com/ggml-medical-small.bin"
+        );
+        assert_eq!(model.filename, "custom-medical-small.bin");
+        assert!(model.local_path.is_none());
+        assert_eq!(model.sha256.as_deref(), Some("ab12"));
+        assert_eq!(
+            model.model_path(),
+            Config::models_dir().join("custom-medical-small.bin")
+        );
+    }
+
+    #[test]
+    fn test_from_custom_local_model_uses_path_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-legal.bin");
+        std::fs::write(&path, vec![0u8; 1234]).unwrap();
+
+        let model = WhisperModel::from_custom(&CustomModel {
+            id: "legal".to_string(),
+            display_name: "Legal".to_string(),
+            source: path.to_string_lossy().to_string(),
+            sha256: None,
+        });
+        assert!(model.url.is_empty());
+        assert_eq!(model.model_path(), path);
+        assert_eq!(model.size_bytes, 1234);
+    }
+
+    #[test]
+    fn test_find_model_covers_builtin_and_custom() {
+        assert!(find_model("tiny").is_some());
+        assert!(find_model("test-find-custom").is_none());
+
+        set_custom_models(&[CustomModel {
+            id: "test-find-custom".to_string(),
+            display_name: "Test".to_string(),
+            source: "https://example.com/ggml-test.bin".to_string(),
+            sha256: None,
+        }]);
+        let found = find_model("test-find-custom").expect("custom model registered");
+        assert!(found.custom);
+        assert!(all_models().len() > get_model_registry().len());
+
+        set_custom_models(&[]);
+        assert!(find_model("test-find-custom").is_none());
+    }
+
+    // --- Mirror Tests ---
+
+    #[test]
+    fn test_apply_mirror_with_path_template() {
+        let url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin";
+        assert_eq!(
+            apply_mirror(url, "https://artifacts.corp/hf/{path}?download=1"),
+            "https://artifacts.corp/hf/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin?download=1"
+        );
+    }
+
+    #[test]
+    fn test_apply_mirror_with_base_url() {
+        let url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin";
+        let expected = "http://127.0.0.1:8080/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin";
+        assert_eq!(apply_mirror(url, "http://127.0.0.1:8080"), expected);
+        assert_eq!(apply_mirror(url, "http://127.0.0.1:8080/"), expected);
+    }
+
+    #[test]
+    fn test_apply_mirror_leaves_other_hosts_alone() {
+        let url = "https://models.internal/ggml-custom.bin";
+        assert_eq!(apply_mirror(url, "https://artifacts.corp/{path}"), url);
+        assert_eq!(apply_mirror("", "https://artifacts.corp/{path}"), "");
+    }
 }
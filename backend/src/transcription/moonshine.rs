@@ -1,6 +1,7 @@
 //! Moonshine ONNX inference engine wrapping transcribe-rs.
 //! Provides fast CPU-only speech-to-text via the Moonshine model family.
 
+use super::streaming::Recognizer;
 use std::path::Path;
 use transcribe_rs::engines::moonshine::{
     ModelVariant, MoonshineEngine as TrMoonshineEngine, MoonshineModelParams,
@@ -73,6 +74,12 @@ impl MoonshineEngine {
     }
 }
 
+impl Recognizer for MoonshineEngine {
+    fn transcribe(&mut self, audio: &[f32]) -> Result<String, String> {
+        MoonshineEngine::transcribe(self, audio)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
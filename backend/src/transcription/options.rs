@@ -0,0 +1,105 @@
+//! Whisper engine options (built from `Config` by `hardware::engine_options`)
+//! and the mode a model was actually loaded with.
+
+use crate::hardware::Device;
+use serde::Serialize;
+
+/// How the Whisper context is created and run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineOptions {
+    pub use_gpu: bool,
+    /// CUDA device index; ignored when `use_gpu` is false.
+    pub gpu_device: i32,
+    /// Try flash attention first, falling back if the context fails to load.
+    pub flash_attn: bool,
+    pub n_threads: usize,
+}
+
+impl EngineOptions {
+    /// Flash-attention settings to try, in order.
+    pub fn flash_attn_attempts(&self) -> &'static [bool] {
+        if self.flash_attn {
+            &[true, false]
+        } else {
+            &[false]
+        }
+    }
+
+    /// The mode resulting from a successful load with `flash_attn`.
+    pub fn mode(&self, flash_attn: bool) -> EngineMode {
+        EngineMode {
+            requested_device: if self.use_gpu {
+                Device::Gpu
+            } else {
+                Device::Cpu
+            },
+            gpu_device: self.use_gpu.then_some(self.gpu_device),
+            flash_attn,
+            flash_attn_fallback: self.flash_attn && !flash_attn,
+            n_threads: self.n_threads,
+        }
+    }
+}
+
+/// How the active model is running, reported to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EngineMode {
+    /// The device the context was created for. whisper.cpp falls back to the
+    /// CPU on its own if the GPU backend fails to initialize and does not
+    /// report it, so this is what was requested, not a guarantee.
+    pub requested_device: Device,
+    /// CUDA device index requested with the GPU.
+    pub gpu_device: Option<i32>,
+    pub flash_attn: bool,
+    /// Flash attention was requested but the model only loaded without it.
+    pub flash_attn_fallback: bool,
+    pub n_threads: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(use_gpu: bool, flash_attn: bool) -> EngineOptions {
+        EngineOptions {
+            use_gpu,
+            gpu_device: 1,
+            flash_attn,
+            n_threads: 6,
+        }
+    }
+
+    #[test]
+    fn test_flash_attn_attempts() {
+        assert_eq!(options(true, true).flash_attn_attempts(), &[true, false]);
+        assert_eq!(options(true, false).flash_attn_attempts(), &[false]);
+    }
+
+    #[test]
+    fn test_mode_gpu() {
+        let mode = options(true, true).mode(true);
+        assert_eq!(mode.requested_device, Device::Gpu);
+        assert_eq!(mode.gpu_device, Some(1));
+        assert!(mode.flash_attn);
+        assert!(!mode.flash_attn_fallback);
+        assert_eq!(mode.n_threads, 6);
+    }
+
+    #[test]
+    fn test_mode_cpu_ignores_gpu_device() {
+        let mode = options(false, false).mode(false);
+        assert_eq!(mode.requested_device, Device::Cpu);
+        assert_eq!(mode.gpu_device, None);
+        assert!(
+            !mode.flash_attn_fallback,
+            "flash attention was not requested"
+        );
+    }
+
+    #[test]
+    fn test_mode_reports_flash_attn_fallback() {
+        let mode = options(true, true).mode(false);
+        assert!(!mode.flash_attn);
+        assert!(mode.flash_attn_fallback);
+    }
+}
@@ -0,0 +1,604 @@
+//! Dictation session driver: pulls VAD-gated audio from a source, runs it
+//! through a `StreamingTranscriber`, sends confirmed text to an output and
+//! reports UI events. The dictation command wires it to the audio pipeline,
+//! the real engines, the sinks from `output::live::from_config` and the Tauri
+//! event bus; tests replay recorded or scripted audio instead.
+
+use super::streaming::{StreamEvent, StreamingTranscriber};
+use crate::audio::AudioMessage;
+use crate::config::FormatRules;
+use crate::output::format::Formatter;
+use crate::output::joiner::Joiner;
+use crate::output::live::LiveText;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+
+/// Where session audio comes from.
+pub trait AudioSource {
+    /// Block for the next message; `None` once the source is finished.
+    fn recv(&mut self) -> Option<AudioMessage>;
+    /// A message that is already queued, without blocking.
+    fn try_recv(&mut self) -> Option<AudioMessage>;
+}
+
+impl AudioSource for mpsc::Receiver<AudioMessage> {
+    fn recv(&mut self) -> Option<AudioMessage> {
+        mpsc::Receiver::recv(self).ok()
+    }
+
+    fn try_recv(&mut self) -> Option<AudioMessage> {
+        mpsc::Receiver::try_recv(self).ok()
+    }
+}
+
+/// Pre-recorded messages grouped into batches. Each batch is what the live
+/// source would have queued between two inference steps.
+pub struct Replay {
+    batches: VecDeque<Vec<AudioMessage>>,
+    queued: VecDeque<AudioMessage>,
+}
+
+impl Replay {
+    /// One inference step per message.
+    pub fn new(messages: Vec<AudioMessage>) -> Self {
+        Self::batched(messages.into_iter().map(|m| vec![m]).collect())
+    }
+
+    pub fn batched(batches: Vec<Vec<AudioMessage>>) -> Self {
+        Self {
+            batches: batches.into_iter().filter(|b| !b.is_empty()).collect(),
+            queued: VecDeque::new(),
+        }
+    }
+}
+
+impl AudioSource for Replay {
+    fn recv(&mut self) -> Option<AudioMessage> {
+        if let Some(message) = self.queued.pop_front() {
+            return Some(message);
+        }
+        self.queued = self.batches.pop_front()?.into();
+        self.queued.pop_front()
+    }
+
+    fn try_recv(&mut self) -> Option<AudioMessage> {
+        self.queued.pop_front()
+    }
+}
+
+/// Events the session reports to the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// `transcription-update`: confirmed (`is_partial: false`) or tentative text.
+    Update { text: String, is_partial: bool },
+    /// `transcription-error`
+    TranscriptionError(String),
+    /// `output-error`
+    OutputError(String),
+    /// `transcript-ready`: the formatted text of a finished utterance.
+    Transcript(String),
+}
+
+impl SessionEvent {
+    /// Tauri event name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Update { .. } => "transcription-update",
+            Self::TranscriptionError(_) => "transcription-error",
+            Self::OutputError(_) => "output-error",
+            Self::Transcript(_) => "transcript-ready",
+        }
+    }
+
+    /// Tauri event payload.
+    pub fn payload(&self) -> serde_json::Value {
+        match self {
+            Self::Update { text, is_partial } => {
+                serde_json::json!({ "text": text, "is_partial": is_partial })
+            }
+            Self::TranscriptionError(message)
+            | Self::OutputError(message)
+            | Self::Transcript(message) => serde_json::Value::String(message.clone()),
+        }
+    }
+}
+
+pub struct DictationSession {
+    transcriber: StreamingTranscriber,
+    formatter: Formatter,
+    joiner: Joiner,
+    live: Option<LiveText>,
+    /// Context for the live text, which differs from the confirmed output
+    /// when Whisper's final transcript corrects it.
+    live_joiner: Joiner,
+    /// Confirmed text output so far in the current utterance.
+    utterance: String,
+}
+
+impl DictationSession {
+    pub fn new(transcriber: StreamingTranscriber) -> Self {
+        Self::with_format(transcriber, FormatRules::default())
+    }
+
+    /// A session whose output is formatted with `rules`.
+    pub fn with_format(transcriber: StreamingTranscriber, rules: FormatRules) -> Self {
+        Self {
+            transcriber,
+            formatter: Formatter::new(rules),
+            joiner: Joiner::new(),
+            live: None,
+            live_joiner: Joiner::new(),
+            utterance: String::new(),
+        }
+    }
+
+    /// Type each hypothesis through `live` and rewrite it as it changes.
+    /// `output` in `run` then only receives confirmed text for other outputs.
+    pub fn with_live(mut self, live: LiveText) -> Self {
+        self.live = Some(live);
+        self
+    }
+
+    /// Run until `source` is finished. Each step takes everything queued so
+    /// far, so a slow recognizer sees larger batches rather than a backlog.
+    /// `output` receives text to type; its errors become `OutputError` events.
+    pub fn run(
+        &mut self,
+        source: &mut impl AudioSource,
+        mut output: impl FnMut(&str) -> Result<(), String>,
+        mut emit: impl FnMut(SessionEvent),
+    ) {
+        while let Some(message) = source.recv() {
+            let mut end_of_speech = self.accept(message);
+            // Drain all queued messages to get latest audio state
+            while let Some(message) = source.try_recv() {
+                end_of_speech |= self.accept(message);
+            }
+
+            for event in self.transcriber.step(end_of_speech) {
+                match event {
+                    StreamEvent::Confirmed(text) => {
+                        let normalized = self.formatter.normalize_piece(&text);
+                        self.confirm(&normalized, &mut output, &mut emit);
+                        emit(SessionEvent::Update {
+                            text,
+                            is_partial: false,
+                        });
+                    }
+                    StreamEvent::Tentative(text) => emit(SessionEvent::Update {
+                        text,
+                        is_partial: true,
+                    }),
+                    StreamEvent::Hypothesis(text) => {
+                        if let Some(live) = &mut self.live {
+                            let joined = self.live_joiner.join(&self.formatter.normalize(&text));
+                            let target = self.formatter.utterance(&joined, end_of_speech);
+                            if let Err(e) = live.show(&target) {
+                                emit(SessionEvent::OutputError(format!("Output error: {}", e)));
+                            }
+                        }
+                    }
+                    StreamEvent::Error(e) => emit(SessionEvent::TranscriptionError(e)),
+                }
+            }
+
+            if end_of_speech {
+                let rest = self.formatter.normalize_rest();
+                self.confirm(&rest, &mut output, &mut emit);
+                let suffix = self.formatter.end_utterance();
+                if !suffix.is_empty() {
+                    if let Err(e) = output(&suffix) {
+                        emit(SessionEvent::OutputError(format!("Output error: {}", e)));
+                    }
+                    self.joiner.push(&suffix);
+                }
+                self.joiner.end_utterance();
+                if let Some(live) = &mut self.live {
+                    self.live_joiner.push(live.typed());
+                    self.live_joiner.end_utterance();
+                    live.commit();
+                }
+                let transcript = std::mem::take(&mut self.utterance);
+                if !transcript.trim().is_empty() {
+                    emit(SessionEvent::Transcript(transcript.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    /// Join, format and output confirmed text.
+    fn confirm(
+        &mut self,
+        text: &str,
+        output: &mut impl FnMut(&str) -> Result<(), String>,
+        emit: &mut impl FnMut(SessionEvent),
+    ) {
+        let typed = self.formatter.piece(&self.joiner.join(text));
+        if typed.is_empty() {
+            return;
+        }
+        self.joiner.push(&typed);
+        self.utterance.push_str(&typed);
+        if let Err(e) = output(&typed) {
+            emit(SessionEvent::OutputError(format!("Output error: {}", e)));
+        }
+    }
+
+    /// Feed a message; returns whether it marks end of speech.
+    fn accept(&mut self, message: AudioMessage) -> bool {
+        match message {
+            AudioMessage::Segment(segment) => {
+                self.transcriber.feed(&segment);
+                false
+            }
+            AudioMessage::EndOfSpeech => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::segmenter::segment_clip;
+    use crate::audio::vad::EnergyVad;
+    use crate::output::live::Keys;
+    use crate::transcription::streaming::Recognizer;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+
+    /// Returns scripted results in order, then empty text, counting calls.
+    struct Scripted {
+        results: VecDeque<Result<String, String>>,
+        calls: Rc<RefCell<usize>>,
+    }
+
+    fn scripted(results: &[Result<&str, &str>]) -> (Box<dyn Recognizer>, Rc<RefCell<usize>>) {
+        let calls = Rc::new(RefCell::new(0));
+        let recognizer = Scripted {
+            results: results
+                .iter()
+                .map(|r| r.map(str::to_string).map_err(str::to_string))
+                .collect(),
+            calls: calls.clone(),
+        };
+        (Box::new(recognizer), calls)
+    }
+
+    impl Recognizer for Scripted {
+        fn transcribe(&mut self, _audio: &[f32]) -> Result<String, String> {
+            *self.calls.borrow_mut() += 1;
+            self.results.pop_front().unwrap_or(Ok(String::new()))
+        }
+    }
+
+    /// Transcribes each second of audio as the next word of a fixed script.
+    struct Words(&'static [&'static str]);
+
+    impl Recognizer for Words {
+        fn transcribe(&mut self, audio: &[f32]) -> Result<String, String> {
+            let n = (audio.len() / 16000).min(self.0.len());
+            Ok(self.0[..n].join(" "))
+        }
+    }
+
+    fn seconds(n: usize) -> AudioMessage {
+        AudioMessage::Segment(vec![0.0; 16000 * n])
+    }
+
+    /// Run a session and collect typed text and emitted events.
+    fn run(
+        transcriber: StreamingTranscriber,
+        source: &mut impl AudioSource,
+        fail_output: bool,
+    ) -> (Vec<String>, Vec<SessionEvent>) {
+        run_session(DictationSession::new(transcriber), source, fail_output)
+    }
+
+    fn run_session(
+        mut session: DictationSession,
+        source: &mut impl AudioSource,
+        fail_output: bool,
+    ) -> (Vec<String>, Vec<SessionEvent>) {
+        let mut typed = Vec::new();
+        let mut events = Vec::new();
+        session.run(
+            source,
+            |text| {
+                typed.push(text.to_string());
+                if fail_output {
+                    Err("no display".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            |event| events.push(event),
+        );
+        (typed, events)
+    }
+
+    fn update(text: &str, is_partial: bool) -> SessionEvent {
+        SessionEvent::Update {
+            text: text.to_string(),
+            is_partial,
+        }
+    }
+
+    // --- Scripted Replay Tests ---
+
+    #[test]
+    fn test_replay_types_confirmed_text_and_emits_events() {
+        let (whisper, _) = scripted(&[
+            Ok("hello there"),
+            Ok("hello there general"),
+            Ok("hello there general kenobi"),
+        ]);
+        let mut source = Replay::new(vec![
+            seconds(1),
+            seconds(1),
+            seconds(1),
+            AudioMessage::EndOfSpeech,
+        ]);
+        let (typed, events) = run(StreamingTranscriber::new(whisper, None), &mut source, false);
+
+        assert_eq!(typed, vec!["hello there", " general", " kenobi"]);
+        assert_eq!(
+            events,
+            vec![
+                update("hello there", true),
+                update("hello there", false),
+                update("general", true),
+                update("general", false),
+                update("kenobi", true),
+                // End of speech: the quality pass returns nothing new, so
+                // finalization confirms the tentative tail.
+                update("kenobi", false),
+                update("", true),
+                SessionEvent::Transcript("hello there general kenobi".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_queued_messages_share_one_step() {
+        let (whisper, calls) = scripted(&[Ok("one"), Ok("one two")]);
+        let mut source = Replay::batched(vec![
+            vec![seconds(1), seconds(1), seconds(1)],
+            vec![AudioMessage::EndOfSpeech],
+        ]);
+        let (typed, _) = run(StreamingTranscriber::new(whisper, None), &mut source, false);
+        assert_eq!(*calls.borrow(), 2, "one stream pass, one quality pass");
+        assert_eq!(typed, vec!["one", " two"]);
+    }
+
+    #[test]
+    fn test_output_failure_is_reported_and_session_continues() {
+        let (whisper, _) = scripted(&[Ok("first"), Ok("first")]);
+        let mut source = Replay::new(vec![seconds(1), seconds(1), AudioMessage::EndOfSpeech]);
+        let (typed, events) = run(StreamingTranscriber::new(whisper, None), &mut source, true);
+        assert_eq!(typed, vec!["first"]);
+        assert!(events.contains(&SessionEvent::OutputError(
+            "Output error: no display".to_string()
+        )));
+        assert!(events.contains(&update("", true)));
+    }
+
+    #[test]
+    fn test_recognizer_errors_become_events() {
+        let (whisper, _) = scripted(&[Err("boom"), Err("still broken")]);
+        let mut source = Replay::new(vec![seconds(1), AudioMessage::EndOfSpeech]);
+        let (typed, events) = run(StreamingTranscriber::new(whisper, None), &mut source, false);
+        assert!(typed.is_empty());
+        assert_eq!(
+            events,
+            vec![
+                SessionEvent::TranscriptionError("Transcription failed: boom".to_string()),
+                SessionEvent::TranscriptionError(
+                    "Whisper quality pass failed: still broken".to_string()
+                ),
+                update("", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dual_path_streams_with_second_recognizer() {
+        let (whisper, whisper_calls) = scripted(&[Ok("quality text")]);
+        let (moonshine, _) = scripted(&[Ok("fast words"), Ok("fast words here")]);
+        let mut source = Replay::new(vec![seconds(1), seconds(1), AudioMessage::EndOfSpeech]);
+        let (typed, _) = run(
+            StreamingTranscriber::new(whisper, Some(moonshine)),
+            &mut source,
+            false,
+        );
+        assert_eq!(typed, vec!["fast words", " here"]);
+        assert_eq!(
+            *whisper_calls.borrow(),
+            1,
+            "whisper only runs at end of speech"
+        );
+    }
+
+    #[test]
+    fn test_format_rules_shape_output() {
+        let (whisper, _) = scripted(&[
+            Ok("Print it"),
+            Ok("Print it"),
+            Ok("Print it"),
+            Ok("Again"),
+            Ok("Again"),
+        ]);
+        let mut source = Replay::new(vec![
+            seconds(1),
+            seconds(1),
+            AudioMessage::EndOfSpeech,
+            seconds(1),
+            AudioMessage::EndOfSpeech,
+        ]);
+        let rules = FormatRules {
+            capitalize: false,
+            suffix: "\n".to_string(),
+            ..FormatRules::default()
+        };
+        let session =
+            DictationSession::with_format(StreamingTranscriber::new(whisper, None), rules);
+        let (typed, events) = run_session(session, &mut source, false);
+        assert_eq!(typed, vec!["print it", "\n", "again", "\n"]);
+        // The UI still shows the recognized text.
+        assert!(events.contains(&update("Print it", false)));
+        // One transcript per utterance, formatted but without the suffix.
+        let transcripts: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                SessionEvent::Transcript(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(transcripts, vec!["print it", "again"]);
+    }
+
+    #[test]
+    fn test_normalization_spans_confirmed_pieces() {
+        let (whisper, _) = scripted(&[
+            Ok("It costs twenty"),
+            Ok("It costs twenty three dollars"),
+            Ok("It costs twenty three dollars."),
+        ]);
+        let mut source = Replay::new(vec![seconds(1), seconds(1), AudioMessage::EndOfSpeech]);
+        let rules = FormatRules {
+            normalize: true,
+            ..FormatRules::default()
+        };
+        let session =
+            DictationSession::with_format(StreamingTranscriber::new(whisper, None), rules);
+        let (typed, _) = run_session(session, &mut source, false);
+        // "twenty" waits for the next piece to see whether the number goes on.
+        assert_eq!(typed, vec!["It costs", " $23."]);
+    }
+
+    // --- Live Rewrite Tests ---
+
+    /// A text field with the cursor at the end.
+    #[derive(Clone, Default)]
+    struct Field(Arc<Mutex<String>>);
+
+    impl Keys for Field {
+        fn replace(&mut self, erase: usize, text: &str) -> Result<(), String> {
+            let mut field = self.0.lock().unwrap();
+            let keep = field.chars().count() - erase;
+            *field = field.chars().take(keep).collect::<String>() + text;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_live_rewrite_types_tentative_text_and_final_corrections() {
+        let (whisper, _) = scripted(&[Ok("Ice cream, please.")]);
+        let (moonshine, _) = scripted(&[Ok("I scream"), Ok("I scream please")]);
+        let field = Field::default();
+        let mut session = DictationSession::with_format(
+            StreamingTranscriber::new(whisper, Some(moonshine)),
+            FormatRules {
+                suffix: "\n".to_string(),
+                ..FormatRules::default()
+            },
+        )
+        .with_live(LiveText::new(Box::new(field.clone())));
+
+        let mut source = Replay::new(vec![seconds(1), seconds(1), AudioMessage::EndOfSpeech]);
+        let mut typed = Vec::new();
+        let mut at_first_confirmation = None;
+        session.run(
+            &mut source,
+            |text| {
+                typed.push(text.to_string());
+                Ok(())
+            },
+            |event| {
+                if let SessionEvent::Update {
+                    is_partial: false, ..
+                } = event
+                {
+                    at_first_confirmation.get_or_insert(field.0.lock().unwrap().clone());
+                }
+            },
+        );
+
+        // The first pass was typed before anything was confirmed.
+        assert_eq!(at_first_confirmation.as_deref(), Some("I scream"));
+        // Whisper's final transcript replaces the stream words.
+        assert_eq!(*field.0.lock().unwrap(), "Ice cream, please.\n");
+        // Other outputs still get confirmed text only.
+        assert_eq!(typed, vec!["I scream", " please", "\n"]);
+    }
+
+    // --- Recorded Audio Tests ---
+
+    /// Two utterances of tone separated by silence, gated by the energy VAD
+    /// exactly as the DSP thread would.
+    fn recorded_clip() -> Vec<AudioMessage> {
+        let tone = |secs: f32| -> Vec<f32> {
+            (0..(16000.0 * secs) as usize)
+                .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * 0.3)
+                .collect()
+        };
+        let mut audio = tone(2.0);
+        audio.extend(vec![0.0; 16000]);
+        audio.extend(tone(3.0));
+        audio.extend(vec![0.0; 16000]);
+        let mut vad = EnergyVad::new(0.012);
+        segment_clip(&audio, 1600, |f| vad.contains_speech(f), || {})
+    }
+
+    #[test]
+    fn test_recorded_audio_through_energy_vad() {
+        let messages = recorded_clip();
+        let ends = messages
+            .iter()
+            .filter(|m| matches!(m, AudioMessage::EndOfSpeech))
+            .count();
+        assert_eq!(ends, 2);
+
+        let words = Words(&["alpha", "beta", "gamma", "delta"]);
+        let mut source = Replay::new(messages);
+        let (typed, events) = run(
+            StreamingTranscriber::new(Box::new(words), None),
+            &mut source,
+            false,
+        );
+        assert_eq!(typed.concat(), "alpha beta alpha beta gamma");
+        assert!(events
+            .iter()
+            .all(|e| !matches!(e, SessionEvent::TranscriptionError(_))));
+    }
+
+    #[test]
+    fn test_channel_source_ends_when_sender_drops() {
+        let (tx, mut rx) = mpsc::channel();
+        tx.send(seconds(1)).unwrap();
+        tx.send(AudioMessage::EndOfSpeech).unwrap();
+        drop(tx);
+        let (whisper, _) = scripted(&[Ok("queued"), Ok("queued")]);
+        let (typed, _) = run(StreamingTranscriber::new(whisper, None), &mut rx, false);
+        // Both messages were already queued, so they form a single end-of-speech step.
+        assert_eq!(typed, vec!["queued"]);
+    }
+
+    // --- Event Tests ---
+
+    #[test]
+    fn test_event_names_and_payloads() {
+        let u = update("hi", true);
+        assert_eq!(u.name(), "transcription-update");
+        assert_eq!(
+            u.payload(),
+            serde_json::json!({ "text": "hi", "is_partial": true })
+        );
+        let e = SessionEvent::OutputError("Output error: x".to_string());
+        assert_eq!(e.name(), "output-error");
+        assert_eq!(e.payload(), serde_json::json!("Output error: x"));
+        let t = SessionEvent::Transcript("Hello there.".to_string());
+        assert_eq!(t.name(), "transcript-ready");
+        assert_eq!(t.payload(), serde_json::json!("Hello there."));
+    }
+}
@@ -0,0 +1,331 @@
+//! Streaming transcription core shared by the dictation thread and the offline
+//! evaluation tool: buffers utterance audio, runs stream passes through
+//! `LocalAgreement` while speech continues, and a Whisper quality pass plus
+//! finalization on end of speech.
+//!
+//! Two recognizers are involved. The quality recognizer (Whisper) always runs
+//! the end-of-speech pass. The optional stream recognizer (Moonshine) handles
+//! passes during speech; without it Whisper does both.
+
+use super::agreement::LocalAgreement;
+
+/// Minimum buffered audio before running inference (1.0s), to avoid
+/// hallucinations on very short input.
+pub const MIN_SAMPLES: usize = 16000;
+/// Maximum utterance window (30s); older audio is dropped.
+pub const MAX_SAMPLES: usize = 16000 * 30;
+
+/// Anything that turns 16kHz mono audio into text.
+pub trait Recognizer {
+    fn transcribe(&mut self, audio: &[f32]) -> Result<String, String>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// Newly confirmed words, without surrounding spaces; the session's
+    /// `Joiner` decides how they attach to the text before them.
+    Confirmed(String),
+    /// Current tentative tail (empty clears the display).
+    Tentative(String),
+    /// The whole utterance as currently understood, for live rewriting:
+    /// confirmed words plus the tentative tail during speech, and Whisper's
+    /// transcript after the quality pass (which may correct confirmed words).
+    Hypothesis(String),
+    /// A pass failed; the message is ready for `transcription-error`.
+    Error(String),
+}
+
+pub struct StreamingTranscriber {
+    quality: Box<dyn Recognizer>,
+    stream: Option<Box<dyn Recognizer>>,
+    audio: Vec<f32>,
+    agreement: LocalAgreement,
+}
+
+impl StreamingTranscriber {
+    pub fn new(quality: Box<dyn Recognizer>, stream: Option<Box<dyn Recognizer>>) -> Self {
+        Self {
+            quality,
+            stream,
+            audio: Vec::new(),
+            agreement: LocalAgreement::new(),
+        }
+    }
+
+    /// Append utterance audio, keeping at most `MAX_SAMPLES`.
+    pub fn feed(&mut self, samples: &[f32]) {
+        self.audio.extend_from_slice(samples);
+        if self.audio.len() > MAX_SAMPLES {
+            let excess = self.audio.len() - MAX_SAMPLES;
+            self.audio.drain(..excess);
+        }
+    }
+
+    /// Run one inference step over the buffered audio: a stream pass, or the
+    /// quality pass and finalization when `end_of_speech` is set.
+    pub fn step(&mut self, end_of_speech: bool) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if self.audio.len() < MIN_SAMPLES {
+            if end_of_speech {
+                self.reset();
+            }
+            return events;
+        }
+
+        if end_of_speech {
+            self.quality_pass(&mut events);
+        } else {
+            self.stream_pass(&mut events);
+        }
+        events
+    }
+
+    /// Drop buffered audio and agreement state.
+    pub fn reset(&mut self) {
+        self.audio.clear();
+        self.agreement.reset();
+    }
+
+    fn stream_pass(&mut self, events: &mut Vec<StreamEvent>) {
+        let text = match &mut self.stream {
+            Some(stream) => stream.transcribe(&self.audio).unwrap_or_default(),
+            None => match self.quality.transcribe(&self.audio) {
+                Ok(text) => text,
+                Err(e) => {
+                    events.push(StreamEvent::Error(format!("Transcription failed: {}", e)));
+                    String::new()
+                }
+            },
+        };
+        if !text.is_empty() {
+            let result = self.agreement.process(&text);
+            self.confirm(result.newly_confirmed, events);
+            let hypothesis = join(self.agreement.confirmed_text(), &result.tentative);
+            events.push(StreamEvent::Hypothesis(hypothesis));
+            events.push(StreamEvent::Tentative(result.tentative));
+        }
+    }
+
+    fn quality_pass(&mut self, events: &mut Vec<StreamEvent>) {
+        // One last stream pass through agreement (dual-path only).
+        if let Some(stream) = &mut self.stream {
+            if let Ok(text) = stream.transcribe(&self.audio) {
+                if !text.is_empty() {
+                    let result = self.agreement.process(&text);
+                    self.confirm(result.newly_confirmed, events);
+                }
+            }
+        }
+
+        // Whisper quality pass on the full utterance.
+        let quality = self.quality.transcribe(&self.audio);
+        if let Ok(text) = &quality {
+            // In whisper-only mode the quality pass also goes through agreement.
+            if self.stream.is_none() && !text.is_empty() {
+                let result = self.agreement.process(text);
+                self.confirm(result.newly_confirmed, events);
+                events.push(StreamEvent::Tentative(result.tentative));
+            }
+        }
+
+        // Finalize: confirm remaining tentative words, even if Whisper failed.
+        let confirmed = self.agreement.confirmed_text().to_string();
+        let remaining = self.agreement.finalize();
+        let hypothesis = match &quality {
+            Ok(text) if !text.trim().is_empty() => text.trim().to_string(),
+            _ => join(&confirmed, &remaining),
+        };
+        self.confirm(remaining, events);
+        if let Err(e) = quality {
+            events.push(StreamEvent::Error(format!(
+                "Whisper quality pass failed: {}",
+                e
+            )));
+        }
+
+        events.push(StreamEvent::Hypothesis(hypothesis));
+        events.push(StreamEvent::Tentative(String::new()));
+        self.audio.clear();
+    }
+
+    fn confirm(&mut self, text: String, events: &mut Vec<StreamEvent>) {
+        if !text.is_empty() {
+            events.push(StreamEvent::Confirmed(text));
+        }
+    }
+}
+
+/// Two word runs separated by a space, either of which may be empty.
+fn join(a: &str, b: &str) -> String {
+    match (a.is_empty(), b.is_empty()) {
+        (false, false) => format!("{} {}", a, b),
+        (true, _) => b.to_string(),
+        (false, true) => a.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Returns scripted results in order, then empty text.
+    struct Scripted(VecDeque<Result<String, String>>);
+
+    impl Scripted {
+        fn boxed(results: &[Result<&str, &str>]) -> Box<dyn Recognizer> {
+            Box::new(Self(
+                results
+                    .iter()
+                    .map(|r| r.map(str::to_string).map_err(str::to_string))
+                    .collect(),
+            ))
+        }
+    }
+
+    impl Recognizer for Scripted {
+        fn transcribe(&mut self, _audio: &[f32]) -> Result<String, String> {
+            self.0.pop_front().unwrap_or(Ok(String::new()))
+        }
+    }
+
+    fn confirmed(events: &[StreamEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::Confirmed(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    static SECOND: [f32; 16000] = [0.0; 16000];
+
+    #[test]
+    fn test_short_audio_is_skipped_and_reset_on_end() {
+        let mut t = StreamingTranscriber::new(Scripted::boxed(&[Ok("never")]), None);
+        t.feed(&SECOND[..8000]);
+        assert!(t.step(false).is_empty());
+        assert!(t.step(true).is_empty());
+        t.feed(&SECOND[..8000]);
+        assert!(t.step(false).is_empty(), "buffer was cleared on end");
+    }
+
+    #[test]
+    fn test_whisper_only_confirms_agreeing_words() {
+        let mut t = StreamingTranscriber::new(
+            Scripted::boxed(&[
+                Ok("hello there"),
+                Ok("hello there general"),
+                Ok("hello there general kenobi"),
+            ]),
+            None,
+        );
+        t.feed(&SECOND);
+        assert_eq!(
+            t.step(false),
+            vec![
+                StreamEvent::Hypothesis("hello there".to_string()),
+                StreamEvent::Tentative("hello there".to_string())
+            ]
+        );
+        t.feed(&SECOND);
+        let events = t.step(false);
+        assert_eq!(confirmed(&events), vec!["hello there"]);
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::Tentative("general".to_string()))
+        );
+
+        t.feed(&SECOND);
+        let events = t.step(true);
+        assert_eq!(confirmed(&events), vec!["general", "kenobi"]);
+        assert_eq!(events.last(), Some(&StreamEvent::Tentative(String::new())));
+    }
+
+    fn hypotheses(events: &[StreamEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::Hypothesis(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hypothesis_covers_whole_utterance() {
+        let mut t = StreamingTranscriber::new(
+            Scripted::boxed(&[Ok("Whisper fixed everything.")]),
+            Some(Scripted::boxed(&[
+                Ok("one two"),
+                Ok("one two three"),
+                Ok("one two three four"),
+            ])),
+        );
+        t.feed(&SECOND);
+        t.step(false);
+        t.feed(&SECOND);
+        assert_eq!(hypotheses(&t.step(false)), vec!["one two three"]);
+
+        // The quality pass replaces the stream words, confirmed or not.
+        t.feed(&SECOND);
+        let events = t.step(true);
+        assert_eq!(confirmed(&events), vec!["three", "four"]);
+        assert_eq!(hypotheses(&events), vec!["Whisper fixed everything."]);
+    }
+
+    #[test]
+    fn test_failed_quality_pass_keeps_stream_hypothesis() {
+        let mut t =
+            StreamingTranscriber::new(Scripted::boxed(&[Ok("good words"), Err("gpu lost")]), None);
+        t.feed(&SECOND);
+        t.step(false);
+        assert_eq!(hypotheses(&t.step(true)), vec!["good words"]);
+    }
+
+    #[test]
+    fn test_dual_path_uses_stream_recognizer_during_speech() {
+        let mut t = StreamingTranscriber::new(
+            Scripted::boxed(&[Ok("ignored in dual path")]),
+            Some(Scripted::boxed(&[Ok("one two"), Ok("one two three")])),
+        );
+        t.feed(&SECOND);
+        t.step(false);
+        t.feed(&SECOND);
+        let events = t.step(true);
+        assert_eq!(confirmed(&events), vec!["one two", "three"]);
+    }
+
+    #[test]
+    fn test_quality_failure_still_finalizes() {
+        let mut t =
+            StreamingTranscriber::new(Scripted::boxed(&[Ok("good words"), Err("gpu lost")]), None);
+        t.feed(&SECOND);
+        t.step(false);
+        let events = t.step(true);
+        assert_eq!(confirmed(&events), vec!["good words"]);
+        assert!(events.contains(&StreamEvent::Error(
+            "Whisper quality pass failed: gpu lost".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_stream_failure_reports_error() {
+        let mut t = StreamingTranscriber::new(Scripted::boxed(&[Err("boom")]), None);
+        t.feed(&SECOND);
+        assert_eq!(
+            t.step(false),
+            vec![StreamEvent::Error("Transcription failed: boom".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_buffer_is_capped() {
+        let mut t = StreamingTranscriber::new(Scripted::boxed(&[]), None);
+        for _ in 0..40 {
+            t.feed(&SECOND);
+        }
+        assert_eq!(t.audio.len(), MAX_SAMPLES);
+    }
+}
@@ -0,0 +1,724 @@
+//! Wyoming ASR server, the protocol Home Assistant uses for speech services.
+//! Each event is a JSON header line (`type`, optional inline `data`,
+//! `data_length`, `payload_length`), followed by `data_length` bytes of JSON
+//! data and `payload_length` bytes of binary payload.
+//!
+//! Clients `describe` the service to get an `info` event listing the loaded
+//! model and its languages, then send `transcribe` (optional `name` and
+//! `language`),
+//! `audio-start`, PCM `audio-chunk`s and `audio-stop`, and receive a
+//! `transcript`. No authentication: bind to localhost unless the network is
+//! trusted.
+
+use crate::audio::wav::TARGET_RATE;
+use crate::audio::{resample, to_mono};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::TcpStream;
+use tokio_util::sync::CancellationToken;
+
+/// Wyoming protocol version sent in event headers.
+pub const PROTOCOL_VERSION: &str = "1.5.2";
+/// Standard port for Wyoming speech-to-text services.
+pub const DEFAULT_PORT: u16 = 10300;
+/// Largest header line, data or payload accepted from a client.
+const MAX_EVENT_BYTES: usize = 4 * 1024 * 1024;
+/// Longest audio stream accepted between `audio-start` and `audio-stop`.
+const MAX_STREAM_SECS: u64 = 300;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub kind: String,
+    pub data: Map<String, Value>,
+    pub payload: Vec<u8>,
+}
+
+impl Event {
+    pub fn new(kind: &str, data: Value) -> Self {
+        Self {
+            kind: kind.to_string(),
+            data: match data {
+                Value::Object(map) => map,
+                _ => Map::new(),
+            },
+            payload: Vec::new(),
+        }
+    }
+
+    fn str(&self, key: &str) -> Option<&str> {
+        self.data.get(key).and_then(Value::as_str)
+    }
+
+    fn u64(&self, key: &str) -> Option<u64> {
+        self.data.get(key).and_then(Value::as_u64)
+    }
+}
+
+/// Read the next event, or `None` at end of stream.
+pub async fn read_event<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Event>, String> {
+    let mut line = Vec::new();
+    let read = (&mut *reader)
+        .take(MAX_EVENT_BYTES as u64)
+        .read_until(b'\n', &mut line)
+        .await
+        .map_err(|e| e.to_string())?;
+    if read == 0 {
+        return Ok(None);
+    }
+    let header: Value =
+        serde_json::from_slice(&line).map_err(|e| format!("Invalid event header: {}", e))?;
+    let kind = header
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or("Event header has no type")?;
+    let length = |key: &str| -> Result<usize, String> {
+        let n = header.get(key).and_then(Value::as_u64).unwrap_or(0) as usize;
+        if n > MAX_EVENT_BYTES {
+            return Err(format!("{} of {} bytes is too large", key, n));
+        }
+        Ok(n)
+    };
+    let (data_length, payload_length) = (length("data_length")?, length("payload_length")?);
+
+    let mut event = Event::new(kind, header.get("data").cloned().unwrap_or_default());
+    if data_length > 0 {
+        let mut bytes = vec![0; data_length];
+        reader
+            .read_exact(&mut bytes)
+            .await
+            .map_err(|e| e.to_string())?;
+        match serde_json::from_slice(&bytes) {
+            Ok(Value::Object(extra)) => event.data.extend(extra),
+            _ => return Err("Event data is not a JSON object".to_string()),
+        }
+    }
+    if payload_length > 0 {
+        event.payload = vec![0; payload_length];
+        reader
+            .read_exact(&mut event.payload)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(Some(event))
+}
+
+pub async fn write_event<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    event: &Event,
+) -> Result<(), String> {
+    let data = serde_json::to_vec(&event.data).map_err(|e| e.to_string())?;
+    let mut header = json!({ "type": event.kind, "version": PROTOCOL_VERSION });
+    if !event.data.is_empty() {
+        header["data_length"] = json!(data.len());
+    }
+    if !event.payload.is_empty() {
+        header["payload_length"] = json!(event.payload.len());
+    }
+    let mut bytes = serde_json::to_vec(&header).map_err(|e| e.to_string())?;
+    bytes.push(b'\n');
+    if !event.data.is_empty() {
+        bytes.extend_from_slice(&data);
+    }
+    bytes.extend_from_slice(&event.payload);
+    writer.write_all(&bytes).await.map_err(|e| e.to_string())?;
+    writer.flush().await.map_err(|e| e.to_string())
+}
+
+/// A model advertised in the `info` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AsrModel {
+    pub name: String,
+    pub description: String,
+    pub languages: Vec<String>,
+}
+
+/// Transcription behind the server. The app implements this over the loaded
+/// `TranscriptionEngine`; calls block, so the server runs them off the async
+/// runtime.
+pub trait WyomingBackend: Send + Sync {
+    /// Models requests can use: the loaded one, if any.
+    fn models(&self) -> Vec<AsrModel>;
+    /// Transcribe 16kHz mono audio; `None` uses the configured language.
+    fn transcribe(&self, audio: &[f32], language: Option<&str>) -> Result<String, String>;
+}
+
+fn info(models: Vec<AsrModel>) -> Event {
+    let attribution = json!({ "name": "OpenAI", "url": "https://github.com/openai/whisper" });
+    let models: Vec<Value> = models
+        .into_iter()
+        .map(|m| {
+            json!({
+                "name": m.name,
+                "description": m.description,
+                "attribution": attribution,
+                "installed": true,
+                "version": null,
+                "languages": m.languages,
+            })
+        })
+        .collect();
+    Event::new(
+        "info",
+        json!({
+            "asr": [{
+                "name": "whispertype",
+                "description": "WhisperType local speech-to-text",
+                "attribution": {
+                    "name": "WhisperType",
+                    "url": env!("CARGO_PKG_HOMEPAGE"),
+                },
+                "installed": true,
+                "version": env!("CARGO_PKG_VERSION"),
+                "models": models,
+            }],
+            "tts": [],
+            "handle": [],
+            "intent": [],
+            "wake": [],
+            "mic": [],
+            "snd": [],
+        }),
+    )
+}
+
+fn error(text: impl Into<String>, code: &str) -> Event {
+    Event::new("error", json!({ "text": text.into(), "code": code }))
+}
+
+/// Audio format of `audio-start`/`audio-chunk` events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PcmFormat {
+    rate: u32,
+    width: u16,
+    channels: u16,
+}
+
+impl PcmFormat {
+    fn from_event(event: &Event) -> Self {
+        Self {
+            rate: event.u64("rate").unwrap_or(TARGET_RATE as u64) as u32,
+            width: event.u64("width").unwrap_or(2) as u16,
+            channels: event.u64("channels").unwrap_or(1).max(1) as u16,
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match self.width {
+            2 | 4 => Ok(()),
+            width => Err(format!("Unsupported sample width: {} bytes", width)),
+        }
+    }
+
+    /// Bytes per second of audio.
+    fn byte_rate(&self) -> u64 {
+        self.rate as u64 * self.width as u64 * self.channels as u64
+    }
+
+    /// Convert little-endian PCM to 16kHz mono f32.
+    fn decode(&self, pcm: &[u8]) -> Result<Vec<f32>, String> {
+        self.check()?;
+        let samples: Vec<f32> = match self.width {
+            2 => pcm
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                .collect(),
+            4 => pcm
+                .chunks_exact(4)
+                .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0)
+                .collect(),
+            width => return Err(format!("Unsupported sample width: {} bytes", width)),
+        };
+        let mono = to_mono(&samples, self.channels);
+        Ok(resample(&mono, self.rate, TARGET_RATE))
+    }
+}
+
+/// State of one client connection.
+#[derive(Default)]
+struct Session {
+    /// Model named by `transcribe`; must be one of the backend's models.
+    model: Option<String>,
+    language: Option<String>,
+    /// Format of the current stream, set by its first chunk.
+    format: Option<PcmFormat>,
+    /// Raw PCM of the current stream, decoded and resampled once at
+    /// `audio-stop`.
+    pcm: Vec<u8>,
+    /// The stream went over `MAX_STREAM_SECS`; its remaining chunks are
+    /// dropped.
+    too_long: bool,
+}
+
+impl Session {
+    fn reset_stream(&mut self) {
+        self.format = None;
+        self.pcm = Vec::new();
+        self.too_long = false;
+    }
+
+    /// Buffer an `audio-chunk`, or return the error to send.
+    fn add_chunk(&mut self, event: &Event) -> Result<(), Event> {
+        if self.too_long {
+            return Ok(());
+        }
+        let format = PcmFormat::from_event(event);
+        format.check().map_err(|e| error(e, "audio-format"))?;
+        match self.format {
+            Some(current) if current != format => {
+                return Err(error("Audio format changed mid-stream", "audio-format"));
+            }
+            _ => self.format = Some(format),
+        }
+        let len = (self.pcm.len() + event.payload.len()) as u64;
+        if len > format.byte_rate() * MAX_STREAM_SECS {
+            self.pcm = Vec::new();
+            self.too_long = true;
+            return Err(error(
+                format!("Audio is longer than {} seconds", MAX_STREAM_SECS),
+                "audio-too-long",
+            ));
+        }
+        self.pcm.extend_from_slice(&event.payload);
+        Ok(())
+    }
+
+    /// Handle one event and return the reply, if any.
+    async fn handle(&mut self, event: Event, backend: &Arc<dyn WyomingBackend>) -> Option<Event> {
+        match event.kind.as_str() {
+            "describe" => {
+                let backend = backend.clone();
+                let models = tokio::task::spawn_blocking(move || backend.models())
+                    .await
+                    .unwrap_or_default();
+                Some(info(models))
+            }
+            "ping" => Some(Event::new("pong", json!({ "text": event.str("text") }))),
+            "transcribe" => {
+                self.model = event.str("name").map(str::to_string);
+                self.language = event.str("language").map(str::to_string);
+                None
+            }
+            "audio-start" => {
+                self.reset_stream();
+                None
+            }
+            "audio-chunk" => self.add_chunk(&event).err(),
+            "audio-stop" => {
+                let too_long = self.too_long;
+                let (format, pcm) = (self.format, std::mem::take(&mut self.pcm));
+                self.reset_stream();
+                let model = self.model.take();
+                let language = self.language.take();
+                if too_long {
+                    // The error was sent when the limit was reached.
+                    return None;
+                }
+                if let Some(name) = model {
+                    let models = {
+                        let backend = backend.clone();
+                        tokio::task::spawn_blocking(move || backend.models())
+                            .await
+                            .unwrap_or_default()
+                    };
+                    if !models.iter().any(|m| m.name == name) {
+                        return Some(error(
+                            format!("Model '{}' is not loaded", name),
+                            "unknown-model",
+                        ));
+                    }
+                }
+                let audio = match format.map(|f| f.decode(&pcm)) {
+                    Some(Ok(audio)) => audio,
+                    Some(Err(e)) => return Some(error(e, "audio-format")),
+                    None => Vec::new(),
+                };
+                let backend = backend.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    backend.transcribe(&audio, language.as_deref())
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r);
+                Some(match result {
+                    Ok(text) => Event::new("transcript", json!({ "text": text })),
+                    Err(e) => error(e, "transcription-failed"),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+async fn serve_client(
+    stream: TcpStream,
+    backend: Arc<dyn WyomingBackend>,
+    shutdown: CancellationToken,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut session = Session::default();
+    loop {
+        let event = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            event = read_event(&mut reader) => event,
+        };
+        let event = match event {
+            Ok(Some(event)) => event,
+            Ok(None) => break,
+            Err(e) => {
+                // The stream is out of sync after a malformed event.
+                let _ = write_event(&mut writer, &error(e, "bad-event")).await;
+                break;
+            }
+        };
+        if let Some(reply) = session.handle(event, &backend).await {
+            if write_event(&mut writer, &reply).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Handle to a running server; dropping it stops the server and closes
+/// client connections.
+pub struct WyomingServer {
+    addr: SocketAddr,
+    shutdown: CancellationToken,
+}
+
+impl WyomingServer {
+    /// Bind `host:port` (port 0 picks a free port) and return the handle plus
+    /// the future that accepts clients, to be spawned on the async runtime.
+    pub fn bind(
+        host: &str,
+        port: u16,
+        backend: Arc<dyn WyomingBackend>,
+    ) -> Result<(Self, impl Future<Output = ()> + Send), String> {
+        let listener = std::net::TcpListener::bind((host, port))
+            .map_err(|e| format!("Failed to bind {}:{}: {}", host, port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+
+        let shutdown = CancellationToken::new();
+        let stopped = shutdown.clone();
+        let serve = async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    eprintln!("wyoming: server stopped: {}", e);
+                    return;
+                }
+            };
+            loop {
+                tokio::select! {
+                    _ = stopped.cancelled() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            tokio::spawn(serve_client(stream, backend.clone(), stopped.clone()));
+                        }
+                        Err(e) => eprintln!("wyoming: accept failed: {}", e),
+                    },
+                }
+            }
+        };
+        Ok((Self { addr, shutdown }, serve))
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for WyomingServer {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::io::BufReader;
+    use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+    /// Reports the number of samples and language it was given.
+    #[derive(Default)]
+    struct FakeBackend {
+        calls: Mutex<Vec<(usize, Option<String>)>>,
+    }
+
+    impl WyomingBackend for FakeBackend {
+        fn models(&self) -> Vec<AsrModel> {
+            vec![AsrModel {
+                name: "base".to_string(),
+                description: "Base".to_string(),
+                languages: vec!["en".to_string(), "de".to_string()],
+            }]
+        }
+
+        fn transcribe(&self, audio: &[f32], language: Option<&str>) -> Result<String, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((audio.len(), language.map(str::to_string)));
+            if audio.is_empty() {
+                return Err("No audio".to_string());
+            }
+            Ok(format!("{} samples", audio.len()))
+        }
+    }
+
+    struct Client {
+        reader: BufReader<OwnedReadHalf>,
+        writer: OwnedWriteHalf,
+    }
+
+    impl Client {
+        async fn connect(addr: SocketAddr) -> Self {
+            let (reader, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+            Self {
+                reader: BufReader::new(reader),
+                writer,
+            }
+        }
+
+        async fn send(&mut self, event: Event) {
+            write_event(&mut self.writer, &event).await.unwrap();
+        }
+
+        async fn recv(&mut self) -> Event {
+            read_event(&mut self.reader).await.unwrap().unwrap()
+        }
+    }
+
+    fn serve() -> (WyomingServer, Arc<FakeBackend>) {
+        let backend = Arc::new(FakeBackend::default());
+        let (server, future) = WyomingServer::bind("127.0.0.1", 0, backend.clone()).unwrap();
+        tokio::spawn(future);
+        (server, backend)
+    }
+
+    fn chunk(rate: u32, channels: u16, samples: &[i16]) -> Event {
+        let mut event = Event::new(
+            "audio-chunk",
+            json!({ "rate": rate, "width": 2, "channels": channels }),
+        );
+        event.payload = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        event
+    }
+
+    // --- Framing Tests ---
+
+    #[tokio::test]
+    async fn test_event_roundtrip() {
+        let mut event = Event::new("audio-chunk", json!({ "rate": 16000 }));
+        event.payload = vec![1, 2, 3, 4];
+        let mut bytes = Vec::new();
+        write_event(&mut bytes, &event).await.unwrap();
+
+        let header_end = bytes.iter().position(|&b| b == b'\n').unwrap();
+        let header: Value = serde_json::from_slice(&bytes[..header_end]).unwrap();
+        assert_eq!(header["type"], "audio-chunk");
+        assert_eq!(header["payload_length"], 4);
+
+        let mut reader = &bytes[..];
+        assert_eq!(read_event(&mut reader).await.unwrap(), Some(event));
+        assert_eq!(read_event(&mut reader).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_inline_and_separate_data() {
+        let bytes = b"{\"type\":\"transcribe\",\"data\":{\"name\":\"base\"},\"data_length\":17}\n{\"language\":\"de\"}";
+        let event = read_event(&mut &bytes[..]).await.unwrap().unwrap();
+        assert_eq!(event.kind, "transcribe");
+        assert_eq!(event.str("name"), Some("base"));
+        assert_eq!(event.str("language"), Some("de"));
+    }
+
+    #[tokio::test]
+    async fn test_read_rejects_bad_header() {
+        assert!(read_event(&mut &b"not json\n"[..]).await.is_err());
+        assert!(read_event(&mut &b"{\"data\":{}}\n"[..]).await.is_err());
+    }
+
+    #[test]
+    fn test_pcm_decode_downmixes_and_resamples() {
+        let format = PcmFormat {
+            rate: 32000,
+            width: 2,
+            channels: 2,
+        };
+        let pcm: Vec<u8> = [16384i16, 0]
+            .repeat(3200)
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let samples = format.decode(&pcm).unwrap();
+        assert_eq!(samples.len(), 1600);
+        assert!((samples[0] - 0.25).abs() < 1e-6);
+
+        let format = PcmFormat { width: 3, ..format };
+        assert!(format.decode(&pcm).is_err());
+    }
+
+    // --- Server Tests ---
+
+    #[tokio::test]
+    async fn test_describe_lists_models_and_languages() {
+        let (server, _) = serve();
+        let mut client = Client::connect(server.addr()).await;
+        client.send(Event::new("describe", json!({}))).await;
+        let info = client.recv().await;
+        assert_eq!(info.kind, "info");
+        let asr = &info.data["asr"][0];
+        assert_eq!(asr["name"], "whispertype");
+        assert_eq!(asr["installed"], true);
+        assert_eq!(asr["models"][0]["name"], "base");
+        assert_eq!(asr["models"][0]["languages"], json!(["en", "de"]));
+    }
+
+    #[tokio::test]
+    async fn test_transcribes_audio_stream() {
+        let (server, backend) = serve();
+        let mut client = Client::connect(server.addr()).await;
+        client
+            .send(Event::new(
+                "transcribe",
+                json!({ "name": "base", "language": "de" }),
+            ))
+            .await;
+        client
+            .send(Event::new(
+                "audio-start",
+                json!({ "rate": 8000, "width": 2, "channels": 1 }),
+            ))
+            .await;
+        client.send(chunk(8000, 1, &[1000; 4000])).await;
+        client.send(chunk(8000, 1, &[1000; 4000])).await;
+        client.send(Event::new("audio-stop", json!({}))).await;
+
+        let transcript = client.recv().await;
+        assert_eq!(transcript.kind, "transcript");
+        assert_eq!(transcript.str("text"), Some("16000 samples"));
+
+        // The language applies to one request only.
+        client.send(Event::new("audio-start", json!({}))).await;
+        client.send(chunk(16000, 1, &[0; 1600])).await;
+        client.send(Event::new("audio-stop", json!({}))).await;
+        assert_eq!(client.recv().await.str("text"), Some("1600 samples"));
+
+        assert_eq!(
+            *backend.calls.lock().unwrap(),
+            vec![(16000, Some("de".to_string())), (1600, None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_model_that_is_not_loaded() {
+        let (server, backend) = serve();
+        let mut client = Client::connect(server.addr()).await;
+        client
+            .send(Event::new("transcribe", json!({ "name": "large-v3" })))
+            .await;
+        client.send(Event::new("audio-start", json!({}))).await;
+        client.send(chunk(16000, 1, &[1000; 1600])).await;
+        client.send(Event::new("audio-stop", json!({}))).await;
+        let reply = client.recv().await;
+        assert_eq!(reply.kind, "error");
+        assert_eq!(reply.str("code"), Some("unknown-model"));
+        assert!(backend.calls.lock().unwrap().is_empty());
+
+        // The model applies to one request only.
+        client.send(Event::new("audio-start", json!({}))).await;
+        client.send(chunk(16000, 1, &[1000; 1600])).await;
+        client.send(Event::new("audio-stop", json!({}))).await;
+        assert_eq!(client.recv().await.str("text"), Some("1600 samples"));
+    }
+
+    #[tokio::test]
+    async fn test_resamples_whole_stream_once() {
+        let (server, backend) = serve();
+        let mut client = Client::connect(server.addr()).await;
+        client.send(Event::new("audio-start", json!({}))).await;
+        // 1000 samples at 22.05kHz is not a whole number of 16kHz samples,
+        // so resampling each chunk on its own would drop some.
+        for _ in 0..22 {
+            client.send(chunk(22050, 1, &[1000; 1000])).await;
+        }
+        client.send(Event::new("audio-stop", json!({}))).await;
+        assert_eq!(client.recv().await.kind, "transcript");
+        assert_eq!(backend.calls.lock().unwrap()[0].0, 22000 * 16000 / 22050);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_overlong_stream() {
+        let (server, backend) = serve();
+        let mut client = Client::connect(server.addr()).await;
+        // At 100 Hz the limit is 30000 samples.
+        client.send(Event::new("audio-start", json!({}))).await;
+        client.send(chunk(100, 1, &[0; 20000])).await;
+        client.send(chunk(100, 1, &[0; 20000])).await;
+        let reply = client.recv().await;
+        assert_eq!(reply.kind, "error");
+        assert_eq!(reply.str("code"), Some("audio-too-long"));
+
+        // Later chunks and the stop are dropped without another reply.
+        client.send(chunk(100, 1, &[0; 100])).await;
+        client.send(Event::new("audio-stop", json!({}))).await;
+        client.send(Event::new("ping", json!({}))).await;
+        assert_eq!(client.recv().await.kind, "pong");
+        assert!(backend.calls.lock().unwrap().is_empty());
+
+        // The next stream starts fresh.
+        client.send(Event::new("audio-start", json!({}))).await;
+        client.send(chunk(100, 1, &[0; 200])).await;
+        client.send(Event::new("audio-stop", json!({}))).await;
+        assert_eq!(client.recv().await.str("text"), Some("32000 samples"));
+    }
+
+    #[tokio::test]
+    async fn test_reports_errors() {
+        let (server, _) = serve();
+        let mut client = Client::connect(server.addr()).await;
+
+        let mut bad = chunk(16000, 1, &[0; 10]);
+        bad.data.insert("width".to_string(), json!(3));
+        client.send(bad).await;
+        let reply = client.recv().await;
+        assert_eq!(reply.kind, "error");
+        assert_eq!(reply.str("code"), Some("audio-format"));
+
+        client.send(Event::new("audio-stop", json!({}))).await;
+        let reply = client.recv().await;
+        assert_eq!(reply.kind, "error");
+        assert_eq!(reply.str("text"), Some("No audio"));
+
+        client
+            .send(Event::new("ping", json!({ "text": "hi" })))
+            .await;
+        assert_eq!(client.recv().await.kind, "pong");
+    }
+
+    #[tokio::test]
+    async fn test_server_closes_clients_when_dropped() {
+        let (server, _) = serve();
+        let mut client = Client::connect(server.addr()).await;
+        client.send(Event::new("ping", json!({}))).await;
+        client.recv().await;
+        drop(server);
+        let next = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            read_event(&mut client.reader),
+        )
+        .await
+        .unwrap();
+        assert_eq!(next.unwrap(), None);
+    }
+}
@@ -24,6 +24,9 @@ export function ModelSelector({ models, activeModel, onSelect }: ModelSelectorPr
           <option key={model.id} value={model.id} disabled={!model.downloaded}>
             {model.display_name}
             {!model.downloaded ? ' (not downloaded)' : ''}
+            {model.downloaded && model.measured_rtf != null
+              ? ` · ${model.measured_rtf.toFixed(2)}× RTF`
+              : ''}
           </option>
         ))}
       </select>
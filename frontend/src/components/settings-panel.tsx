@@ -58,9 +58,71 @@ export function SettingsPanel({ onClose }: SettingsPanelProps) {
             <option value="clipboard" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
               Clipboard Only
             </option>
+            <option value="file" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+              Append to File
+            </option>
+            <option value="socket" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+              Local Socket
+            </option>
+            <option value="paste" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+              Paste (keeps clipboard)
+            </option>
           </select>
         </div>
 
+        {/* Paste Shortcut */}
+        {localConfig.output_mode === 'paste' && (
+          <div>
+            <label className="text-xs font-medium uppercase tracking-wider text-muted-foreground block mb-2">
+              Paste Shortcut
+            </label>
+            <select
+              value={localConfig.paste_shortcut}
+              onChange={(e) => setLocalConfig({ ...localConfig, paste_shortcut: e.target.value })}
+              className="w-full bg-[#18181b] border border-white/[0.08] rounded-md px-3 py-2 text-sm text-foreground outline-none"
+            >
+              <option value="Ctrl+V" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+                Ctrl+V
+              </option>
+              <option value="Ctrl+Shift+V" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+                Ctrl+Shift+V (terminals)
+              </option>
+              <option value="Shift+Insert" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+                Shift+Insert
+              </option>
+            </select>
+          </div>
+        )}
+
+        {/* Live Rewrite */}
+        {(localConfig.output_mode === 'type_into_field' || localConfig.output_mode === 'both') && (
+          <div>
+            <label className="text-xs font-medium uppercase tracking-wider text-muted-foreground block mb-2">
+              Live Rewrite
+            </label>
+            <select
+              value={localConfig.live_rewrite}
+              onChange={(e) =>
+                setLocalConfig({
+                  ...localConfig,
+                  live_rewrite: e.target.value as Config['live_rewrite'],
+                })
+              }
+              className="w-full bg-[#18181b] border border-white/[0.08] rounded-md px-3 py-2 text-sm text-foreground outline-none"
+            >
+              <option value="off" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+                Off (confirmed words only)
+              </option>
+              <option value="backspace" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+                Correct with Backspace
+              </option>
+              <option value="select" style={{ backgroundColor: '#18181b', color: '#fafafa' }}>
+                Correct by selecting
+              </option>
+            </select>
+          </div>
+        )}
+
         {/* Audio Device */}
         <div>
           <label className="text-xs font-medium uppercase tracking-wider text-muted-foreground block mb-2">
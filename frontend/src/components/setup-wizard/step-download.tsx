@@ -8,6 +8,7 @@ interface StepDownloadProps {
 
 export function StepDownload({ models, onNext }: StepDownloadProps) {
   const [progress, setProgress] = useState<Record<string, number>>({});
+  const [retrying, setRetrying] = useState<Record<string, number>>({});
   const [currentModel, setCurrentModel] = useState<string | null>(null);
   const [completed, setCompleted] = useState<string[]>([]);
   const [error, setError] = useState<string | null>(null);
@@ -15,6 +16,10 @@ export function StepDownload({ models, onNext }: StepDownloadProps) {
   useEffect(() => {
     const unlisten = events.onDownloadProgress((data) => {
       setProgress((prev) => ({ ...prev, [data.model_id]: data.percent }));
+      setRetrying((prev) => ({
+        ...prev,
+        [data.model_id]: data.status === 'retrying' ? data.attempt : 0,
+      }));
     });
 
     const downloadAll = async () => {
@@ -58,6 +63,7 @@ export function StepDownload({ models, onNext }: StepDownloadProps) {
           const pct = progress[modelId] ?? 0;
           const done = completed.includes(modelId);
           const active = currentModel === modelId;
+          const retryAttempt = retrying[modelId] ?? 0;
 
           return (
             <div key={modelId} className="space-y-1.5">
@@ -66,7 +72,13 @@ export function StepDownload({ models, onNext }: StepDownloadProps) {
                   {modelId}
                 </span>
                 <span className="text-xs text-muted-foreground">
-                  {done ? 'Done' : active ? `${pct.toFixed(0)}%` : 'Waiting...'}
+                  {done
+                    ? 'Done'
+                    : active
+                      ? retryAttempt > 0
+                        ? `${pct.toFixed(0)}% (retrying, attempt ${retryAttempt + 1})`
+                        : `${pct.toFixed(0)}%`
+                      : 'Waiting...'}
                 </span>
               </div>
               <div className="w-full h-1.5 bg-white/[0.05] rounded-full overflow-hidden">
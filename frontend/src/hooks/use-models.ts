@@ -27,9 +27,19 @@ export function useModels() {
         refresh();
       }
     });
+    const unlistenChanged = events.onModelsChanged(() => refresh());
+    const unlistenLoad = events.onModelLoad((data) => {
+      if (data.status === 'loaded') {
+        setActiveModel(data.model_id);
+      } else if (data.status === 'failed') {
+        console.error(`Failed to load model ${data.model_id}:`, data.error);
+      }
+    });
 
     return () => {
       unlisten.then((fn) => fn());
+      unlistenChanged.then((fn) => fn());
+      unlistenLoad.then((fn) => fn());
     };
   }, [refresh]);
 
@@ -54,6 +64,14 @@ export function useModels() {
     [refresh]
   );
 
+  const cancelDownload = useCallback(async (modelId: string) => {
+    try {
+      await commands.cancelDownload(modelId);
+    } catch (err) {
+      console.error('Failed to cancel download:', err);
+    }
+  }, []);
+
   const deleteModel = useCallback(
     async (modelId: string) => {
       try {
@@ -66,5 +84,14 @@ export function useModels() {
     [refresh]
   );
 
-  return { models, activeModel, loadModel, downloadModel, deleteModel, refresh, loading };
+  return {
+    models,
+    activeModel,
+    loadModel,
+    downloadModel,
+    cancelDownload,
+    deleteModel,
+    refresh,
+    loading,
+  };
 }
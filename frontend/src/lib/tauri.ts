@@ -8,7 +8,7 @@ export interface Config {
   version: number;
   hotkey: string;
   default_model: string;
-  output_mode: 'type_into_field' | 'clipboard' | 'both';
+  output_mode: OutputMode;
   audio_device: string | null;
   language: string;
   vad_threshold: number;
@@ -18,6 +18,76 @@ export interface Config {
   downloaded_models: string[];
   first_run_complete: boolean;
   stream_engine: 'whisper_only' | 'moonshine';
+  max_concurrent_downloads: number;
+  custom_models: CustomModel[];
+  model_mirror: string | null;
+  model_catalog_url: string | null;
+  models_location: string | null;
+  compute_device: ComputeDevice;
+  n_threads: number;
+  gpu_device: number;
+  flash_attn: boolean;
+  additional_outputs: OutputMode[];
+  output_file: string;
+  output_socket: string;
+  typing_backend: TypingBackend;
+  paste_shortcut: string;
+  live_rewrite: LiveRewrite;
+  format: FormatRules;
+  profiles: OutputProfile[];
+  api_enabled: boolean;
+  api_port: number;
+  api_token: string;
+  wyoming_enabled: boolean;
+  wyoming_host: string;
+  wyoming_port: number;
+}
+
+export type OutputMode =
+  | 'type_into_field'
+  | 'clipboard'
+  | 'both'
+  | 'file'
+  | 'stdout'
+  | 'socket'
+  | 'paste';
+
+export type ComputeDevice = 'auto' | 'gpu' | 'cpu';
+
+export type TypingBackend = 'auto' | 'enigo' | 'wtype' | 'ydotool';
+
+export type LiveRewrite = 'off' | 'backspace' | 'select';
+
+export interface FormatRules {
+  capitalize: boolean;
+  suffix: string;
+  normalize: boolean;
+  locale: string | null;
+}
+
+export interface FormatOverrides {
+  capitalize?: boolean | null;
+  suffix?: string | null;
+  normalize?: boolean | null;
+  locale?: string | null;
+}
+
+export interface OutputProfile {
+  name: string;
+  window_class: string | null;
+  window_title: string | null;
+  output_mode: OutputMode | null;
+  paste_shortcut: string | null;
+  format: FormatOverrides | null;
+  language: string | null;
+  model: string | null;
+}
+
+export interface CustomModel {
+  id: string;
+  display_name: string;
+  source: string;
+  sha256: string | null;
 }
 
 export interface ModelInfo {
@@ -27,6 +97,94 @@ export interface ModelInfo {
   size_bytes: number;
   vram_mb: number;
   downloaded: boolean;
+  custom: boolean;
+  measured_rtf: number | null;
+}
+
+export interface LatencyStats {
+  min_ms: number;
+  mean_ms: number;
+  p50_ms: number;
+  p90_ms: number;
+  p95_ms: number;
+  p99_ms: number;
+  max_ms: number;
+}
+
+export interface ModelBench {
+  model_id: string;
+  display_name: string;
+  model_type: 'whisper_ggml' | 'moonshine_onnx';
+  device: 'gpu' | 'cpu';
+  load_ms: number;
+  passes: number;
+  latency: LatencyStats;
+  rtf: number;
+  peak_rss_mb: number | null;
+  transcript: string;
+}
+
+export interface BenchReport {
+  app_version: string;
+  created_at: number;
+  clip: string;
+  audio_secs: number;
+  hardware: HardwareInfo;
+  results: ModelBench[];
+  failures: { model_id: string; error: string }[];
+}
+
+export interface BenchmarkProgress {
+  model_id: string;
+  index: number;
+  total: number;
+}
+
+export type ImportMode = 'copy' | 'link';
+
+export interface BundleImport {
+  imported: string[];
+  skipped: string[];
+}
+
+export interface StorageEntry {
+  path: string;
+  size_bytes: number;
+  model_id: string | null;
+}
+
+export interface StorageReport {
+  models_dir: string;
+  models: { id: string; display_name: string; path: string; size_bytes: number; complete: boolean }[];
+  orphans: StorageEntry[];
+  unknown: StorageEntry[];
+  total_bytes: number;
+}
+
+export interface CleanupResult {
+  removed: string[];
+  freed_bytes: number;
+}
+
+export interface ModelLoadEvent {
+  model_id: string;
+  status: 'loading' | 'loaded' | 'failed';
+  elapsed_ms: number;
+  error: string | null;
+  engine: EngineMode | null;
+}
+
+export interface EngineMode {
+  requested_device: 'gpu' | 'cpu';
+  gpu_device: number | null;
+  flash_attn: boolean;
+  flash_attn_fallback: boolean;
+  n_threads: number;
+}
+
+export interface ModelsChanged {
+  added: string[];
+  removed: string[];
 }
 
 export interface GpuInfo {
@@ -35,6 +193,33 @@ export interface GpuInfo {
   cuda_available: boolean;
 }
 
+export interface HardwareInfo {
+  cpu_model: string;
+  physical_cores: number;
+  logical_cores: number;
+  simd: string[];
+  total_ram_mb: number;
+  available_ram_mb: number;
+  gpu: { name: string; vram_total_mb: number; vram_free_mb: number } | null;
+  cuda_build: boolean;
+}
+
+export interface ModelRecommendation {
+  model_id: string;
+  display_name: string;
+  device: 'gpu' | 'cpu';
+  expected_rtf: number;
+  fits: boolean;
+}
+
+export interface Recommendations {
+  hardware: HardwareInfo;
+  device: 'gpu' | 'cpu';
+  n_threads: number;
+  models: ModelRecommendation[];
+  recommended: string | null;
+}
+
 export interface TranscriptionUpdate {
   text: string;
   is_partial: boolean;
@@ -45,22 +230,54 @@ export interface DownloadProgress {
   percent: number;
   downloaded_bytes: number;
   total_bytes: number;
+  status: 'downloading' | 'retrying';
+  attempt: number;
+  retry_in_ms: number;
 }
 
 // Commands
+export interface ApiStatus {
+  enabled: boolean;
+  running: boolean;
+  url: string | null;
+  token: string;
+}
+
 export const commands = {
   toggleDictation: () => invoke<boolean>('toggle_dictation'),
   startDictation: () => invoke<void>('start_dictation'),
   stopDictation: () => invoke<void>('stop_dictation'),
   listModels: () => invoke<ModelInfo[]>('list_models'),
   downloadModel: (modelId: string) => invoke<void>('download_model', { modelId }),
+  cancelDownload: (modelId: string) => invoke<boolean>('cancel_download', { modelId }),
   deleteModel: (modelId: string) => invoke<void>('delete_model', { modelId }),
   loadModel: (modelId: string) => invoke<void>('load_model', { modelId }),
   getActiveModel: () => invoke<string | null>('get_active_model'),
+  getLoadingModel: () => invoke<string | null>('get_loading_model'),
+  getEngineMode: () => invoke<EngineMode | null>('get_engine_mode'),
+  runBenchmark: (modelIds?: string[], clip?: string, passes?: number) =>
+    invoke<BenchReport>('run_benchmark', { modelIds, clip, passes }),
+  getBenchmarkResults: () => invoke<BenchReport | null>('get_benchmark_results'),
+  addCustomModel: (model: CustomModel) => invoke<ModelInfo>('add_custom_model', { model }),
+  removeCustomModel: (modelId: string) => invoke<void>('remove_custom_model', { modelId }),
+  refreshModelCatalog: () => invoke<number>('refresh_model_catalog'),
+  getStorageReport: () => invoke<StorageReport>('get_storage_report'),
+  cleanupModelsDir: () => invoke<CleanupResult>('cleanup_models_dir'),
+  setModelsLocation: (path: string | null) => invoke<void>('set_models_location', { path }),
+  importModel: (path: string, modelId?: string, mode?: ImportMode) =>
+    invoke<string>('import_model', { path, modelId, mode }),
+  exportModelBundle: (modelIds: string[], dest: string) =>
+    invoke<unknown>('export_model_bundle', { modelIds, dest }),
+  importModelBundle: (path: string, mode?: ImportMode) =>
+    invoke<BundleImport>('import_model_bundle', { path, mode }),
   getConfig: () => invoke<Config>('get_config'),
   updateConfig: (config: Config) => invoke<void>('update_config', { config }),
   listAudioDevices: () => invoke<string[]>('list_audio_devices'),
   getGpuInfo: () => invoke<GpuInfo>('get_gpu_info'),
+  getHardwareInfo: () => invoke<HardwareInfo>('get_hardware_info'),
+  recommendModels: () => invoke<Recommendations>('recommend_models'),
+  getApiStatus: () => invoke<ApiStatus>('get_api_status'),
+  regenerateApiToken: () => invoke<string>('regenerate_api_token'),
 };
 
 // Event Listeners
@@ -71,6 +288,12 @@ export const events = {
     listen<string>('dictation-status', (event) => handler(event.payload)),
   onDownloadProgress: (handler: (data: DownloadProgress) => void): Promise<UnlistenFn> =>
     listen<DownloadProgress>('download-progress', (event) => handler(event.payload)),
+  onModelLoad: (handler: (data: ModelLoadEvent) => void): Promise<UnlistenFn> =>
+    listen<ModelLoadEvent>('model-load', (event) => handler(event.payload)),
+  onBenchmarkProgress: (handler: (data: BenchmarkProgress) => void): Promise<UnlistenFn> =>
+    listen<BenchmarkProgress>('benchmark-progress', (event) => handler(event.payload)),
+  onModelsChanged: (handler: (data: ModelsChanged) => void): Promise<UnlistenFn> =>
+    listen<ModelsChanged>('models-changed', (event) => handler(event.payload)),
   onOutputError: (handler: (message: string) => void): Promise<UnlistenFn> =>
     listen<string>('output-error', (event) => handler(event.payload)),
   onTranscriptionError: (handler: (message: string) => void): Promise<UnlistenFn> =>